@@ -49,11 +49,11 @@ pub fn derive_dimension_builder(input: TokenStream) -> TokenStream {
 
 	let expanded = quote! {
 		impl DimensionBuilder for #name {
-			fn set_height(&mut self, v: i32) {
-				self.dimension.height = v;
+			fn get_dimension(&self) -> Dimension {
+				self.dimension
 			}
-			fn set_width(&mut self, v: i32) {
-				self.dimension.width = v;
+			fn set_dimension(&mut self, d: Dimension) {
+				self.dimension = d
 			}
 		}
 	};
@@ -65,9 +65,10 @@ pub fn derive_dimension_builder(input: TokenStream) -> TokenStream {
 pub fn derive_window_base(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 	let name = input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
 	let expanded = quote! {
-		impl WindowBase for #name {
+		impl #impl_generics WindowBase for #name #ty_generics #where_clause {
 			fn init_state(h_instance: HINSTANCE) -> Self {
 				Self { h_instance, ..Default::default()}
 			}
@@ -0,0 +1,363 @@
+// Keyboard shortcuts sending commands as menu items do. `Shortcut` parses and writes the text of a shortcut, e.g.
+// "Ctrl+Shift+Z", without Win32. `AcceleratorTable` pairs shortcuts with command ids for TranslateAcceleratorW, which
+// `MessageLoop` calls for the windows tables are attached to.
+
+use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc, str::FromStr};
+
+use windows::Win32::{
+	Foundation::HWND,
+	UI::{
+		Input::KeyboardAndMouse::*,
+		WindowsAndMessaging::{
+			CreateAcceleratorTableW, DestroyAcceleratorTable, GetAncestor, IsWindow,
+			TranslateAcceleratorW, ACCEL, FALT, FCONTROL, FSHIFT, FVIRTKEY, GA_ROOT, HACCEL, MSG,
+		},
+	},
+};
+
+use crate::assert::{assert_ne, Result, WithLastWin32Error};
+
+#[derive(Debug)]
+pub struct ParseShortcutError {
+	pub shortcut: String,
+}
+
+impl fmt::Display for ParseShortcutError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid shortcut {:?}", self.shortcut)
+	}
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+/// Key pressed along with modifiers, e.g. `"Ctrl+S".parse::<Shortcut>()`. Letters, digits, `F1` to `F24` and the
+/// keys of `KEY_NAMES` are known, case doesn't matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	/// Virtual-key code, e.g. `VK_DELETE`.
+	pub key: VIRTUAL_KEY,
+}
+
+/// Names of the keys other than letters, digits and function keys. The first name of a key is the one written.
+pub const KEY_NAMES: &[(&str, VIRTUAL_KEY)] = &[
+	("Enter", VK_RETURN),
+	("Return", VK_RETURN),
+	("Esc", VK_ESCAPE),
+	("Escape", VK_ESCAPE),
+	("Space", VK_SPACE),
+	("Tab", VK_TAB),
+	("Backspace", VK_BACK),
+	("Del", VK_DELETE),
+	("Delete", VK_DELETE),
+	("Ins", VK_INSERT),
+	("Insert", VK_INSERT),
+	("Home", VK_HOME),
+	("End", VK_END),
+	("PgUp", VK_PRIOR),
+	("PageUp", VK_PRIOR),
+	("PgDn", VK_NEXT),
+	("PageDown", VK_NEXT),
+	("Up", VK_UP),
+	("Down", VK_DOWN),
+	("Left", VK_LEFT),
+	("Right", VK_RIGHT),
+	("+", VK_OEM_PLUS),
+	("Plus", VK_OEM_PLUS),
+	("-", VK_OEM_MINUS),
+	("Minus", VK_OEM_MINUS),
+	(",", VK_OEM_COMMA),
+	(".", VK_OEM_PERIOD),
+];
+
+impl Shortcut {
+	pub fn new(key: VIRTUAL_KEY) -> Self {
+		Self {
+			ctrl: false,
+			shift: false,
+			alt: false,
+			key,
+		}
+	}
+
+	pub fn ctrl(mut self) -> Self {
+		self.ctrl = true;
+		self
+	}
+
+	pub fn shift(mut self) -> Self {
+		self.shift = true;
+		self
+	}
+
+	pub fn alt(mut self) -> Self {
+		self.alt = true;
+		self
+	}
+
+	/// Entry of an accelerator table sending `cmd` in a `WM_COMMAND`, as a menu item with this id would.
+	pub fn accel(self, cmd: u16) -> ACCEL {
+		let mut flags = FVIRTKEY;
+		if self.ctrl {
+			flags |= FCONTROL;
+		}
+		if self.shift {
+			flags |= FSHIFT;
+		}
+		if self.alt {
+			flags |= FALT;
+		}
+		ACCEL {
+			fVirt: flags as u8,
+			key: self.key,
+			cmd,
+		}
+	}
+}
+
+fn parse_key(name: &str) -> Option<VIRTUAL_KEY> {
+	if let Some((_, key)) = KEY_NAMES
+		.iter()
+		.find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+	{
+		return Some(*key);
+	}
+	let mut chars = name.chars();
+	match (chars.next(), chars.next()) {
+		// virtual-key codes of letters and digits are their uppercase ASCII code
+		(Some(c), None) if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as VIRTUAL_KEY),
+		(Some('F' | 'f'), Some(_)) => match name[1..].parse::<VIRTUAL_KEY>() {
+			Ok(n @ 1..=24) => Some(VK_F1 + n - 1),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+fn fmt_key(f: &mut fmt::Formatter, key: VIRTUAL_KEY) -> fmt::Result {
+	if let Some((name, _)) = KEY_NAMES.iter().find(|(_, k)| *k == key) {
+		return f.write_str(name);
+	}
+	match key {
+		0x30..=0x39 | 0x41..=0x5A => write!(f, "{}", key as u8 as char),
+		VK_F1..=VK_F24 => write!(f, "F{}", key - VK_F1 + 1),
+		_ => write!(f, "{:#04X}", key),
+	}
+}
+
+impl FromStr for Shortcut {
+	type Err = ParseShortcutError;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let err = || ParseShortcutError {
+			shortcut: s.to_owned(),
+		};
+		// the key is last, "Ctrl++" being Ctrl and the plus key
+		let (modifiers, key) = match s.trim_end().strip_suffix("++") {
+			Some(modifiers) => (modifiers, "+"),
+			None => s.rsplit_once('+').unwrap_or(("", s)),
+		};
+		let mut shortcut = Shortcut::new(parse_key(key.trim()).ok_or_else(err)?);
+		for modifier in modifiers.split('+').map(str::trim) {
+			match modifier.to_ascii_lowercase().as_str() {
+				"ctrl" | "control" => shortcut.ctrl = true,
+				"shift" => shortcut.shift = true,
+				"alt" => shortcut.alt = true,
+				// no modifiers
+				"" if modifiers.trim().is_empty() => {}
+				_ => return Err(err()),
+			}
+		}
+		Ok(shortcut)
+	}
+}
+
+/// Written as menus show shortcuts, modifiers first, e.g. `Ctrl+Shift+Z`.
+impl fmt::Display for Shortcut {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.ctrl {
+			f.write_str("Ctrl+")?;
+		}
+		if self.shift {
+			f.write_str("Shift+")?;
+		}
+		if self.alt {
+			f.write_str("Alt+")?;
+		}
+		fmt_key(f, self.key)
+	}
+}
+
+/// Text of a menu item followed by its shortcut, e.g. `"&Save\tCtrl+S"`: menus show what follows a tab aligned to the
+/// right.
+pub fn menu_text(text: &str, shortcut: &Shortcut) -> String {
+	format!("{}\t{}", text, shortcut)
+}
+
+/// Shortcuts of a window, each sending a `WM_COMMAND` with the id it's paired with and a hiword of 1. Destroyed when
+/// dropped.
+///
+/// ```ignore
+/// let table = AcceleratorTable::new(&[("Ctrl+S".parse()?, ID_SAVE), ("F5".parse()?, ID_REFRESH)])?;
+/// table.attach(h_window);
+/// ```
+#[derive(Debug)]
+pub struct AcceleratorTable {
+	h_accel: HACCEL,
+}
+
+impl AcceleratorTable {
+	pub fn new(shortcuts: &[(Shortcut, u16)]) -> Result<Self> {
+		let entries: Vec<ACCEL> = shortcuts
+			.iter()
+			.map(|(shortcut, cmd)| shortcut.accel(*cmd))
+			.collect();
+		let len = i32::try_from(entries.len()).map_err(|_| "too many shortcuts")?;
+		let h_accel = unsafe { CreateAcceleratorTableW(entries.as_ptr(), len) };
+		assert_ne(h_accel, 0, "failed to create accelerator table").with_last_win32_err()?;
+		Ok(Self { h_accel })
+	}
+
+	pub fn h_accel(&self) -> HACCEL {
+		self.h_accel
+	}
+
+	/// Send the command of the shortcut `msg` is the key press of to `h_window`, returns true if it was one.
+	pub fn translate(&self, h_window: HWND, msg: &MSG) -> bool {
+		unsafe { TranslateAcceleratorW(h_window, self.h_accel, msg) != 0 }
+	}
+
+	/// Translate the key presses of `h_window` and its children in `MessageLoop`, replacing the previous table of the
+	/// window. The table is dropped along with the window.
+	pub fn attach(self, h_window: HWND) {
+		let previous = TABLES.with(|tables| tables.borrow_mut().insert(h_window, Rc::new(self)));
+		drop(previous);
+	}
+}
+
+impl Drop for AcceleratorTable {
+	fn drop(&mut self) {
+		unsafe { DestroyAcceleratorTable(self.h_accel) };
+	}
+}
+
+thread_local! {
+	static TABLES: RefCell<BTreeMap<HWND, Rc<AcceleratorTable>>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Drop the table attached to `h_window`, its shortcuts aren't translated anymore.
+pub fn detach(h_window: HWND) {
+	let table = TABLES.with(|tables| tables.borrow_mut().remove(&h_window));
+	drop(table);
+}
+
+/// Let the table attached to the top-level window of `msg` translate it, returns true if it was a shortcut. Tables of
+/// destroyed windows are dropped.
+pub fn is_accelerator_message(msg: &MSG) -> bool {
+	if msg.hwnd == 0 {
+		return false;
+	}
+	let h_window = unsafe { GetAncestor(msg.hwnd, GA_ROOT) };
+	let (table, destroyed) = TABLES.with(|tables| {
+		let mut tables = tables.borrow_mut();
+		let mut destroyed = vec![];
+		tables.retain(|h_window, table| {
+			let alive = unsafe { IsWindow(*h_window) }.as_bool();
+			if !alive {
+				destroyed.push(table.clone());
+			}
+			alive
+		});
+		(tables.get(&h_window).cloned(), destroyed)
+	});
+	// dropped once the tables aren't borrowed
+	drop(destroyed);
+	// the command may attach another table to the window
+	table.is_some_and(|table| table.translate(h_window, msg))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(s: &str) -> Shortcut {
+		s.parse().unwrap()
+	}
+
+	#[test]
+	fn parse_modifiers() {
+		assert_eq!(parse("Ctrl+S"), Shortcut::new(b'S' as u16).ctrl());
+		assert_eq!(
+			parse("Ctrl+Shift+Z"),
+			Shortcut::new(b'Z' as u16).ctrl().shift()
+		);
+		assert_eq!(parse("alt+shift+ctrl+1"), parse("Ctrl+Shift+Alt+1"));
+		assert_eq!(parse(" Control + s "), parse("Ctrl+S"));
+	}
+
+	#[test]
+	fn parse_keys() {
+		assert_eq!(parse("F5"), Shortcut::new(VK_F5));
+		assert_eq!(parse("Shift+f24"), Shortcut::new(VK_F24).shift());
+		assert_eq!(parse("Delete"), Shortcut::new(VK_DELETE));
+		assert_eq!(parse("Ctrl+PageDown"), parse("Ctrl+PgDn"));
+		assert_eq!(parse("Ctrl++"), Shortcut::new(VK_OEM_PLUS).ctrl());
+		assert_eq!(parse("Ctrl+Plus"), parse("Ctrl++"));
+		assert_eq!(parse("Ctrl+-"), Shortcut::new(VK_OEM_MINUS).ctrl());
+	}
+
+	#[test]
+	fn parse_errors() {
+		for s in [
+			"", "Ctrl+", "Ctrl", "Ctrl+SS", "Win+S", "F0", "F25", "Ctrl++S", "+", "S+Ctrl",
+		] {
+			assert!(s.parse::<Shortcut>().is_err(), "{:?}", s);
+		}
+		let err = "Meta+S".parse::<Shortcut>().unwrap_err();
+		assert_eq!(err.to_string(), "invalid shortcut \"Meta+S\"");
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(parse("shift+ctrl+z").to_string(), "Ctrl+Shift+Z");
+		assert_eq!(parse("Alt+F4").to_string(), "Alt+F4");
+		assert_eq!(parse("Ctrl+Return").to_string(), "Ctrl+Enter");
+		assert_eq!(parse("Ctrl+Plus").to_string(), "Ctrl++");
+		assert_eq!(Shortcut::new(VK_NUMPAD0).to_string(), "0x60");
+	}
+
+	#[test]
+	fn format_parses_back() {
+		for s in [
+			"Ctrl+S",
+			"Ctrl+Shift+Z",
+			"Alt+F4",
+			"Ctrl+Del",
+			"Shift+Tab",
+			"Ctrl++",
+			"Ctrl+-",
+			"9",
+		] {
+			assert_eq!(parse(s).to_string(), s);
+		}
+	}
+
+	#[test]
+	fn menu_text_suffix() {
+		assert_eq!(menu_text("&Save", &parse("ctrl+s")), "&Save\tCtrl+S");
+		assert_eq!(
+			menu_text("&Redo", &parse("Ctrl+Shift+Z")),
+			"&Redo\tCtrl+Shift+Z"
+		);
+	}
+
+	#[test]
+	fn accel_flags() {
+		let accel = parse("Ctrl+Shift+S").accel(42);
+		assert_eq!(accel.fVirt, (FVIRTKEY | FCONTROL | FSHIFT) as u8);
+		assert_eq!((accel.key, accel.cmd), (b'S' as u16, 42));
+		assert_eq!(parse("F1").accel(1).fVirt, FVIRTKEY as u8);
+	}
+}
@@ -36,7 +36,7 @@ impl<T> WithLastWin32Error<T> for Result<T> {
 				let win_err = unsafe { GetLastError() };
 				Err(format!(
 					"{}\n\t→ Last win32 error: {:#?} ({:#X}).\n\t  See {}.",
-					e.to_string(),
+					e,
 					win_err,
 					win_err,
 					win32_error_codes_url(win_err),
@@ -55,7 +55,7 @@ impl<T> WrappedError<T> for Result<T> {
 	fn wrap_err(self, msg: &str) -> Result<T> {
 		match self {
 			Ok(i) => Ok(i),
-			Err(e) => Err(format!("{}: {}", msg, e.to_string(),).into()),
+			Err(e) => Err(format!("{}: {}", msg, e).into()),
 		}
 	}
 }
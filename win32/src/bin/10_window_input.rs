@@ -25,7 +25,7 @@ fn app() -> Result<()> {
 	)?;
 	display!("main_window: {:?}", main_window);
 
-	let res = MainWindow::event_loop();
+	let res = MainWindow::event_loop()?;
 	display!("event_loop result: {} ({:#X})", res, res);
 
 	Ok(())
@@ -43,7 +43,7 @@ impl App {
 		)?;
 		display!("main_window: {:?}", main_window);
 
-		let res = Self::event_loop();
+		let res = Self::event_loop()?;
 		display!("event_loop result: {} ({:#X})", res, res);
 
 		Ok(())
@@ -29,7 +29,7 @@ fn app() -> Result<()> {
 	)?;
 	display!("main_window: {:?}", main_window);
 
-	let res = MainWindow::event_loop();
+	let res = MainWindow::event_loop()?;
 	display!("event_loop result: {} ({:#X})", res, res);
 
 	Ok(())
@@ -341,7 +341,7 @@ impl Button {
 	}
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 struct Dimension {
 	height: i32,
 	width: i32,
@@ -351,16 +351,18 @@ trait DimensionBuilder
 where
 	Self: Sized,
 {
-	fn set_height(&mut self, v: i32);
-	fn set_width(&mut self, v: i32);
+	fn get_dimension(&self) -> Dimension;
+	fn set_dimension(&mut self, d: Dimension);
 
 	fn height(mut self, v: i32) -> Self {
-		self.set_height(v);
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { height: v, ..d });
 		self
 	}
 
 	fn width(mut self, v: i32) -> Self {
-		self.set_width(v);
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { width: v, ..d });
 		self
 	}
 }
@@ -373,6 +375,7 @@ struct Margin {
 	bottom: i32,
 }
 
+#[allow(dead_code)]
 trait MarginBuilder
 where
 	Self: Sized,
@@ -433,6 +436,7 @@ struct Padding {
 	bottom: i32,
 }
 
+#[allow(dead_code)]
 trait PaddingBuilder
 where
 	Self: Sized,
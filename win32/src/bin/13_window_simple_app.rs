@@ -1,11 +1,23 @@
 use gui::{
 	assert::Result,
+	display,
 	layout::{Button, DimensionBuilder, HStack, InputText, MarginBuilder, PaddingBuilder, VStack},
 	SimpleApp,
 };
 
+#[derive(Debug)]
+struct State {
+	text: String,
+	clicks: u32,
+}
+
 fn main() -> Result<()> {
-	let app = SimpleApp::new("Simple App Window — Win32 💖 Rust", || {
+	let state = State {
+		text: "hello".to_owned(),
+		clicks: 0,
+	};
+
+	let app = SimpleApp::new("Simple App Window — Win32 💖 Rust", state, |state| {
 		VStack::new()
 			.left_padding(10)
 			.spacing(10)
@@ -13,8 +25,17 @@ fn main() -> Result<()> {
 				HStack::new()
 					.spacing(10)
 					.items(vec![
-						InputText::new("hello").height(20).width(100).done(),
-						InputText::new("world").height(20).width(100).done(),
+						InputText::new(&state.text)
+							.height(20)
+							.width(100)
+							.on_change(|state: &mut State, text| state.text = text.to_owned())
+							.done(),
+						// mirrors the first input
+						InputText::new(&state.text)
+							.height(20)
+							.width(100)
+							.disabled(true)
+							.done(),
 					])
 					.done(),
 				HStack::new()
@@ -24,13 +45,18 @@ fn main() -> Result<()> {
 						InputText::new("world").height(20).width(100).done(),
 					])
 					.done(),
-				Button::new("My Button 1")
+				Button::new(&format!("Clicked {} times", state.clicks))
 					.height(40)
-					.width(100)
+					.width(120)
 					.left_margin(30)
+					.disabled(state.clicks >= 10)
+					.on_click(|state: &mut State| state.clicks += 1)
 					.done(),
 			])
 			.done()
 	});
-	app.run()
+
+	let state = app.run()?;
+	display!("final state: {:?}", state);
+	Ok(())
 }
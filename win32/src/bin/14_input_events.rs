@@ -1,15 +1,14 @@
 use derive::WindowBase;
 use gui::{
 	assert::{assert_ne, Result, WithLastWin32Error},
-	display, hiword,
-	input::{self, style},
-	loword,
+	display,
+	input::{self, style, TextAlign},
 	wide_string::ToWide,
-	window::{self, message, MessageAction, Options, WindowBase, WindowHandler},
+	window::{self, message, Message, MessageAction, Options, WindowBase, WindowHandler},
 };
 use windows::Win32::{
 	Foundation::{HINSTANCE, HWND},
-	UI::WindowsAndMessaging::{CreateWindowExW, SetWindowLongW, GWL_ID, WINDOW_STYLE},
+	UI::WindowsAndMessaging::{CreateWindowExW, SetWindowLongW, GWL_ID},
 };
 
 fn main() -> Result<()> {
@@ -42,7 +41,7 @@ impl App {
 		)?;
 		display!("main_window: {:?}", main_window);
 
-		let res = Self::event_loop();
+		let res = Self::event_loop()?;
 		display!("event_loop result: {} ({:#X})", res, res);
 
 		Ok(())
@@ -56,15 +55,20 @@ impl WindowHandler for App {
 		&mut self,
 		message: window::message::Type,
 		wparam: windows::Win32::Foundation::WPARAM,
-		_lparam: windows::Win32::Foundation::LPARAM,
+		lparam: windows::Win32::Foundation::LPARAM,
 	) -> Result<MessageAction> {
 		use MessageAction::*;
-		match message {
-			message::Create => return self.on_create(),
-			message::Command => {
-				let notif_code: input::event::Type = hiword(wparam).try_into().unwrap();
-				let control_id: u32 = loword(wparam).try_into().unwrap();
-				match notif_code {
+		match Message::decode(message, wparam, lparam) {
+			Message::Other {
+				message: message::Create,
+				..
+			} => return self.on_create(),
+			Message::Command {
+				id: control_id,
+				code,
+				..
+			} => {
+				match u32::from(code) {
 					input::event::AfterPaste => display!("edit #{}: event AfterPaste", control_id),
 					input::event::AlignLtrEc => display!("edit #{}: event AlignLtrEc", control_id),
 					input::event::AlignRtlEc => display!("edit #{}: event AlignRtlEc", control_id),
@@ -88,9 +92,10 @@ impl WindowHandler for App {
 	}
 
 	fn on_create(&self) -> Result<MessageAction> {
-		let styles = TryInto::<WINDOW_STYLE>::try_into(style::Left)
-			.expect("cannot cast to WINDOW_STYLE")
-			| (window::style::Visible | window::style::Child | window::style::Overlapped).0;
+		let styles = window::style::Type::from(style::Type::empty().with_align(TextAlign::Left))
+			| window::style::Visible
+			| window::style::Child
+			| window::style::Overlapped;
 		let ex_styles = window::ex_style::ClientEdge
 			| window::ex_style::Left
 			| window::ex_style::LtrReading
@@ -101,7 +106,7 @@ impl WindowHandler for App {
 				ex_styles.0,
 				EDIT_CLASS.to_wide().as_pwstr(),
 				"Type text".to_wide().as_pwstr(),
-				styles,
+				styles.0,
 				10,
 				10,
 				100,
@@ -120,7 +125,7 @@ impl WindowHandler for App {
 				ex_styles.0,
 				EDIT_CLASS.to_wide().as_pwstr(),
 				"Type text".to_wide().as_pwstr(),
-				styles,
+				styles.0,
 				10,
 				40,
 				100,
@@ -3,11 +3,9 @@
 use derive::WindowBase;
 use gui::{
 	assert::{assert_not_null, Result},
+	combo_box::{ComboBox, ComboBoxBuilder, ComboKind},
 	display, err_display,
-	window::{
-		message, style, MessageAction, Options, WinProc, WindowBase, WindowCreateData,
-		WindowHandler,
-	},
+	window::{message, MessageAction, Options, WinProc, WindowBase, WindowHandler},
 	window_long::{get_property, set_property, set_window_long_ptr},
 };
 use std::sync::Once;
@@ -15,10 +13,7 @@ use windows::Win32::{
 	Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
 	UI::{
 		Input::KeyboardAndMouse::{GetFocus, SetFocus, VK_ESCAPE, VK_RETURN, VK_TAB},
-		WindowsAndMessaging::{
-			CallWindowProcW, GetWindow, SendMessageW, CBS_DROPDOWN, CB_ADDSTRING, CB_ERR,
-			CB_FINDSTRINGEXACT, CB_GETCURSEL, CB_SETCURSEL, GWLP_WNDPROC, GW_CHILD, WINDOW_STYLE,
-		},
+		WindowsAndMessaging::{CallWindowProcW, SendMessageW, GWLP_WNDPROC},
 	},
 };
 
@@ -42,8 +37,8 @@ pub struct App {
 	h_window: HWND,
 	title: String,
 	edit_base_win_proc: Option<WinProc>,
-	h_combo1: Option<HWND>,
-	h_combo2: Option<HWND>,
+	combo1: Option<ComboBox>,
+	combo2: Option<ComboBox>,
 	h_edit1: Option<HWND>,
 	h_edit2: Option<HWND>,
 }
@@ -51,41 +46,22 @@ pub struct App {
 impl WindowHandler for App {
 	fn on_create_mut(&mut self) -> Result<MessageAction> {
 		// 1. create two combobox
-		let class_name = "COMBOBOX";
-		let combo_style: WINDOW_STYLE = CBS_DROPDOWN.try_into().unwrap();
-		let style = style::Child | style::Visible | style::Type(combo_style);
-
-		let combo1 = self.create_window(
-			class_name,
-			None,
-			None,
-			Some(style),
-			10,
-			10,
-			100,
-			50,
-			None,
-			Some(self.h_window),
-			WindowCreateData::None,
-		)?;
-
-		let combo2 = self.create_window(
-			class_name,
-			None,
-			None,
-			Some(style),
-			120,
-			10,
-			100,
-			50,
-			Some(self.h_instance),
-			Some(self.h_window),
-			WindowCreateData::None,
-		)?;
+		let combo1 = ComboBoxBuilder::new(self.h_window)
+			.kind(ComboKind::DropDown)
+			.at(10, 10)
+			.size(100, 50)
+			.create()?;
+
+		let combo2 = ComboBoxBuilder::new(self.h_window)
+			.kind(ComboKind::DropDown)
+			.at(120, 10)
+			.size(100, 50)
+			.h_instance(self.h_instance)
+			.create()?;
 
 		// 2. get the edit control handle from each combobox
-		let edit1 = unsafe { GetWindow(combo1, GW_CHILD) };
-		let edit2 = unsafe { GetWindow(combo2, GW_CHILD) };
+		let edit1 = combo1.edit()?.ok_or("no edit in combo box")?.h_window();
+		let edit2 = combo2.edit()?.ok_or("no edit in combo box")?.h_window();
 
 		// 3. change the win-proc for both edit handlers. Both base win-proc are the same, we only need one pointer.
 		//
@@ -104,13 +80,13 @@ impl WindowHandler for App {
 		set_property(edit2, APP_STATE_PROPERTY, self)?;
 
 		// 5. keep references to combobox, edits, and base win-proc
-		self.h_combo1 = Some(combo1);
-		self.h_combo2 = Some(combo2);
+		self.combo1 = Some(combo1);
+		self.combo2 = Some(combo2);
 		self.h_edit1 = Some(edit1);
 		self.h_edit2 = Some(edit2);
 
 		unsafe {
-			self.edit_base_win_proc = Some(std::mem::transmute(base_win_proc));
+			self.edit_base_win_proc = Some(std::mem::transmute::<isize, WinProc>(base_win_proc));
 		}
 
 		Ok(MessageAction::Continue)
@@ -122,7 +98,7 @@ impl WindowHandler for App {
 		_wparam: WPARAM,
 		_lparam: LPARAM,
 	) -> Result<MessageAction> {
-		use gui::window::MessageAction::*;
+		use gui::window::MessageAction::{Continue, FullyHandled};
 
 		match message {
 			message::Create => self.on_create_mut(),
@@ -130,7 +106,7 @@ impl WindowHandler for App {
 			// 6. set default focus to first combo box
 			message::Setfocus => {
 				display!("on_message => SetFocus");
-				unsafe { SetFocus(self.h_combo1.unwrap()) };
+				unsafe { SetFocus(self.combo1().h_window()) };
 				Ok(FullyHandled)
 			}
 
@@ -140,9 +116,9 @@ impl WindowHandler for App {
 				unsafe {
 					let focus = GetFocus();
 					if focus == self.h_edit1.unwrap() {
-						SetFocus(self.h_combo2.unwrap());
+						SetFocus(self.combo2().h_window());
 					} else if focus == self.h_edit2.unwrap() {
-						SetFocus(self.h_combo1.unwrap());
+						SetFocus(self.combo1().h_window());
 					} else {
 						return Ok(Continue);
 					}
@@ -153,26 +129,18 @@ impl WindowHandler for App {
 			// 8. on esc clear combo selection and reset focus
 			app_message::Esc => {
 				display!("on_message => Esc");
-				unsafe {
-					let focus = GetFocus();
-					let combo = if focus == self.h_edit1.unwrap() {
-						self.h_combo1.unwrap()
-					} else if focus == self.h_edit2.unwrap() {
-						self.h_combo2.unwrap()
-					} else {
-						return Ok(Continue);
-					};
-
-					// clear selection and focus main window
-					SendMessageW(
-						combo,
-						CB_SETCURSEL,
-						WPARAM::MAX, /* equivalent to (WPARAM)(-1) */
-						0,
-					);
-
-					SetFocus(self.h_window);
-				}
+				let focus = unsafe { GetFocus() };
+				let combo = if focus == self.h_edit1.unwrap() {
+					self.combo1()
+				} else if focus == self.h_edit2.unwrap() {
+					self.combo2()
+				} else {
+					return Ok(Continue);
+				};
+
+				// clear selection and focus main window
+				combo.select(None)?;
+				unsafe { SetFocus(self.h_window) };
 				Ok(FullyHandled)
 			}
 
@@ -180,44 +148,26 @@ impl WindowHandler for App {
 			app_message::Enter => {
 				display!("on_message => Enter");
 
-				unsafe {
-					let combo = if GetFocus() == self.h_edit1.unwrap() {
-						self.h_combo1.unwrap()
-					} else {
-						self.h_combo2.unwrap()
-					};
-					SetFocus(self.h_window);
-
-					//  if nothing is selected, select first item
-					let cb_err: isize = CB_ERR.try_into().unwrap();
-					let selected = SendMessageW(combo, CB_GETCURSEL, 0, 0);
-					if selected == cb_err {
-						let mut buffer: [u8; 256] = [0; 256];
-						let text = SendMessageW(
-							combo,
-							message::GetText,
-							buffer.len(),
-							buffer.as_mut_ptr() as _,
-						);
-						if text == 0 {
-							return Ok(FullyHandled);
-						}
-
-						let mut index = SendMessageW(
-							combo,
-							CB_FINDSTRINGEXACT,
-							WPARAM::MAX,
-							buffer.as_mut_ptr() as _,
-						);
-
-						// add string to combo list and select it
-						if index == cb_err {
-							index = SendMessageW(combo, CB_ADDSTRING, 0, buffer.as_mut_ptr() as _);
-						}
-						if index != cb_err {
-							SendMessageW(combo, CB_SETCURSEL, index.try_into().unwrap(), 0);
-						}
+				let combo = if unsafe { GetFocus() } == self.h_edit1.unwrap() {
+					self.combo1()
+				} else {
+					self.combo2()
+				};
+				unsafe { SetFocus(self.h_window) };
+
+				// if nothing is selected, select the typed text
+				if combo.selected().is_none() {
+					let text = combo.text()?;
+					if text.is_empty() {
+						return Ok(FullyHandled);
 					}
+
+					// add string to combo list and select it
+					let index = match combo.find_exact(&text) {
+						Some(index) => index,
+						None => combo.add(&text)?,
+					};
+					combo.select(Some(index))?;
 				}
 
 				Ok(FullyHandled)
@@ -236,6 +186,14 @@ impl App {
 		}
 	}
 
+	fn combo1(&self) -> &ComboBox {
+		self.combo1.as_ref().expect("combo box not created")
+	}
+
+	fn combo2(&self) -> &ComboBox {
+		self.combo2.as_ref().expect("combo box not created")
+	}
+
 	pub fn run(&self) -> Result<()> {
 		let _main_window = Self::new_window(
 			"MainWindow",
@@ -245,7 +203,7 @@ impl App {
 			},
 		)?;
 
-		let res = Self::event_loop();
+		let res = Self::event_loop()?;
 		display!("event_loop result: {} ({:#X})", res, res);
 
 		Ok(())
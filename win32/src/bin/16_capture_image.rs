@@ -1,27 +1,29 @@
 // See https://docs.microsoft.com/en-us/windows/win32/gdi/capturing-an-image#code-example
 
+use std::time::Duration;
+
 use derive::WindowBase;
 use gui::{
 	assert::{assert_eq, assert_ne, Result, WithLastWin32Error},
-	display, err_display, loword, menu,
+	display, err_display,
+	menu::{MenuBar, MenuItem},
 	window::{
-		message::{self}, MessageAction, Options, WindowBase, WindowHandler,
+		MenuHandler, MessageAction, Options, WindowBase, WindowHandler,
 	},
 };
 
 use windows::Win32::{
-	Foundation::{CloseHandle, HANDLE, HINSTANCE, HWND, LPARAM, RECT, WPARAM},
+	Foundation::{CloseHandle, HANDLE, HINSTANCE, HWND, RECT},
 	Graphics::Gdi::{
 		BeginPaint, CreateCompatibleDC, DeleteObject, EndPaint,
-		GetDC, ReleaseDC, SetStretchBltMode, StretchBlt, HALFTONE, HBITMAP, HDC,
+		GetDC, InvalidateRect, ReleaseDC, SetStretchBltMode, StretchBlt, HALFTONE, HBITMAP, HDC,
 		PAINTSTRUCT, SRCCOPY,
 	},
 	System::{
 		Memory::{GlobalFree, GlobalUnlock},
 	},
 	UI::WindowsAndMessaging::{
-		AppendMenuW, CreateMenu, CreatePopupMenu, DestroyWindow, GetClientRect, GetSystemMetrics,
-		SendMessageW, SetMenu, SM_CXSCREEN, SM_CYSCREEN,
+		DestroyWindow, GetClientRect, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN,
 	},
 };
 
@@ -61,7 +63,7 @@ impl App {
 			},
 		)?;
 
-		let res = Self::event_loop();
+		let res = Self::event_loop()?;
 		display!("event_loop result: {} ({:#X})", res, res);
 
 		Ok(())
@@ -87,76 +89,6 @@ impl WindowHandler for App {
 		// wcex.lpszClassName = szWindowClass;
 		// wcex.hIconSm = LoadIcon(wcex.hInstance, MAKEINTRESOURCE(IDI_SMALL));
 
-		unsafe {
-			let menubar = CreateMenu();
-			let menu = CreateMenu();
-			let _menu_file = CreatePopupMenu();
-
-			// [ File ]
-			//   ├─ Save
-			//   └─ Exit
-			assert_eq(
-				AppendMenuW(
-					menubar,
-					menu::item_flag::Popup,
-					menu.try_into().unwrap(),
-					"&File",
-				)
-				.as_bool(),
-				true,
-				"failed to append to menubar",
-			)
-			.with_last_win32_err()?;
-
-			assert_eq(
-				AppendMenuW(
-					menu,
-					menu::item_flag::String,
-					app_menu::MenuSave.try_into().unwrap(),
-					"&Save",
-				)
-				.as_bool(),
-				true,
-				"failed to append menu",
-			)
-			.with_last_win32_err()?;
-
-			assert_eq(
-				AppendMenuW(
-					menu,
-					menu::item_flag::String,
-					app_menu::MenuExit.try_into().unwrap(),
-					"&Exit",
-				)
-				.as_bool(),
-				true,
-				"failed to append menu",
-			)
-			.with_last_win32_err()?;
-
-			// [ About ]
-			assert_eq(
-				AppendMenuW(
-					menubar,
-					menu::item_flag::String,
-					app_menu::MenuAbout.try_into().unwrap(),
-					"&About",
-				)
-				.as_bool(),
-				true,
-				"failed to append menu",
-			)
-			.with_last_win32_err()?;
-
-			// top menu
-			assert_eq(
-				SetMenu(self.h_window, menubar).as_bool(),
-				true,
-				"failed to set window menu",
-			)
-			.with_last_win32_err()?;
-		}
-
 		Ok(MessageAction::Continue)
 	}
 
@@ -171,33 +103,56 @@ impl WindowHandler for App {
 		Ok(MessageAction::FullyHandled)
 	}
 
-	fn on_command(&self, _lparam: LPARAM, wparam: WPARAM) -> Result<MessageAction> {
-		use gui::window::MessageAction::*;
-		let control_id: u32 = loword(wparam).try_into().unwrap();
-		match control_id {
-			app_menu::MenuSave => {
+	fn on_create_mut(&mut self) -> Result<MessageAction> {
+		// [ File ]
+		//   ├─ Save  Ctrl+S
+		//   ├─────
+		//   └─ Exit
+		// [ About ]
+		self.set_menu_bar(
+			MenuBar::new()
+				.submenu("&File", |m| {
+					m.push(MenuItem::new("&Save", Cmd::Save).shortcut("Ctrl+S"))
+						.separator()
+						.item("&Exit", Cmd::Exit)
+				})
+				.item("&About", Cmd::About),
+		)?;
+
+		// the capture follows what's on screen
+		self.set_timer(Duration::from_millis(100), |app| unsafe {
+			InvalidateRect(app.h_window, std::ptr::null(), false);
+		})?;
+		Ok(MessageAction::Continue)
+	}
+}
+
+impl MenuHandler for App {
+	type Command = Cmd;
+
+	fn on_menu(&mut self, cmd: Cmd) -> Result<MessageAction> {
+		match cmd {
+			Cmd::Save => {
 				display!("Save!");
 				// ScreenCapture::save_file(self.h_window).unwrap();
-				Ok(FullyHandled)
 			}
-			app_menu::MenuAbout => {
+			Cmd::About => {
 				display!("About!");
-
-				Ok(FullyHandled)
 			}
-			app_menu::MenuExit => {
+			Cmd::Exit => {
 				display!("Exit!");
 				unsafe { DestroyWindow(self.h_window) };
-				Ok(FullyHandled)
 			}
-			_ => Ok(Continue),
 		}
+		Ok(MessageAction::FullyHandled)
 	}
+}
 
-	fn on_move(&self) -> Result<MessageAction> {
-		unsafe { SendMessageW(self.h_window, message::Paint, 0, 0) };
-		Ok(MessageAction::Continue)
-	}
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cmd {
+	Save,
+	Exit,
+	About,
 }
 
 struct ScreenCapture {
@@ -462,13 +417,3 @@ impl ScreenCapture {
 		Ok(())
 	}
 }
-
-#[allow(dead_code)]
-#[allow(non_upper_case_globals)]
-mod app_menu {
-	pub type Type = u32;
-
-	pub const MenuAbout: Type = 1;
-	pub const MenuExit: Type = MenuAbout + 1;
-	pub const MenuSave: Type = MenuAbout + 2;
-}
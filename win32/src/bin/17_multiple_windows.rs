@@ -0,0 +1,60 @@
+use derive::WindowBase;
+use gui::{
+	assert::Result,
+	display, err_display,
+	window::{self, MessageAction, MouseButton, MouseEvent, QuitPolicy, WindowBase, WindowHandler},
+};
+use windows::Win32::Foundation::{HINSTANCE, HWND};
+
+fn main() -> std::result::Result<(), ()> {
+	match app() {
+		Ok(_) => Ok(()),
+		Err(e) => {
+			err_display!("App error: {}", e);
+			Err(())
+		}
+	}
+}
+
+fn app() -> Result<()> {
+	// the default, stated for the example: closing the first window keeps the others open
+	window::set_quit_policy(QuitPolicy::LastWindowClosed);
+
+	let main_window = Editor::new_window("Editor", "Editor 1 — Win32 💖 Rust", None)?;
+	display!("main_window: {:?}", main_window);
+
+	let res = Editor::event_loop()?;
+	display!("event_loop result: {} ({:#X})", res, res);
+
+	Ok(())
+}
+
+// every window has its own state, a click opens a new window of the same class
+#[derive(Debug, Default, WindowBase)]
+struct Editor {
+	h_instance: HINSTANCE,
+	h_window: HWND,
+	// boxed, win_proc keeps a pointer to each window state
+	#[allow(clippy::vec_box)]
+	editors: Vec<Box<Editor>>,
+}
+
+impl WindowHandler for Editor {
+	fn on_mouse_down(&mut self, event: MouseEvent) -> Result<MessageAction> {
+		if event.button != Some(MouseButton::Left) {
+			return Ok(MessageAction::Continue);
+		}
+		let title = format!(
+			"Editor {} — Win32 💖 Rust",
+			window::open_windows().len() + 1
+		);
+		let editor = Self::new_window("Editor", &title, None)?;
+		self.editors.push(editor);
+		Ok(MessageAction::FullyHandled)
+	}
+
+	fn on_destroy(&mut self) -> Result<MessageAction> {
+		display!("{} windows left", window::open_windows().len());
+		Ok(MessageAction::Continue)
+	}
+}
@@ -0,0 +1,100 @@
+use derive::WindowBase;
+use gui::{
+	assert::Result,
+	dialog::{self, Dialog, DialogBuilder},
+	display, err_display,
+	layout::{Button, DimensionBuilder, HStack, InputText, PaddingBuilder, VStack},
+	window::{MessageAction, MouseButton, MouseEvent, WindowBase, WindowHandler},
+};
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::WindowsAndMessaging::SetWindowTextW,
+};
+
+fn main() -> std::result::Result<(), ()> {
+	match app() {
+		Ok(_) => Ok(()),
+		Err(e) => {
+			err_display!("App error: {}", e);
+			Err(())
+		}
+	}
+}
+
+fn app() -> Result<()> {
+	let main_window = MainWindow::new_window("MainWindow", "Dialogs — Win32 💖 Rust", None)?;
+	display!("main_window: {:?}", main_window);
+
+	let res = MainWindow::event_loop()?;
+	display!("event_loop result: {} ({:#X})", res, res);
+
+	Ok(())
+}
+
+// left click opens a modal dialog renaming the window, right click a modeless one
+#[derive(Debug, Default, WindowBase)]
+struct MainWindow {
+	h_instance: HINSTANCE,
+	h_window: HWND,
+}
+
+fn rename_dialog(title: &str) -> DialogBuilder<String, String> {
+	DialogBuilder::new(title, "Dialogs".to_owned(), |name: &String| {
+		VStack::new()
+			.vertical_padding(10)
+			.horizontal_padding(10)
+			.spacing(10)
+			.items(vec![
+				InputText::new(name)
+					.height(20)
+					.on_change(|name: &mut String, text| *name = text.to_owned())
+					.done(),
+				HStack::new()
+					.spacing(10)
+					.items(vec![
+						Button::new("OK").id(dialog::id::Ok).width(80).done(),
+						Button::new("Cancel")
+							.id(dialog::id::Cancel)
+							.width(80)
+							.done(),
+					])
+					.done(),
+			])
+			.done()
+	})
+	.size(300, 130)
+	// an empty name keeps the dialog open
+	.on_ok(|name| (!name.is_empty()).then(|| name.clone()))
+}
+
+impl WindowHandler for MainWindow {
+	fn on_mouse_down(&mut self, event: MouseEvent) -> Result<MessageAction> {
+		let h_window = self.h_window;
+		let rename = move |name: String| {
+			let title = format!("{} — Win32 💖 Rust", name);
+			unsafe { SetWindowTextW(h_window, title.as_str()) };
+		};
+
+		match event.button {
+			Some(MouseButton::Left) => {
+				match Dialog::show_modal(self.h_window, rename_dialog("Rename (modal)")) {
+					Ok(name) => rename(name),
+					Err(e) => display!("modal dialog: {}", e),
+				}
+				Ok(MessageAction::FullyHandled)
+			}
+			Some(MouseButton::Right) => {
+				Dialog::show_modeless(
+					self.h_window,
+					rename_dialog("Rename (modeless)"),
+					move |res| match res {
+						Ok(name) => rename(name),
+						Err(e) => display!("modeless dialog: {}", e),
+					},
+				)?;
+				Ok(MessageAction::FullyHandled)
+			}
+			_ => Ok(MessageAction::Continue),
+		}
+	}
+}
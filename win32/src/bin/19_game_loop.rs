@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use derive::WindowBase;
+use gui::{
+	assert::Result,
+	display, err_display,
+	window::{MessageLoop, WindowBase, WindowHandler},
+};
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::WindowsAndMessaging::SetWindowTextW,
+};
+
+fn main() -> std::result::Result<(), ()> {
+	match app() {
+		Ok(_) => Ok(()),
+		Err(e) => {
+			err_display!("App error: {}", e);
+			Err(())
+		}
+	}
+}
+
+fn app() -> Result<()> {
+	let main_window = MainWindow::new_window("MainWindow", "Game Loop — Win32 💖 Rust", None)?;
+	display!("main_window: {:?}", main_window);
+
+	// frames are counted whenever no message is waiting, the title shows the rate once per second
+	let h_window = main_window.h_window();
+	let mut frames = 0;
+	let mut since = Instant::now();
+	let res = MessageLoop::new().run_with_idle(|| {
+		frames += 1;
+		if since.elapsed() >= Duration::from_secs(1) {
+			let title = format!("Game Loop — {} fps", frames);
+			unsafe { SetWindowTextW(h_window, title.as_str()) };
+			frames = 0;
+			since = Instant::now();
+		}
+		Ok(())
+	})?;
+	display!("event_loop result: {} ({:#X})", res, res);
+
+	Ok(())
+}
+
+#[derive(Debug, Default, WindowBase)]
+struct MainWindow {
+	h_instance: HINSTANCE,
+	h_window: HWND,
+}
+
+impl WindowHandler for MainWindow {}
@@ -95,7 +95,7 @@ impl Quiz {
 			),
 			&format!(
 				"{}: {}/{}",
-				self.title.to_string(),
+				self.title,
 				self.current + 1,
 				self.questions.len()
 			),
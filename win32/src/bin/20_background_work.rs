@@ -0,0 +1,65 @@
+use std::{thread, time::Duration};
+
+use derive::WindowBase;
+use gui::{
+	assert::Result,
+	display, err_display,
+	window::{MessageAction, WindowBase, WindowHandler},
+};
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::WindowsAndMessaging::SetWindowTextW,
+};
+
+fn main() -> std::result::Result<(), ()> {
+	match app() {
+		Ok(_) => Ok(()),
+		Err(e) => {
+			err_display!("App error: {}", e);
+			Err(())
+		}
+	}
+}
+
+fn app() -> Result<()> {
+	let main_window =
+		MainWindow::new_window("MainWindow", "Background Work — Win32 💖 Rust", None)?;
+	display!("main_window: {:?}", main_window);
+
+	let res = MainWindow::event_loop()?;
+	display!("event_loop result: {} ({:#X})", res, res);
+
+	Ok(())
+}
+
+#[derive(Debug, Default, WindowBase)]
+struct MainWindow {
+	h_instance: HINSTANCE,
+	h_window: HWND,
+	progress: u32,
+}
+
+impl MainWindow {
+	fn set_progress(&mut self, progress: u32) {
+		self.progress = progress;
+		let title = format!("Background Work — {}%", progress);
+		unsafe { SetWindowTextW(self.h_window, title.as_str()) };
+	}
+}
+
+impl WindowHandler for MainWindow {
+	fn on_create_mut(&mut self) -> Result<MessageAction> {
+		// the worker thread reports its progress, the window is only touched on the UI thread
+		let app = self.app_handle();
+		thread::spawn(move || -> Result<()> {
+			for step in 1..=10 {
+				thread::sleep(Duration::from_millis(300));
+				app.post(move |window: &mut MainWindow| window.set_progress(step * 10))?;
+			}
+			let progress = app.send(|window: &mut MainWindow| window.progress)?;
+			display!("worker done, window shows {}%", progress);
+			Ok(())
+		});
+		Ok(MessageAction::Continue)
+	}
+}
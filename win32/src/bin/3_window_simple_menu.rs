@@ -26,18 +26,20 @@ mod main_window {
 		assert::{assert_eq, assert_ne, Result, WithLastWin32Error},
 		cursor::{self, load_cursor},
 		icon::{self, load_icon},
-		menu, message_box,
+		loword,
+		menu::{Menu, MenuBar},
+		message_box,
 		wide_string::ToWide,
 		window::{class_style, ex_style, message, show_cmd, style},
 		Point,
 	};
+	use std::cell::RefCell;
 	use windows::Win32::System::LibraryLoader::GetModuleHandleExW;
 	use windows::Win32::{
 		Graphics::Gdi::{UpdateWindow, HBRUSH},
 		UI::WindowsAndMessaging::{
-			AppendMenuW, CreateMenu, CreatePopupMenu, CreateWindowExW, DefWindowProcW,
-			DispatchMessageW, GetMessageW, PostQuitMessage, RegisterClassExW, SetMenu, ShowWindow,
-			TranslateMessage, COLOR_BACKGROUND, MSG,
+			CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, PostQuitMessage,
+			RegisterClassExW, ShowWindow, TranslateMessage, COLOR_BACKGROUND, MSG,
 		},
 	};
 	use windows::Win32::{
@@ -49,9 +51,16 @@ mod main_window {
 	const TITLE: &str = "Simple menu — Win32 💖 Rust";
 	static mut H_INSTANCE: Option<HINSTANCE> = None;
 
-	const IDM_FILE_NEW: usize = 1;
+	#[derive(Debug, Clone, PartialEq)]
+	enum Cmd {
+		FileNew,
+		ImportMail,
+	}
 
-	const IDM_IMPORT_MAIL: usize = 11;
+	thread_local! {
+		// finds the command of a WM_COMMAND
+		static MENU: RefCell<Option<Menu<Cmd>>> = const { RefCell::new(None) };
+	}
 
 	fn assert_init() -> Result<HINSTANCE> {
 		match unsafe { H_INSTANCE } {
@@ -155,47 +164,22 @@ mod main_window {
 	}
 
 	fn create_menus(window: HWND) -> Result<()> {
-		unsafe {
-			let menubar = CreateMenu();
-			let menu = CreateMenu();
-			let submenu = CreatePopupMenu();
-
-			let mut res = AppendMenuW(menu, menu::item_flag::String, IDM_FILE_NEW, "&New");
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = AppendMenuW(
-				menu,
-				menu::item_flag::String | menu::item_flag::Popup,
-				submenu
-					.try_into()
-					.expect("failed to convert isize to usize"),
-				"&Import",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = AppendMenuW(
-				submenu,
-				menu::item_flag::String,
-				IDM_IMPORT_MAIL,
-				"Import &mail",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = AppendMenuW(
-				menubar,
-				menu::item_flag::Popup,
-				menu.try_into().expect("failed to convert isize to usize"),
-				"&File",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = SetMenu(window, menubar);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-		}
+		let menu = MenuBar::new()
+			.submenu("&File", |m| {
+				m.item("&New", Cmd::FileNew)
+					.submenu("&Import", |m| m.item("Import &mail", Cmd::ImportMail))
+			})
+			.attach(window)?;
+		MENU.with(|m| *m.borrow_mut() = Some(menu));
 
 		Ok(())
 	}
 
+	fn command(wparam: WPARAM) -> Option<Cmd> {
+		let id = loword(wparam) as u16;
+		MENU.with(|m| m.borrow().as_ref()?.command(id).cloned())
+	}
+
 	extern "system" fn win_proc(
 		window: HWND,
 		message: message::Type,
@@ -204,12 +188,12 @@ mod main_window {
 	) -> LRESULT {
 		match message {
 			message::Create => create_menus(window).unwrap(),
-			message::Command => match wparam {
-				IDM_FILE_NEW => {
+			message::Command => match command(wparam) {
+				Some(Cmd::FileNew) => {
 					message_box::new("New file selected", "Information", message_box::style::Ok)
 						.unwrap();
 				}
-				IDM_IMPORT_MAIL => {
+				Some(Cmd::ImportMail) => {
 					message_box::new(
 						"Import mail selected",
 						"Information",
@@ -217,7 +201,7 @@ mod main_window {
 					)
 					.unwrap();
 				}
-				_ => {}
+				None => {}
 			},
 			message::Destroy => unsafe { PostQuitMessage(0) },
 			_ => {}
@@ -195,7 +195,7 @@ impl Window {
 				return default_win_proc();
 			}
 			// 12. pass message to object method
-			match { (*state).on_message(message, wparam, lparam) } {
+			match (*state).on_message(message, wparam, lparam) {
 				Continue => default_win_proc(),
 				_FullyHandled => 0,
 			}
@@ -45,6 +45,7 @@ enum MessageAction {
 }
 
 // 1. create our window type
+#[allow(dead_code)]
 #[derive(Debug)]
 struct MainWindow {
 	h_instance: HINSTANCE,
@@ -85,6 +86,7 @@ impl WindowBase for MainWindow {
 }
 
 // 3. define functions required to initiate the state and handle messages as a WindowBas trait
+#[allow(dead_code)]
 trait WindowBase {
 	fn init_state(h_instance: HINSTANCE) -> Self;
 	fn h_instance(&self) -> HINSTANCE;
@@ -219,7 +221,7 @@ where
 				return default_win_proc();
 			}
 
-			match { (*state).on_message(h_window, message, wparam, lparam) } {
+			match (*state).on_message(h_window, message, wparam, lparam) {
 				Continue => default_win_proc(),
 				_FullyHandled => 0,
 			}
@@ -25,7 +25,8 @@ mod main_window {
 		cursor::{self, load_cursor},
 		display,
 		icon::{self, load_icon},
-		menu, rich_edit,
+		menu::MenuBar,
+		rich_edit,
 		wide_string::ToWide,
 		window::{class_style, ex_style, message, show_cmd, style},
 		Point,
@@ -35,10 +36,9 @@ mod main_window {
 		Graphics::Gdi::{UpdateWindow, HBRUSH},
 		System::LibraryLoader::GetModuleHandleExW,
 		UI::WindowsAndMessaging::{
-			AppendMenuW, CreateMenu, CreateWindowExW, DefWindowProcW, DispatchMessageW,
-			EnumChildWindows, GetClientRect, GetMessageW, MoveWindow, PostQuitMessage,
-			RegisterClassExW, SetMenu, ShowWindow, TranslateMessage, COLOR_WINDOW, MSG,
-			WNDCLASSEXW,
+			CreateWindowExW, DefWindowProcW, DispatchMessageW, EnumChildWindows, GetClientRect,
+			GetMessageW, MoveWindow, PostQuitMessage, RegisterClassExW, ShowWindow,
+			TranslateMessage, COLOR_WINDOW, MSG, WNDCLASSEXW,
 		},
 	};
 
@@ -148,48 +148,13 @@ mod main_window {
 	}
 
 	fn create_menus(window: HWND) -> Result<()> {
-		unsafe {
-			let menubar = CreateMenu();
-			let menu_file = CreateMenu();
-			let menu_edit = CreateMenu();
-			let menu_view = CreateMenu();
-
-			// menu bar:
-			// | File | Edit | View |
-			let mut res = AppendMenuW(
-				menubar,
-				menu::item_flag::Popup,
-				menu_file
-					.try_into()
-					.expect("failed to convert menu to usize"),
-				"&File",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = AppendMenuW(
-				menubar,
-				menu::item_flag::Popup,
-				menu_edit
-					.try_into()
-					.expect("failed to convert menu to usize"),
-				"&Edit",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			res = AppendMenuW(
-				menubar,
-				menu::item_flag::Popup,
-				menu_view
-					.try_into()
-					.expect("failed to convert menu to usize"),
-				"&View",
-			);
-			assert_eq(res.as_bool(), true, "failed to append menu").with_last_win32_err()?;
-
-			// set window menu
-			res = SetMenu(window, menubar);
-			assert_eq(res.as_bool(), true, "failed to set window menu").with_last_win32_err()?;
-		}
+		// menu bar:
+		// | File | Edit | View |
+		MenuBar::<()>::new()
+			.submenu("&File", |m| m)
+			.submenu("&Edit", |m| m)
+			.submenu("&View", |m| m)
+			.attach(window)?;
 
 		Ok(())
 	}
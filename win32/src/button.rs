@@ -10,8 +10,65 @@ use crate::{
 	window,
 };
 
+mod controls;
+pub use controls::*;
+
 const BUTTON_CLASS: &str = "BUTTON";
 
+/// Type of a button, stored in the `BS_TYPEMASK` bits of its style. Unlike the flags of `style::Type`, types are values
+/// of these bits: a button has exactly one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonKind {
+	PushButton,
+	/// Push button with a heavy border, the one Enter clicks in dialogs.
+	DefPushButton,
+	CheckBox,
+	AutoCheckBox,
+	RadioButton,
+	ThreeState,
+	AutoThreeState,
+	GroupBox,
+	UserButton,
+	AutoRadioButton,
+	PushBox,
+	OwnerDraw,
+}
+
+const BUTTON_KINDS: [(ButtonKind, i32); 12] = [
+	(ButtonKind::PushButton, BS_PUSHBUTTON),
+	(ButtonKind::DefPushButton, BS_DEFPUSHBUTTON),
+	(ButtonKind::CheckBox, BS_CHECKBOX),
+	(ButtonKind::AutoCheckBox, BS_AUTOCHECKBOX),
+	(ButtonKind::RadioButton, BS_RADIOBUTTON),
+	(ButtonKind::ThreeState, BS_3STATE),
+	(ButtonKind::AutoThreeState, BS_AUTO3STATE),
+	(ButtonKind::GroupBox, BS_GROUPBOX),
+	(ButtonKind::UserButton, BS_USERBUTTON),
+	(ButtonKind::AutoRadioButton, BS_AUTORADIOBUTTON),
+	(ButtonKind::PushBox, BS_PUSHBOX),
+	(ButtonKind::OwnerDraw, BS_OWNERDRAW),
+];
+
+impl ButtonKind {
+	/// Value of the `BS_TYPEMASK` bits.
+	pub fn bits(self) -> u32 {
+		let (_, bits) = BUTTON_KINDS
+			.into_iter()
+			.find(|(kind, _)| *kind == self)
+			.expect("all kinds have bits");
+		bits as u32
+	}
+
+	/// Type of a button style, `None` for types without a variant, e.g. the split buttons of common controls 6.
+	pub fn of(style: style::Type) -> Option<Self> {
+		let bits = style.0 & BS_TYPEMASK as u32;
+		BUTTON_KINDS
+			.into_iter()
+			.find(|(_, kind_bits)| *kind_bits as u32 == bits)
+			.map(|(kind, _)| kind)
+	}
+}
+
 pub fn create(
 	owner: HWND,
 	h_instance: HINSTANCE,
@@ -20,12 +77,12 @@ pub fn create(
 	y: i32,
 	width: i32,
 	height: i32,
-) -> Result<()> {
-	let btn_styles: WINDOW_STYLE = (style::PushButton | style::Text)
-		.0
-		.try_into()
-		.expect("cannot cast to WINDOW_STYLE");
-	let styles = window::style::Visible | window::style::Child | window::style::Overlapped;
+) -> Result<HWND> {
+	let styles = window::style::Type::from(style::Text.with_kind(ButtonKind::PushButton))
+		| window::style::Visible
+		| window::style::Child
+		| window::style::Overlapped
+		| window::style::Tabstop;
 	let ex_styles =
 		window::ex_style::Left | window::ex_style::LtrReading | window::ex_style::RightScrollbar;
 
@@ -34,7 +91,7 @@ pub fn create(
 			ex_styles.0,
 			BUTTON_CLASS.to_wide().as_pwstr(),
 			title.to_wide().as_pwstr(),
-			btn_styles | styles.0,
+			styles.0,
 			x,
 			y,
 			width,
@@ -47,45 +104,56 @@ pub fn create(
 	};
 	assert_ne(control, 0, "failed to create button control").with_last_win32_err()?;
 
-	Ok(())
+	Ok(control)
 }
 
-impl_ops_for_all!(style::Type, message::Type);
+impl_ops_for_all!(message::Type);
+
+impl From<style::Type> for window::style::Type {
+	fn from(style: style::Type) -> Self {
+		Self(style.0)
+	}
+}
 
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
 pub mod style {
+	use crate::flags::flags;
 	use windows::Win32::UI::WindowsAndMessaging::*;
-	pub struct Type(pub i32);
-
-	pub const BS_3State: Type = Type(BS_3STATE);
-	pub const Auto3state: Type = Type(BS_AUTO3STATE);
-	pub const AutoCheckbox: Type = Type(BS_AUTOCHECKBOX);
-	pub const AutoRadioButton: Type = Type(BS_AUTORADIOBUTTON);
-	pub const Bitmap: Type = Type(BS_BITMAP);
-	pub const Bottom: Type = Type(BS_BOTTOM);
-	pub const Center: Type = Type(BS_CENTER);
-	pub const Checkbox: Type = Type(BS_CHECKBOX);
-	pub const DefPushButton: Type = Type(BS_DEFPUSHBUTTON);
-	pub const Flat: Type = Type(BS_FLAT);
-	pub const GroupBox: Type = Type(BS_GROUPBOX);
-	pub const Icon: Type = Type(BS_ICON);
-	pub const Left: Type = Type(BS_LEFT);
-	pub const LeftText: Type = Type(BS_LEFTTEXT);
-	pub const Multiline: Type = Type(BS_MULTILINE);
-	pub const Notify: Type = Type(BS_NOTIFY);
-	pub const OwnerDraw: Type = Type(BS_OWNERDRAW);
-	pub const Pushbox: Type = Type(BS_PUSHBOX);
-	pub const PushButton: Type = Type(BS_PUSHBUTTON);
-	pub const PushLike: Type = Type(BS_PUSHLIKE);
-	pub const RadioButton: Type = Type(BS_RADIOBUTTON);
-	pub const Right: Type = Type(BS_RIGHT);
-	pub const RightButton: Type = Type(BS_RIGHTBUTTON);
-	pub const Text: Type = Type(BS_TEXT);
-	pub const Top: Type = Type(BS_TOP);
-	pub const Typemask: Type = Type(BS_TYPEMASK);
-	pub const UserButton: Type = Type(BS_USERBUTTON);
-	pub const VCenter: Type = Type(BS_VCENTER);
+
+	use super::ButtonKind;
+
+	// the type of the button, see `ButtonKind`, is kept out of the flags
+	flags! {
+		pub struct Type(pub WINDOW_STYLE);
+
+		Bitmap = BS_BITMAP as u32;
+		Bottom = BS_BOTTOM as u32;
+		Center = BS_CENTER as u32;
+		Flat = BS_FLAT as u32;
+		Icon = BS_ICON as u32;
+		Left = BS_LEFT as u32;
+		LeftText = BS_LEFTTEXT as u32;
+		Multiline = BS_MULTILINE as u32;
+		Notify = BS_NOTIFY as u32;
+		PushLike = BS_PUSHLIKE as u32;
+		Right = BS_RIGHT as u32;
+		RightButton = BS_RIGHTBUTTON as u32;
+		Text = BS_TEXT as u32;
+		Top = BS_TOP as u32;
+		VCenter = BS_VCENTER as u32;
+	}
+
+	impl Type {
+		pub fn kind(self) -> Option<ButtonKind> {
+			ButtonKind::of(self)
+		}
+
+		/// Same flags, with the type replaced by `kind`.
+		pub fn with_kind(self, kind: ButtonKind) -> Self {
+			Self(self.0 & !(BS_TYPEMASK as u32) | kind.bits())
+		}
+	}
 }
 
 #[allow(dead_code)]
@@ -122,3 +190,64 @@ pub const _BSM_NETDRIVER: u32 = BSM_NETDRIVER;
 pub const _BSM_VXDS: u32 = BSM_VXDS;
 pub const _BST_FOCUS: u32 = BST_FOCUS;
 pub const _BST_PUSHED: u32 = BST_PUSHED;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const KINDS: [ButtonKind; 12] = [
+		ButtonKind::PushButton,
+		ButtonKind::DefPushButton,
+		ButtonKind::CheckBox,
+		ButtonKind::AutoCheckBox,
+		ButtonKind::RadioButton,
+		ButtonKind::ThreeState,
+		ButtonKind::AutoThreeState,
+		ButtonKind::GroupBox,
+		ButtonKind::UserButton,
+		ButtonKind::AutoRadioButton,
+		ButtonKind::PushBox,
+		ButtonKind::OwnerDraw,
+	];
+
+	#[test]
+	fn kinds() {
+		for kind in KINDS {
+			assert_eq!(ButtonKind::of(style::Type(kind.bits())), Some(kind));
+			assert_eq!(kind.bits() & !(BS_TYPEMASK as u32), 0);
+		}
+		// BS_AUTORADIOBUTTON has the bits of BS_AUTOCHECKBOX and of BS_PUSHBUTTON, it's still neither
+		let radio = style::Text.with_kind(ButtonKind::AutoRadioButton);
+		assert_eq!(radio.kind(), Some(ButtonKind::AutoRadioButton));
+		// split buttons of common controls 6
+		assert_eq!(style::Type(0xC).kind(), None);
+	}
+
+	#[test]
+	fn with_kind() {
+		let flags = style::Flat | style::Multiline | style::Notify;
+		let check = flags.with_kind(ButtonKind::AutoThreeState);
+		let push = check.with_kind(ButtonKind::DefPushButton);
+		assert_eq!(push.kind(), Some(ButtonKind::DefPushButton));
+		assert_eq!(
+			push.with_kind(ButtonKind::PushButton),
+			flags.with_kind(ButtonKind::PushButton)
+		);
+		// the flags are kept, the old type is gone
+		assert_eq!(push.0 & !(BS_TYPEMASK as u32), flags.0);
+		assert_eq!(push.0 & BS_TYPEMASK as u32, BS_DEFPUSHBUTTON as u32);
+	}
+
+	#[test]
+	fn flags_have_no_types() {
+		assert_eq!(
+			format!("{:?}", style::Flat | style::Notify),
+			"Flat | Notify"
+		);
+		assert_eq!(
+			"Flat | Notify".parse::<style::Type>().unwrap(),
+			style::Flat | style::Notify
+		);
+		assert!("AutoCheckbox".parse::<style::Type>().is_err());
+	}
+}
@@ -0,0 +1,388 @@
+// Handles of BUTTON controls by kind: push buttons, check boxes, radio groups and group boxes, and the builder creating
+// them.
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+	Graphics::Gdi::HBITMAP,
+	UI::{
+		Controls::{BST_CHECKED, BST_INDETERMINATE, BST_UNCHECKED},
+		Input::KeyboardAndMouse::EnableWindow,
+		WindowsAndMessaging::{SendMessageW, GWL_STYLE, HICON, IMAGE_BITMAP, IMAGE_ICON},
+	},
+};
+
+use super::{message, style, ButtonKind, BUTTON_CLASS};
+use crate::{
+	assert::Result,
+	window::{self, get_window_text, set_window_text, WindowBuilder},
+	window_long::get_window_long,
+};
+
+/// State of a check box, `Indeterminate` only for tri-state ones.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+	#[default]
+	Unchecked,
+	Checked,
+	Indeterminate,
+}
+
+impl CheckState {
+	pub fn is_checked(self) -> bool {
+		self == CheckState::Checked
+	}
+
+	fn from_raw(state: isize) -> Self {
+		match state as u32 {
+			BST_CHECKED => CheckState::Checked,
+			BST_INDETERMINATE => CheckState::Indeterminate,
+			_ => CheckState::Unchecked,
+		}
+	}
+
+	fn to_raw(self) -> u32 {
+		match self {
+			CheckState::Unchecked => BST_UNCHECKED,
+			CheckState::Checked => BST_CHECKED,
+			CheckState::Indeterminate => BST_INDETERMINATE,
+		}
+	}
+}
+
+impl From<bool> for CheckState {
+	fn from(checked: bool) -> Self {
+		match checked {
+			true => CheckState::Checked,
+			false => CheckState::Unchecked,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonImage {
+	Bitmap(HBITMAP),
+	Icon(HICON),
+}
+
+fn send(h_window: HWND, message: message::Type, wparam: WPARAM, lparam: LPARAM) -> isize {
+	unsafe { SendMessageW(h_window, message.0, wparam, lparam) }
+}
+
+/// Operations shared by all kinds of buttons.
+pub trait ButtonControl {
+	fn h_window(&self) -> HWND;
+
+	fn text(&self) -> Result<String> {
+		get_window_text(self.h_window())
+	}
+
+	fn set_text(&self, text: &str) -> Result<()> {
+		set_window_text(self.h_window(), text)
+	}
+
+	fn set_enabled(&self, enabled: bool) {
+		unsafe { EnableWindow(self.h_window(), enabled) };
+	}
+
+	/// Click the button as the user would, the parent gets a `BN_CLICKED` notification.
+	fn click(&self) {
+		send(self.h_window(), message::Click, 0, 0);
+	}
+
+	/// Image shown along with the text, or instead of it with the `Bitmap` or `Icon` style. `None` removes it.
+	fn set_image(&self, image: Option<ButtonImage>) {
+		let (kind, handle) = match image {
+			Some(ButtonImage::Bitmap(bitmap)) => (IMAGE_BITMAP, bitmap),
+			Some(ButtonImage::Icon(icon)) => (IMAGE_ICON, icon),
+			None => (IMAGE_BITMAP, 0),
+		};
+		send(self.h_window(), message::SetImage, kind as _, handle);
+	}
+}
+
+fn check_state(h_window: HWND) -> CheckState {
+	CheckState::from_raw(send(h_window, message::GetCheck, 0, 0))
+}
+
+fn set_check_state(h_window: HWND, state: CheckState) {
+	send(h_window, message::SetCheck, state.to_raw() as _, 0);
+}
+
+#[derive(Debug)]
+pub struct PushButton {
+	h_window: HWND,
+}
+
+impl PushButton {
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+
+	/// Draw the button as the default one of its window, the one Enter clicks in dialogs.
+	pub fn set_default(&self, default: bool) -> Result<()> {
+		let kind = match default {
+			true => ButtonKind::DefPushButton,
+			false => ButtonKind::PushButton,
+		};
+		// only the type changes, flags such as Flat are kept
+		let style = get_window_long(self.h_window, GWL_STYLE)?;
+		let style = style::Type(style as u32).with_kind(kind);
+		// lparam: redraw
+		send(self.h_window, message::SetStyle, style.0 as _, 1);
+		Ok(())
+	}
+}
+
+impl ButtonControl for PushButton {
+	fn h_window(&self) -> HWND {
+		self.h_window
+	}
+}
+
+#[derive(Debug)]
+pub struct CheckBox {
+	h_window: HWND,
+}
+
+impl CheckBox {
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+
+	pub fn is_checked(&self) -> bool {
+		self.state().is_checked()
+	}
+
+	pub fn set_checked(&self, checked: bool) {
+		self.set_state(checked.into());
+	}
+
+	pub fn state(&self) -> CheckState {
+		check_state(self.h_window)
+	}
+
+	/// `Indeterminate` is only shown by tri-state check boxes.
+	pub fn set_state(&self, state: CheckState) {
+		set_check_state(self.h_window, state);
+	}
+}
+
+impl ButtonControl for CheckBox {
+	fn h_window(&self) -> HWND {
+		self.h_window
+	}
+}
+
+/// Radio buttons of which at most one is checked. Clicking one unchecks the others, and so does `select`.
+#[derive(Debug)]
+pub struct RadioGroup {
+	buttons: Vec<HWND>,
+}
+
+impl RadioGroup {
+	/// Group existing radio buttons, in order.
+	pub fn from_raw(buttons: Vec<HWND>) -> Self {
+		Self { buttons }
+	}
+
+	/// Create a radio button per builder, the first one starting the group.
+	pub fn create(buttons: Vec<ButtonBuilder>) -> Result<Self> {
+		let buttons = buttons
+			.into_iter()
+			.enumerate()
+			.map(|(i, builder)| builder.create_radio_button(i == 0))
+			.collect::<Result<_>>()?;
+		Ok(Self { buttons })
+	}
+
+	pub fn buttons(&self) -> &[HWND] {
+		&self.buttons
+	}
+
+	pub fn len(&self) -> usize {
+		self.buttons.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.buttons.is_empty()
+	}
+
+	/// Index of the button sending a notification, e.g. the lparam of a `WM_COMMAND`.
+	pub fn index_of(&self, h_window: HWND) -> Option<usize> {
+		self.buttons.iter().position(|b| *b == h_window)
+	}
+
+	/// Index of the checked button.
+	pub fn selected(&self) -> Option<usize> {
+		self.buttons
+			.iter()
+			.position(|b| check_state(*b).is_checked())
+	}
+
+	/// Check the button at `index`, `None` unchecks them all.
+	pub fn select(&self, index: Option<usize>) {
+		for (i, button) in self.buttons.iter().enumerate() {
+			set_check_state(*button, (Some(i) == index).into());
+		}
+	}
+
+	pub fn is_checked(&self, index: usize) -> bool {
+		check_state(self.buttons[index]).is_checked()
+	}
+
+	/// Checking a button unchecks the other ones.
+	pub fn set_checked(&self, index: usize, checked: bool) {
+		match checked {
+			true => self.select(Some(index)),
+			false => set_check_state(self.buttons[index], CheckState::Unchecked),
+		}
+	}
+
+	/// Click the button at `index` as the user would.
+	pub fn click(&self, index: usize) {
+		send(self.buttons[index], message::Click, 0, 0);
+	}
+
+	pub fn set_enabled(&self, enabled: bool) {
+		for button in &self.buttons {
+			unsafe { EnableWindow(*button, enabled) };
+		}
+	}
+}
+
+/// Frame with a title drawn around other controls. It doesn't contain them, they are its siblings.
+#[derive(Debug)]
+pub struct GroupBox {
+	h_window: HWND,
+}
+
+impl GroupBox {
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+}
+
+impl ButtonControl for GroupBox {
+	fn h_window(&self) -> HWND {
+		self.h_window
+	}
+}
+
+/// Options of a button created as a child of `parent`, its kind is chosen by the method creating it.
+///
+/// ```ignore
+/// let save = ButtonBuilder::new(h_window, "&Save").at(10, 10).size(80, 24).create_push_button(true)?;
+/// let wrap = ButtonBuilder::new(h_window, "Wrap lines").at(10, 40).size(120, 20).create_check_box(false)?;
+/// ```
+pub struct ButtonBuilder {
+	parent: HWND,
+	h_instance: Option<HINSTANCE>,
+	text: String,
+	x: i32,
+	y: i32,
+	width: i32,
+	height: i32,
+	id: Option<u16>,
+	style: style::Type,
+}
+
+impl ButtonBuilder {
+	pub fn new(parent: HWND, text: &str) -> Self {
+		Self {
+			parent,
+			h_instance: None,
+			text: text.to_owned(),
+			x: 0,
+			y: 0,
+			width: 80,
+			height: 24,
+			id: None,
+			style: style::Text,
+		}
+	}
+
+	pub fn h_instance(mut self, h_instance: HINSTANCE) -> Self {
+		self.h_instance = Some(h_instance);
+		self
+	}
+
+	pub fn at(mut self, x: i32, y: i32) -> Self {
+		self.x = x;
+		self.y = y;
+		self
+	}
+
+	pub fn size(mut self, width: i32, height: i32) -> Self {
+		self.width = width;
+		self.height = height;
+		self
+	}
+
+	/// Control id, sent with its notifications in `WM_COMMAND`.
+	pub fn id(mut self, id: u16) -> Self {
+		self.id = Some(id);
+		self
+	}
+
+	/// Styles added to the one of the kind of button, e.g. `style::Flat` or `style::Multiline`.
+	pub fn style(mut self, style: style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	/// `default` makes it the button Enter clicks in dialogs.
+	pub fn create_push_button(self, default: bool) -> Result<PushButton> {
+		let kind = match default {
+			true => ButtonKind::DefPushButton,
+			false => ButtonKind::PushButton,
+		};
+		self.create(kind, window::style::Tabstop)
+			.map(PushButton::from_raw)
+	}
+
+	/// A tri-state check box cycles through checked, indeterminate and unchecked when clicked.
+	pub fn create_check_box(self, tri_state: bool) -> Result<CheckBox> {
+		let kind = match tri_state {
+			true => ButtonKind::AutoThreeState,
+			false => ButtonKind::AutoCheckBox,
+		};
+		self.create(kind, window::style::Tabstop)
+			.map(CheckBox::from_raw)
+	}
+
+	pub fn create_group_box(self) -> Result<GroupBox> {
+		self.create(ButtonKind::GroupBox, window::style::Type::empty())
+			.map(GroupBox::from_raw)
+	}
+
+	// only the first button of a group can be tabbed to, arrows move between the others
+	fn create_radio_button(self, first: bool) -> Result<HWND> {
+		let group = match first {
+			true => window::style::Group | window::style::Tabstop,
+			false => window::style::Type::empty(),
+		};
+		self.create(ButtonKind::AutoRadioButton, group)
+	}
+
+	fn create(self, kind: ButtonKind, window_style: window::style::Type) -> Result<HWND> {
+		let mut builder = WindowBuilder::child(BUTTON_CLASS, self.parent)
+			.text(&self.text)
+			.style(
+				window::style::Type::from(self.style.with_kind(kind))
+					| window_style | window::style::Visible
+					| window::style::Child,
+			)
+			.at(self.x, self.y)
+			.size(self.width, self.height);
+		if let Some(id) = self.id {
+			builder = builder.id(id);
+		}
+		if let Some(h_instance) = self.h_instance {
+			builder = builder.h_instance(h_instance);
+		}
+		let h_window = builder.create()?;
+
+		window::set_default_font(h_window);
+		Ok(h_window)
+	}
+}
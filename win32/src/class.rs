@@ -1 +1,391 @@
+use std::{
+	collections::BTreeMap,
+	sync::{Mutex, MutexGuard, PoisonError},
+};
 
+use crate::{
+	assert::{assert_ne, Result, WithLastWin32Error},
+	cursor::{self, load_cursor},
+	err_display,
+	icon::{self, load_icon, Icon},
+	wide_string::ToWide,
+	window::{class_style, WinProc},
+};
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::WindowsAndMessaging::*,
+};
+
+/// Builder for a `WNDCLASSEXW`. The class is registered by `register`, only once per module and name, and unregistered
+/// when the last `RegisteredClass` of that name is dropped.
+pub struct WindowClass {
+	name: String,
+	style: class_style::Type,
+	win_proc: Option<WinProc>,
+	icon: Icon,
+	small_icon: Option<Icon>,
+	cursor: cursor::Type,
+	bg_brush: u32,
+	menu_name: Option<String>,
+	class_extra: i32,
+	window_extra: i32,
+}
+
+impl WindowClass {
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.to_owned(),
+			style: class_style::HRedraw | class_style::VRedraw,
+			win_proc: None,
+			icon: icon::Application,
+			small_icon: None,
+			cursor: cursor::Arrow,
+			bg_brush: COLOR_WINDOW + 1,
+			menu_name: None,
+			class_extra: 0,
+			window_extra: 0,
+		}
+	}
+
+	pub fn style(mut self, style: class_style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	pub fn win_proc(mut self, win_proc: WinProc) -> Self {
+		self.win_proc = Some(win_proc);
+		self
+	}
+
+	pub fn icon(mut self, icon: Icon) -> Self {
+		self.icon = icon;
+		self
+	}
+
+	/// Icon of the title bar and taskbar, the large icon is used when not set.
+	pub fn small_icon(mut self, icon: Icon) -> Self {
+		self.small_icon = Some(icon);
+		self
+	}
+
+	pub fn cursor(mut self, cursor: cursor::Type) -> Self {
+		self.cursor = cursor;
+		self
+	}
+
+	/// System color index plus one, e.g. `COLOR_WINDOW + 1`.
+	pub fn bg_brush(mut self, bg_brush: u32) -> Self {
+		self.bg_brush = bg_brush;
+		self
+	}
+
+	/// Name of the menu resource used by windows of this class.
+	pub fn menu_name(mut self, menu_name: &str) -> Self {
+		self.menu_name = Some(menu_name.to_owned());
+		self
+	}
+
+	/// Bytes allocated after the class structure.
+	pub fn class_extra(mut self, bytes: i32) -> Self {
+		self.class_extra = bytes;
+		self
+	}
+
+	/// Bytes allocated after each window instance, read and written with `get_window_long_ptr` & co.
+	pub fn window_extra(mut self, bytes: i32) -> Self {
+		self.window_extra = bytes;
+		self
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Register the class if no `RegisteredClass` of this name exists yet, otherwise share the existing one. A class
+	/// registered outside of the registry, e.g. a system class, is used as is and never unregistered. Sharing a class
+	/// registered with another `win_proc` is an error, its windows wouldn't get the messages to the expected state.
+	pub fn register(self, h_instance: HINSTANCE) -> Result<RegisteredClass> {
+		release_destroyed_windows();
+
+		let name = self.name.clone();
+		let key = class_key(h_instance, &name);
+		let mut classes = lock(&CLASSES);
+		match classes.get_mut(&key) {
+			Some(entry) => {
+				self.check_win_proc(entry.win_proc)?;
+				entry.refs += 1;
+			}
+			None => {
+				let (owned, win_proc) = match self.class_info(h_instance) {
+					Some(class) => {
+						self.check_win_proc(class.lpfnWndProc)?;
+						(false, class.lpfnWndProc)
+					}
+					None => (true, self.win_proc),
+				};
+				if owned {
+					self.register_class(h_instance)?;
+				}
+				let entry = ClassEntry {
+					refs: 1,
+					owned,
+					win_proc,
+				};
+				classes.insert(key, entry);
+			}
+		}
+
+		Ok(RegisteredClass { h_instance, name })
+	}
+
+	// a class without win_proc only refers to an existing class, whichever its win_proc
+	fn check_win_proc(&self, registered: Option<WinProc>) -> Result<()> {
+		match (self.win_proc, registered) {
+			(Some(win_proc), Some(registered)) if win_proc as usize == registered as usize => {
+				Ok(())
+			}
+			(Some(_), _) => Err(format!(
+				"class {:?} is already registered with another win_proc",
+				self.name
+			)
+			.into()),
+			(None, _) => Ok(()),
+		}
+	}
+
+	fn class_info(&self, h_instance: HINSTANCE) -> Option<WNDCLASSEXW> {
+		let mut class: WNDCLASSEXW = Default::default();
+		unsafe {
+			GetClassInfoExW(
+				h_instance,
+				self.name.to_wide().as_pwstr(),
+				&mut class as *mut _,
+			)
+		}
+		.as_bool()
+		.then_some(class)
+	}
+
+	fn register_class(self, h_instance: HINSTANCE) -> Result<()> {
+		let win_proc = match self.win_proc {
+			Some(win_proc) => win_proc,
+			None => return Err(format!("class {:?} has no win_proc", self.name).into()),
+		};
+
+		let size: u32 = std::mem::size_of::<WNDCLASSEXW>()
+			.try_into()
+			.expect("WNDCLASSEXW size not u32");
+
+		let icon = load_icon(self.icon)?;
+		let small_icon = match self.small_icon {
+			Some(small_icon) => load_icon(small_icon)?,
+			None => icon,
+		};
+		// kept alive until RegisterClassExW copied them
+		let class_name = self.name.to_wide();
+		let menu_name = self
+			.menu_name
+			.map(|name| name.to_wide())
+			.unwrap_or_default();
+
+		let wnd_class = WNDCLASSEXW {
+			cbSize: size,
+			style: self.style.0,
+			lpfnWndProc: Some(win_proc),
+			cbClsExtra: self.class_extra,
+			cbWndExtra: self.window_extra,
+			hInstance: h_instance,
+			hIcon: icon,
+			hCursor: load_cursor(self.cursor)?,
+			hbrBackground: self
+				.bg_brush
+				.try_into()
+				.expect("cannot convert color to HBRUSH"),
+			// null without a menu
+			lpszMenuName: match menu_name.0.is_empty() {
+				true => Default::default(),
+				false => menu_name.as_pwstr(),
+			},
+			lpszClassName: class_name.as_pwstr(),
+			hIconSm: small_icon,
+		};
+
+		let atom = unsafe { RegisterClassExW(&wnd_class) };
+		assert_ne(
+			atom,
+			0,
+			format!("failed to register class {:?}", self.name).as_str(),
+		)
+		.with_last_win32_err()?;
+		Ok(())
+	}
+}
+
+/// Reference to a class of the registry, the class is unregistered when the last reference is dropped. Windows of the
+/// class must be destroyed by then, see `hold_for_window`.
+#[derive(Debug)]
+pub struct RegisteredClass {
+	h_instance: HINSTANCE,
+	name: String,
+}
+
+impl RegisteredClass {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn h_instance(&self) -> HINSTANCE {
+		self.h_instance
+	}
+
+	/// Keep the class registered as long as `h_window` exists.
+	pub fn hold_for_window(self, h_window: HWND) {
+		lock(&WINDOW_CLASSES).push((h_window, self));
+	}
+}
+
+impl Clone for RegisteredClass {
+	fn clone(&self) -> Self {
+		let key = class_key(self.h_instance, &self.name);
+		if let Some(entry) = lock(&CLASSES).get_mut(&key) {
+			entry.refs += 1;
+		}
+		Self {
+			h_instance: self.h_instance,
+			name: self.name.clone(),
+		}
+	}
+}
+
+impl Drop for RegisteredClass {
+	fn drop(&mut self) {
+		let key = class_key(self.h_instance, &self.name);
+		let mut classes = lock(&CLASSES);
+		let entry = match classes.get_mut(&key) {
+			Some(entry) => entry,
+			None => return,
+		};
+		entry.refs -= 1;
+		if entry.refs > 0 {
+			return;
+		}
+		let owned = entry.owned;
+		classes.remove(&key);
+		drop(classes);
+
+		if owned {
+			let ok = unsafe { UnregisterClassW(self.name.to_wide().as_pwstr(), self.h_instance) };
+			if !ok.as_bool() {
+				// not an error worth failing for, the class is released when the process exits anyway
+				err_display!("failed to unregister class {:?}", self.name);
+			}
+		}
+	}
+}
+
+/// Number of `RegisteredClass` referencing the class, 0 if it isn't in the registry.
+pub fn class_refs(h_instance: HINSTANCE, name: &str) -> usize {
+	let key = class_key(h_instance, name);
+	lock(&CLASSES).get(&key).map_or(0, |entry| entry.refs)
+}
+
+// class names are case-insensitive, "MainWindow" and "mainwindow" are the same class
+fn class_key(h_instance: HINSTANCE, name: &str) -> (HINSTANCE, String) {
+	(h_instance, name.to_ascii_lowercase())
+}
+
+/// Release the classes held by windows that no longer exist. Called when registering a class and when an event loop
+/// ends, a class can't be unregistered from the `win_proc` of its last window.
+pub fn release_destroyed_windows() {
+	let released: Vec<(HWND, RegisteredClass)> = {
+		let mut held = lock(&WINDOW_CLASSES);
+		let (destroyed, alive) = held
+			.drain(..)
+			.partition(|(h_window, _)| !unsafe { IsWindow(*h_window) }.as_bool());
+		*held = alive;
+		destroyed
+	};
+	// dropped once the lock is released
+	drop(released);
+}
+
+struct ClassEntry {
+	refs: usize,
+	// registered by us, not by the system or another module
+	owned: bool,
+	win_proc: Option<WinProc>,
+}
+
+// classes are per process, any thread may register them. Keyed by `class_key`.
+static CLASSES: Mutex<BTreeMap<(HINSTANCE, String), ClassEntry>> = Mutex::new(BTreeMap::new());
+static WINDOW_CLASSES: Mutex<Vec<(HWND, RegisteredClass)>> = Mutex::new(Vec::new());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	// the maps stay consistent even if a thread panicked with the lock held
+	mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+
+	unsafe extern "system" fn first(_: HWND, _: u32, _: WPARAM, _: LPARAM) -> LRESULT {
+		0
+	}
+
+	unsafe extern "system" fn second(_: HWND, _: u32, _: WPARAM, _: LPARAM) -> LRESULT {
+		1
+	}
+
+	#[test]
+	fn win_proc_mismatch() {
+		let class = WindowClass::new("Test").win_proc(first);
+		assert!(class.check_win_proc(Some(first)).is_ok());
+		let e = class.check_win_proc(Some(second)).unwrap_err();
+		assert_eq!(
+			e.to_string(),
+			"class \"Test\" is already registered with another win_proc"
+		);
+		assert!(class.check_win_proc(None).is_err());
+		// only referring to the class
+		assert!(WindowClass::new("Test")
+			.check_win_proc(Some(second))
+			.is_ok());
+	}
+
+	#[test]
+	fn refs() {
+		// an h_instance of its own, the registry is shared by the tests
+		let h_instance = 0x7e57;
+		let entry = ClassEntry {
+			refs: 1,
+			// not unregistered on the last drop
+			owned: false,
+			win_proc: None,
+		};
+		lock(&CLASSES).insert(class_key(h_instance, "MainWindow"), entry);
+		let class = RegisteredClass {
+			h_instance,
+			name: "MainWindow".to_owned(),
+		};
+
+		// whatever the case of the name
+		let other_case = RegisteredClass {
+			h_instance,
+			name: "MAINWINDOW".to_owned(),
+		};
+		let clone = other_case.clone();
+		std::mem::forget(other_case);
+		assert_eq!(class_refs(h_instance, "mainwindow"), 2);
+		drop(clone);
+		assert_eq!(class_refs(h_instance, "MainWindow"), 1);
+		assert!(lock(&CLASSES).contains_key(&class_key(h_instance, "mainWindow")));
+
+		// removed with its last reference
+		drop(class);
+		assert_eq!(class_refs(h_instance, "MainWindow"), 0);
+		assert!(!lock(&CLASSES).contains_key(&class_key(h_instance, "mainWindow")));
+		assert_eq!(class_refs(0, "MainWindow"), 0);
+	}
+}
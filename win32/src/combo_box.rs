@@ -0,0 +1,395 @@
+// Handle of a COMBOBOX control with typed access to its items and selection, and the builder creating it.
+//
+// Item texts are UTF-16 in the control, searches ignore case as the control does.
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::{
+		Controls::{GetComboBoxInfo, COMBOBOXINFO},
+		Input::KeyboardAndMouse::EnableWindow,
+		WindowsAndMessaging::DestroyWindow,
+	},
+};
+
+use crate::{
+	assert::{assert_eq, Result, WithLastWin32Error},
+	input::Edit,
+	item_list::{frame_setters, item_index, ChildFrame, ItemList},
+	wide_string::ToWide,
+	window::{self, get_window_text, set_window_text},
+};
+
+const COMBO_BOX_CLASS: &str = "COMBOBOX";
+
+/// How the items are shown, and if the user can type a text not in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComboKind {
+	/// Edit field above a list always shown.
+	Simple,
+	/// Edit field with a list dropped down from a button.
+	DropDown,
+	/// Read-only field with a list dropped down from a button, only items can be selected.
+	DropDownList,
+}
+
+/// Handle of a combo box.
+#[derive(Debug)]
+pub struct ComboBox {
+	h_window: HWND,
+}
+
+impl ComboBox {
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+
+	pub fn h_window(&self) -> HWND {
+		self.h_window
+	}
+
+	pub fn len(&self) -> usize {
+		self.index(message::GetCount, 0, 0).unwrap_or(0)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Append an item, or insert it in order with the `Sort` style. Returns its index.
+	pub fn add(&self, text: &str) -> Result<usize> {
+		let text = text.to_wide();
+		let index = self.send(message::AddString, 0, text.as_pwstr().0 as _);
+		item_index(index, "failed to add combo box item")
+	}
+
+	/// Insert an item at `index`, the list isn't sorted even with the `Sort` style.
+	pub fn insert(&self, index: usize, text: &str) -> Result<usize> {
+		let text = text.to_wide();
+		let index = self.send(message::InsertString, index, text.as_pwstr().0 as _);
+		item_index(index, "failed to insert combo box item")
+	}
+
+	pub fn remove(&self, index: usize) -> Result<()> {
+		let remaining = self.send(message::DeleteString, index, 0);
+		item_index(remaining, "failed to remove combo box item").map(|_| ())
+	}
+
+	pub fn clear(&self) {
+		self.send(message::ResetContent, 0, 0);
+	}
+
+	/// Replace all the items, the selection is cleared.
+	pub fn set_items(&self, items: &[String]) -> Result<()> {
+		self.clear();
+		for item in items {
+			self.add(item)?;
+		}
+		Ok(())
+	}
+
+	pub fn item(&self, index: usize) -> Result<String> {
+		let len = self.send(message::GetLbTextLen, index, 0);
+		let len = item_index(len, &format!("no item {} in combo box", index))?;
+		let mut buf = vec![0u16; len + 1];
+		let copied = self.send(message::GetLbText, index, buf.as_mut_ptr() as _);
+		let copied = item_index(copied, "failed to get combo box item")?;
+		Ok(String::from_utf16_lossy(&buf[..copied]))
+	}
+
+	pub fn items(&self) -> Result<Vec<String>> {
+		(0..self.len()).map(|index| self.item(index)).collect()
+	}
+
+	/// Index of the first item equal to `text`.
+	pub fn find_exact(&self, text: &str) -> Option<usize> {
+		let text = text.to_wide();
+		// wparam: search the whole list
+		self.index(message::FindStringExact, usize::MAX, text.as_pwstr().0 as _)
+	}
+
+	/// Index of the first item starting with `prefix`.
+	pub fn find_prefix(&self, prefix: &str) -> Option<usize> {
+		let prefix = prefix.to_wide();
+		self.index(message::FindString, usize::MAX, prefix.as_pwstr().0 as _)
+	}
+
+	/// Index of the selected item, `None` when nothing is selected or the user typed a text.
+	pub fn selected(&self) -> Option<usize> {
+		self.index(message::GetCurSel, 0, 0)
+	}
+
+	pub fn selected_text(&self) -> Result<Option<String>> {
+		self.selected().map(|index| self.item(index)).transpose()
+	}
+
+	/// Select the item at `index` and show it in the field, `None` clears the selection and the field. Notifications are
+	/// only sent for changes made by the user.
+	pub fn select(&self, index: Option<usize>) -> Result<()> {
+		match index {
+			Some(index) => {
+				let selected = self.send(message::SetCurSel, index, 0);
+				item_index(selected, &format!("no item {} in combo box", index)).map(|_| ())
+			}
+			None => {
+				// returns CB_ERR when clearing
+				self.send(message::SetCurSel, usize::MAX, 0);
+				Ok(())
+			}
+		}
+	}
+
+	/// Text of the field: the selected item, or what the user typed in an editable combo box.
+	pub fn text(&self) -> Result<String> {
+		get_window_text(self.h_window)
+	}
+
+	pub fn set_text(&self, text: &str) -> Result<()> {
+		set_window_text(self.h_window, text)
+	}
+
+	/// Edit field of a `Simple` or `DropDown` combo box, `None` for a `DropDownList`.
+	pub fn edit(&self) -> Result<Option<Edit>> {
+		let mut info = COMBOBOXINFO {
+			cbSize: std::mem::size_of::<COMBOBOXINFO>() as u32,
+			..Default::default()
+		};
+		assert_eq(
+			unsafe { GetComboBoxInfo(self.h_window, &mut info) }.as_bool(),
+			true,
+			"failed to get combo box info",
+		)
+		.with_last_win32_err()?;
+		// without edit field the item is the combo box itself
+		let edit = match info.hwndItem {
+			0 => None,
+			h_window if h_window == self.h_window => None,
+			h_window => Some(Edit::from_raw(h_window)),
+		};
+		Ok(edit)
+	}
+
+	/// Largest number of characters the user can type in the field, 0 for no limit.
+	pub fn set_limit(&self, limit: usize) {
+		self.send(message::LimitText, limit, 0);
+	}
+
+	/// Hint shown while the field is empty. Only for editable combo boxes.
+	pub fn set_cue_banner(&self, text: &str) -> Result<()> {
+		let text = text.to_wide();
+		let result = self.send(message::SetCueBanner, 0, text.as_pwstr().0 as _);
+		assert_eq(result, 1, "failed to set combo box cue banner")
+	}
+
+	pub fn show_dropdown(&self, show: bool) {
+		self.send(message::ShowDropDown, show as _, 0);
+	}
+
+	pub fn is_dropped_down(&self) -> bool {
+		self.send(message::GetDroppedState, 0, 0) != 0
+	}
+
+	pub fn set_enabled(&self, enabled: bool) {
+		unsafe { EnableWindow(self.h_window, enabled) };
+	}
+}
+
+impl ItemList for ComboBox {
+	fn list_window(&self) -> HWND {
+		self.h_window
+	}
+}
+
+/// Options of a combo box, created as a child of `parent`. The height given to `size` includes the list, dropped down or
+/// not: the field gets the height of the font.
+///
+/// ```ignore
+/// let size = ComboBoxBuilder::new(h_window)
+///     .kind(ComboKind::DropDownList)
+///     .items(&["Small", "Medium", "Large"])
+///     .selected(1)
+///     .at(10, 10)
+///     .size(120, 200)
+///     .create()?;
+/// ```
+pub struct ComboBoxBuilder {
+	frame: ChildFrame,
+	kind: ComboKind,
+	style: style::Type,
+	items: Vec<String>,
+	selected: Option<usize>,
+	limit: Option<usize>,
+	cue_banner: Option<String>,
+}
+
+impl ComboBoxBuilder {
+	/// Drop-down combo box, items kept in the order they are added.
+	pub fn new(parent: HWND) -> Self {
+		Self {
+			frame: ChildFrame::new(parent, 100, 200),
+			kind: ComboKind::DropDown,
+			style: style::AutoHScroll,
+			items: vec![],
+			selected: None,
+			limit: None,
+			cue_banner: None,
+		}
+	}
+
+	frame_setters!();
+
+	pub fn kind(mut self, kind: ComboKind) -> Self {
+		self.kind = kind;
+		self
+	}
+
+	/// Styles added to the one of the kind, e.g. `style::Sort` or `style::Uppercase`.
+	pub fn style(mut self, style: style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	/// Keep the items in alphabetical order.
+	pub fn sort(mut self, sort: bool) -> Self {
+		self.style.set(style::Sort, sort);
+		self
+	}
+
+	pub fn items(mut self, items: &[&str]) -> Self {
+		self.items = items.iter().map(|item| (*item).to_owned()).collect();
+		self
+	}
+
+	pub fn selected(mut self, index: usize) -> Self {
+		self.selected = Some(index);
+		self
+	}
+
+	pub fn limit(mut self, limit: usize) -> Self {
+		self.limit = Some(limit);
+		self
+	}
+
+	pub fn cue_banner(mut self, text: &str) -> Self {
+		self.cue_banner = Some(text.to_owned());
+		self
+	}
+
+	pub fn create(self) -> Result<ComboBox> {
+		let kind = match self.kind {
+			ComboKind::Simple => style::Simple,
+			ComboKind::DropDown => style::DropDown,
+			ComboKind::DropDownList => style::DropDownList,
+		};
+		let builder = self.frame.window_builder(COMBO_BOX_CLASS).style(
+			window::style::Type::from(self.style | kind)
+				| window::style::Visible
+				| window::style::Child
+				| window::style::VScroll
+				| window::style::Tabstop,
+		);
+		let combo = ComboBox::from_raw(builder.create()?);
+
+		// not left on the parent if it can't be set up
+		if let Err(e) = self.set_up(&combo) {
+			unsafe { DestroyWindow(combo.h_window) };
+			return Err(e);
+		}
+		Ok(combo)
+	}
+
+	fn set_up(&self, combo: &ComboBox) -> Result<()> {
+		window::set_default_font(combo.h_window);
+		for item in &self.items {
+			combo.add(item)?;
+		}
+		combo.select(self.selected)?;
+		if let Some(limit) = self.limit {
+			combo.set_limit(limit);
+		}
+		if let Some(text) = &self.cue_banner {
+			combo.set_cue_banner(text)?;
+		}
+		Ok(())
+	}
+}
+
+impl From<style::Type> for window::style::Type {
+	fn from(style: style::Type) -> Self {
+		Self(style.0)
+	}
+}
+
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod style {
+	use crate::flags::flags;
+	use windows::Win32::UI::WindowsAndMessaging::*;
+
+	flags! {
+		pub struct Type(pub WINDOW_STYLE);
+
+		AutoHScroll = CBS_AUTOHSCROLL as u32;
+		DisableNoScroll = CBS_DISABLENOSCROLL as u32;
+		DropDown = CBS_DROPDOWN as u32;
+		DropDownList = CBS_DROPDOWNLIST as u32;
+		HasStrings = CBS_HASSTRINGS as u32;
+		Lowercase = CBS_LOWERCASE as u32;
+		NoIntegralHeight = CBS_NOINTEGRALHEIGHT as u32;
+		OemConvert = CBS_OEMCONVERT as u32;
+		OwnerDrawFixed = CBS_OWNERDRAWFIXED as u32;
+		OwnerDrawVariable = CBS_OWNERDRAWVARIABLE as u32;
+		Simple = CBS_SIMPLE as u32;
+		Sort = CBS_SORT as u32;
+		Uppercase = CBS_UPPERCASE as u32;
+	}
+}
+
+// messages sent to combo boxes
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod message {
+	use windows::Win32::UI::{Controls::*, WindowsAndMessaging::*};
+	pub type Type = u32;
+
+	pub const AddString: Type = CB_ADDSTRING;
+	pub const DeleteString: Type = CB_DELETESTRING;
+	pub const FindString: Type = CB_FINDSTRING;
+	pub const FindStringExact: Type = CB_FINDSTRINGEXACT;
+	pub const GetCount: Type = CB_GETCOUNT;
+	pub const GetCueBanner: Type = CB_GETCUEBANNER;
+	pub const GetCurSel: Type = CB_GETCURSEL;
+	pub const GetDroppedState: Type = CB_GETDROPPEDSTATE;
+	pub const GetEditSel: Type = CB_GETEDITSEL;
+	pub const GetLbText: Type = CB_GETLBTEXT;
+	pub const GetLbTextLen: Type = CB_GETLBTEXTLEN;
+	pub const GetMinVisible: Type = CB_GETMINVISIBLE;
+	pub const InsertString: Type = CB_INSERTSTRING;
+	pub const LimitText: Type = CB_LIMITTEXT;
+	pub const ResetContent: Type = CB_RESETCONTENT;
+	pub const SelectString: Type = CB_SELECTSTRING;
+	pub const SetCueBanner: Type = CB_SETCUEBANNER;
+	pub const SetCurSel: Type = CB_SETCURSEL;
+	pub const SetEditSel: Type = CB_SETEDITSEL;
+	pub const SetMinVisible: Type = CB_SETMINVISIBLE;
+	pub const ShowDropDown: Type = CB_SHOWDROPDOWN;
+}
+
+// notifications sent to the parent in WM_COMMAND
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod event {
+	use windows::Win32::UI::WindowsAndMessaging::*;
+	pub type Type = u32;
+
+	pub const CloseUp: Type = CBN_CLOSEUP;
+	pub const DblClk: Type = CBN_DBLCLK;
+	pub const DropDown: Type = CBN_DROPDOWN;
+	pub const EditChange: Type = CBN_EDITCHANGE;
+	pub const EditUpdate: Type = CBN_EDITUPDATE;
+	pub const ErrSpace: Type = CBN_ERRSPACE as u32;
+	pub const KillFocus: Type = CBN_KILLFOCUS;
+	pub const SelChange: Type = CBN_SELCHANGE;
+	pub const SelEndCancel: Type = CBN_SELENDCANCEL;
+	pub const SelEndOk: Type = CBN_SELENDOK;
+	pub const SetFocus: Type = CBN_SETFOCUS;
+}
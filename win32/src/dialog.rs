@@ -0,0 +1,377 @@
+use std::{
+	any::TypeId,
+	cell::RefCell,
+	collections::hash_map::DefaultHasher,
+	fmt,
+	hash::{Hash, Hasher},
+};
+
+use crate::{
+	assert::Result,
+	layout::{Control, Screen},
+	loword,
+	window::{
+		ex_style, style, MessageAction, MessageLoop, WindowBase, WindowBuilder, WindowHandler,
+	},
+};
+use derive::WindowBase;
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+	UI::{
+		Input::KeyboardAndMouse::{EnableWindow, SetFocus},
+		WindowsAndMessaging::{
+			DestroyWindow, GetNextDlgTabItem, IsDialogMessageW, COLOR_BTNFACE, MSG,
+		},
+	},
+};
+
+/// Ids of the buttons closing a dialog. `IsDialogMessageW` sends them for Enter and Esc.
+#[allow(non_upper_case_globals)]
+pub mod id {
+	use windows::Win32::UI::WindowsAndMessaging::{IDCANCEL, IDOK};
+
+	pub const Ok: u16 = IDOK as u16;
+	pub const Cancel: u16 = IDCANCEL as u16;
+}
+
+pub type OkHandler<S, T> = Box<dyn Fn(&mut S) -> Option<T>>;
+pub type CancelHandler<S, T> = Box<dyn Fn(&mut S) -> T>;
+
+#[derive(Debug)]
+pub enum DialogError {
+	// closed with Esc, the close button or the cancel button, and no `on_cancel` value
+	Cancelled,
+	// WM_QUIT received while the dialog was open
+	Quit,
+}
+
+impl fmt::Display for DialogError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DialogError::Cancelled => write!(f, "dialog cancelled"),
+			DialogError::Quit => write!(f, "app quit while the dialog was open"),
+		}
+	}
+}
+
+impl std::error::Error for DialogError {}
+
+/// Content and behaviour of a dialog: a `Control` tree built from a state `S`, like `SimpleApp`, closing with a `T`.
+///
+/// ```ignore
+/// let builder = DialogBuilder::new("Rename", name, |name: &String| {
+///     VStack::new()
+///         .items(vec![
+///             InputText::new(name).on_change(|name: &mut String, text| *name = text.to_owned()).done(),
+///             Button::new("OK").id(dialog::id::Ok).default_button(true).done(),
+///             Button::new("Cancel").id(dialog::id::Cancel).done(),
+///         ])
+///         .done()
+/// })
+/// .on_ok(|name| Some(name.clone()));
+/// let name: String = Dialog::show_modal(self.h_window(), builder)?;
+/// ```
+pub struct DialogBuilder<S, T> {
+	title: String,
+	width: i32,
+	height: i32,
+	state: S,
+	layout: Box<dyn Fn(&S) -> Control>,
+	on_ok: Option<OkHandler<S, T>>,
+	on_cancel: Option<CancelHandler<S, T>>,
+}
+
+impl<S: 'static, T: 'static> DialogBuilder<S, T> {
+	pub fn new(title: &str, state: S, layout: impl Fn(&S) -> Control + 'static) -> Self {
+		Self {
+			title: title.to_owned(),
+			width: 300,
+			height: 200,
+			state,
+			layout: Box::new(layout),
+			on_ok: None,
+			on_cancel: None,
+		}
+	}
+
+	pub fn size(mut self, width: i32, height: i32) -> Self {
+		self.width = width;
+		self.height = height;
+		self
+	}
+
+	/// Result for Enter and the `id::Ok` button. `None` keeps the dialog open, e.g. while an input is invalid.
+	pub fn on_ok(mut self, f: impl Fn(&mut S) -> Option<T> + 'static) -> Self {
+		self.on_ok = Some(Box::new(f));
+		self
+	}
+
+	/// Result for Esc, the close button and the `id::Cancel` button. Without it the dialog fails with
+	/// `DialogError::Cancelled`.
+	pub fn on_cancel(mut self, f: impl Fn(&mut S) -> T + 'static) -> Self {
+		self.on_cancel = Some(Box::new(f));
+		self
+	}
+}
+
+pub struct Dialog;
+
+impl Dialog {
+	/// Show the dialog and return its result once closed. The owner is disabled in the meantime.
+	pub fn show_modal<S: 'static, T: 'static>(
+		owner: HWND,
+		builder: DialogBuilder<S, T>,
+	) -> Result<T> {
+		unsafe { EnableWindow(owner, false) };
+		let dialog = match DialogWindow::create(owner, builder, None) {
+			Ok(dialog) => dialog,
+			Err(e) => {
+				unsafe { EnableWindow(owner, true) };
+				return Err(e);
+			}
+		};
+		let h_dialog = dialog.h_window;
+
+		// win_proc writes to the dialog while the loop runs, only reach it through a raw pointer meanwhile
+		let dialog = Box::into_raw(dialog);
+		let quit = MessageLoop::without_filters()
+			// Tab, Enter and Esc
+			.filter(move |msg| unsafe { IsDialogMessageW(h_dialog, msg) }.as_bool())
+			.run_until(|| unsafe { (*dialog).result.is_some() });
+		let mut dialog = unsafe { Box::from_raw(dialog) };
+
+		// enabled before the dialog is destroyed, so that the owner gets activated instead of another app
+		unsafe { EnableWindow(owner, true) };
+		unsafe { DestroyWindow(h_dialog) };
+
+		// WM_QUIT is posted again for the outer event loop
+		if quit?.is_some() {
+			return Err(DialogError::Quit.into());
+		}
+		dialog
+			.result
+			.take()
+			.unwrap_or_else(|| Err(DialogError::Cancelled.into()))
+	}
+
+	/// Show the dialog and return right away, `on_close` gets the result. The dialog is kept alive by the thread's
+	/// `event_loop`, which also handles its Tab, Enter and Esc keys.
+	pub fn show_modeless<S: 'static, T: 'static>(
+		owner: HWND,
+		builder: DialogBuilder<S, T>,
+		on_close: impl FnOnce(Result<T>) + 'static,
+	) -> Result<HWND> {
+		let dialog = DialogWindow::create(owner, builder, Some(Box::new(on_close)))?;
+		let h_dialog = dialog.h_window;
+		MODELESS.with(|dialogs| dialogs.borrow_mut().push(dialog));
+		Ok(h_dialog)
+	}
+}
+
+trait ModelessDialog {
+	fn h_window(&self) -> HWND;
+	fn is_destroyed(&self) -> bool;
+}
+
+thread_local! {
+	static MODELESS: RefCell<Vec<Box<dyn ModelessDialog>>> = RefCell::new(Vec::new());
+}
+
+/// Let the modeless dialogs of the thread handle `msg`, returns true if one did. Dialogs destroyed since the last
+/// message are dropped.
+pub fn is_modeless_dialog_message(msg: &MSG) -> bool {
+	let dialogs: Vec<HWND> = MODELESS.with(|dialogs| {
+		let mut dialogs = dialogs.borrow_mut();
+		dialogs.retain(|dialog| !dialog.is_destroyed());
+		dialogs.iter().map(|dialog| dialog.h_window()).collect()
+	});
+	// the borrow is released, handling the message may open another dialog
+	dialogs
+		.into_iter()
+		.any(|h_dialog| unsafe { IsDialogMessageW(h_dialog, msg) }.as_bool())
+}
+
+#[derive(WindowBase)]
+struct DialogWindow<S, T> {
+	h_instance: HINSTANCE,
+	h_window: HWND,
+	// only None before `create`
+	state: Option<S>,
+	layout: Box<dyn Fn(&S) -> Control>,
+	on_ok: Option<OkHandler<S, T>>,
+	on_cancel: Option<CancelHandler<S, T>>,
+	// only for modeless dialogs
+	on_close: Option<Box<dyn FnOnce(Result<T>)>>,
+	screen: Option<Screen>,
+	result: Option<Result<T>>,
+	destroyed: bool,
+}
+
+impl<S, T> Default for DialogWindow<S, T> {
+	fn default() -> Self {
+		Self {
+			h_instance: Default::default(),
+			h_window: Default::default(),
+			state: None,
+			layout: Box::new(|_| Control::None),
+			on_ok: None,
+			on_cancel: None,
+			on_close: None,
+			screen: None,
+			result: None,
+			destroyed: false,
+		}
+	}
+}
+
+impl<S: 'static, T: 'static> DialogWindow<S, T> {
+	fn create(
+		owner: HWND,
+		builder: DialogBuilder<S, T>,
+		on_close: Option<Box<dyn FnOnce(Result<T>)>>,
+	) -> Result<Box<Self>> {
+		let DialogBuilder {
+			title,
+			width,
+			height,
+			state,
+			layout,
+			on_ok,
+			on_cancel,
+		} = builder;
+
+		// a class per dialog type, the class win_proc casts the window state to that type
+		let mut hasher = DefaultHasher::new();
+		TypeId::of::<Self>().hash(&mut hasher);
+		let class_name = format!("Dialog{:016x}", hasher.finish());
+
+		Self::create_with(
+			WindowBuilder::new(&class_name)
+				.text(&title)
+				.owner(owner)
+				.center_on_owner()
+				.size(width, height)
+				.style(style::Popup | style::Caption | style::SysMenu)
+				.ex_style(ex_style::DlgModalFrame | ex_style::ControlParent)
+				.bg_brush(COLOR_BTNFACE + 1),
+			|h_instance| Self {
+				h_instance,
+				state: Some(state),
+				layout,
+				on_ok,
+				on_cancel,
+				on_close,
+				..Default::default()
+			},
+		)
+	}
+
+	fn state_mut(&mut self) -> &mut S {
+		self.state.as_mut().expect("no dialog state")
+	}
+
+	fn ok(&mut self) {
+		let result = match &self.on_ok {
+			Some(on_ok) => on_ok(self.state.as_mut().expect("no dialog state")),
+			None => None,
+		};
+		if let Some(result) = result {
+			self.close(Ok(result));
+		}
+	}
+
+	fn cancel(&mut self) {
+		let result = match &self.on_cancel {
+			Some(on_cancel) => Ok(on_cancel(self.state.as_mut().expect("no dialog state"))),
+			None => Err(DialogError::Cancelled.into()),
+		};
+		self.close(result);
+	}
+
+	fn close(&mut self, result: Result<T>) {
+		if self.result.is_some() {
+			return;
+		}
+		self.result = Some(result);
+		// a modal dialog is destroyed by `show_modal` once its loop ends
+		if self.on_close.is_some() {
+			unsafe { DestroyWindow(self.h_window) };
+		}
+	}
+}
+
+impl<S: 'static, T: 'static> ModelessDialog for DialogWindow<S, T> {
+	fn h_window(&self) -> HWND {
+		self.h_window
+	}
+
+	fn is_destroyed(&self) -> bool {
+		self.destroyed
+	}
+}
+
+impl<S: 'static, T: 'static> WindowHandler for DialogWindow<S, T> {
+	fn on_create_mut(&mut self) -> Result<MessageAction> {
+		let root = (self.layout)(self.state.as_ref().expect("no dialog state"));
+		let mut screen = Screen::new(self.h_instance, self.h_window);
+		screen.render(root)?;
+		self.screen = Some(screen);
+
+		// keyboard focus on the first control, then moved with Tab by IsDialogMessageW
+		unsafe {
+			let first = GetNextDlgTabItem(self.h_window, 0, false);
+			if first != 0 {
+				SetFocus(first);
+			}
+		}
+		Ok(MessageAction::Continue)
+	}
+
+	fn on_size(&self) -> Result<MessageAction> {
+		if let Some(screen) = &self.screen {
+			screen.relayout()?;
+		}
+		Ok(MessageAction::Continue)
+	}
+
+	fn on_command(&mut self, lparam: LPARAM, wparam: WPARAM) -> Result<MessageAction> {
+		let command = match &self.screen {
+			Some(screen) => screen.command(wparam, lparam)?,
+			None => None,
+		};
+		if let Some(command) = command {
+			command.invoke(self.state_mut())?;
+			let root = (self.layout)(self.state.as_ref().expect("no dialog state"));
+			if let Some(screen) = &mut self.screen {
+				screen.update(root)?;
+			}
+		}
+
+		let id: u16 = loword(wparam).try_into().expect("loword is 16 bits");
+		match id {
+			id::Ok => self.ok(),
+			id::Cancel => self.cancel(),
+			_ => return Ok(MessageAction::Continue),
+		}
+		Ok(MessageAction::FullyHandled)
+	}
+
+	fn on_close(&mut self) -> Result<MessageAction> {
+		self.cancel();
+		// destroyed by `close` or `show_modal`
+		Ok(MessageAction::FullyHandled)
+	}
+
+	fn on_destroy(&mut self) -> Result<MessageAction> {
+		self.destroyed = true;
+		if let Some(on_close) = self.on_close.take() {
+			// destroyed without being closed, e.g. along with its owner
+			let result = self
+				.result
+				.take()
+				.unwrap_or_else(|| Err(DialogError::Cancelled.into()));
+			on_close(result);
+		}
+		Ok(MessageAction::Continue)
+	}
+}
@@ -0,0 +1,285 @@
+// Flag sets over `u32` bits used for window, class and control styles: set operations, `Debug` as
+// `Visible | Child | Tabstop`, and parsing from the same format.
+
+use std::fmt;
+
+/// Returned when parsing a flag set from a string containing an unknown flag name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFlagsError {
+	pub flag: String,
+}
+
+impl fmt::Display for ParseFlagsError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "unknown flag {:?}", self.flag)
+	}
+}
+
+impl std::error::Error for ParseFlagsError {}
+
+/// Declare a flag set newtype along with its named flags, as constants of the enclosing module.
+///
+/// ```ignore
+/// flags! {
+///     pub struct Type(pub WINDOW_STYLE);
+///     Child = WS_CHILD;
+///     Visible = WS_VISIBLE;
+/// }
+/// ```
+macro_rules! flags {
+	(
+		$(#[$meta:meta])*
+		pub struct $t:ident(pub $bits:ty);
+		$($(#[$flag_meta:meta])* $name:ident = $value:expr;)*
+	) => {
+		$(#[$meta])*
+		#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+		pub struct $t(pub $bits);
+
+		$($(#[$flag_meta])* pub const $name: $t = $t($value);)*
+
+		impl $t {
+			/// Named flags, in declaration order.
+			pub const FLAGS: &'static [(&'static str, $t)] = &[$((stringify!($name), $name),)*];
+
+			pub const fn empty() -> Self {
+				Self(0)
+			}
+
+			pub const fn is_empty(self) -> bool {
+				self.0 == 0
+			}
+
+			/// True if all the flags of `other` are set.
+			pub const fn contains(self, other: Self) -> bool {
+				self.0 & other.0 == other.0
+			}
+
+			/// True if any of the flags of `other` is set.
+			pub const fn intersects(self, other: Self) -> bool {
+				self.0 & other.0 != 0
+			}
+
+			pub fn insert(&mut self, other: Self) {
+				self.0 |= other.0;
+			}
+
+			pub fn remove(&mut self, other: Self) {
+				self.0 &= !other.0;
+			}
+
+			pub fn toggle(&mut self, other: Self) {
+				self.0 ^= other.0;
+			}
+
+			pub fn set(&mut self, other: Self, value: bool) {
+				match value {
+					true => self.insert(other),
+					false => self.remove(other),
+				}
+			}
+
+			/// Same as `set`, by value.
+			pub fn with(mut self, other: Self, value: bool) -> Self {
+				self.set(other, value);
+				self
+			}
+		}
+
+		impl std::ops::BitOr for $t {
+			type Output = Self;
+			fn bitor(self, rhs: Self) -> Self::Output {
+				Self(self.0 | rhs.0)
+			}
+		}
+
+		impl std::ops::BitOrAssign for $t {
+			fn bitor_assign(&mut self, rhs: Self) {
+				self.0 |= rhs.0;
+			}
+		}
+
+		impl std::ops::BitAnd for $t {
+			type Output = Self;
+			fn bitand(self, rhs: Self) -> Self::Output {
+				Self(self.0 & rhs.0)
+			}
+		}
+
+		impl std::ops::BitAndAssign for $t {
+			fn bitand_assign(&mut self, rhs: Self) {
+				self.0 &= rhs.0;
+			}
+		}
+
+		impl std::ops::BitXor for $t {
+			type Output = Self;
+			fn bitxor(self, rhs: Self) -> Self::Output {
+				Self(self.0 ^ rhs.0)
+			}
+		}
+
+		// flags of self not in rhs
+		impl std::ops::Sub for $t {
+			type Output = Self;
+			fn sub(self, rhs: Self) -> Self::Output {
+				Self(self.0 & !rhs.0)
+			}
+		}
+
+		impl std::ops::Not for $t {
+			type Output = Self;
+			fn not(self) -> Self::Output {
+				Self(!self.0)
+			}
+		}
+
+		impl std::fmt::Debug for $t {
+			fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+				$crate::flags::fmt_flags(f, self.0, Self::FLAGS.iter().map(|(name, flag)| (*name, flag.0)))
+			}
+		}
+
+		impl std::str::FromStr for $t {
+			type Err = $crate::flags::ParseFlagsError;
+			fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+				$crate::flags::parse_flags(s, Self::FLAGS.iter().map(|(name, flag)| (*name, flag.0))).map(Self)
+			}
+		}
+	};
+}
+pub(crate) use flags;
+
+// Flags are written when all their bits are still unwritten, composite flags first, e.g. `OverlappedWindow` rather than
+// `Caption | SysMenu | ...`. Flags with the same number of bits are taken in declaration order, so that the first of
+// two aliases wins. Unnamed bits are written in hex.
+pub(crate) fn fmt_flags<'a>(
+	f: &mut fmt::Formatter,
+	bits: u32,
+	flags: impl Iterator<Item = (&'a str, u32)> + Clone,
+) -> fmt::Result {
+	if bits == 0 {
+		// e.g. Overlapped for a window style, the first flag of value 0 if any
+		let name = flags
+			.clone()
+			.find(|(_, flag)| *flag == 0)
+			.map(|(name, _)| name);
+		return f.write_str(name.unwrap_or("(empty)"));
+	}
+
+	let mut flags: Vec<_> = flags.filter(|(_, flag)| *flag != 0).collect();
+	flags.sort_by_key(|(_, flag)| std::cmp::Reverse(flag.count_ones()));
+
+	let mut remaining = bits;
+	let mut first = true;
+	let mut write = |f: &mut fmt::Formatter, s: &dyn fmt::Display| {
+		let sep = if first { "" } else { " | " };
+		first = false;
+		write!(f, "{}{}", sep, s)
+	};
+	for (name, flag) in flags {
+		if flag & remaining == flag {
+			remaining &= !flag;
+			write(f, &name)?;
+		}
+	}
+	if remaining != 0 {
+		write(f, &format_args!("{:#x}", remaining))?;
+	}
+	Ok(())
+}
+
+// Names separated by `|`, and hex values as written by `fmt_flags`. An empty string is the empty set.
+pub(crate) fn parse_flags<'a>(
+	s: &str,
+	flags: impl Iterator<Item = (&'a str, u32)> + Clone,
+) -> Result<u32, ParseFlagsError> {
+	if s.trim().is_empty() {
+		return Ok(0);
+	}
+	s.split('|').map(str::trim).try_fold(0, |bits, part| {
+		let hex = part
+			.strip_prefix("0x")
+			.and_then(|hex| u32::from_str_radix(hex, 16).ok());
+		let flag = flags
+			.clone()
+			.find(|(name, _)| *name == part)
+			.map(|(_, flag)| flag)
+			.or(hex)
+			.ok_or_else(|| ParseFlagsError {
+				flag: part.to_owned(),
+			})?;
+		Ok(bits | flag)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::window::style;
+
+	#[allow(dead_code)]
+	#[allow(non_upper_case_globals)]
+	mod test_flags {
+		flags! {
+			pub struct Type(pub u32);
+
+			A = 0x1;
+			B = 0x2;
+			Both = 0x3;
+			Alias = 0x2;
+		}
+	}
+
+	fn debug(style: style::Type) -> String {
+		format!("{:?}", style)
+	}
+
+	#[test]
+	fn fmt() {
+		assert_eq!(
+			debug(style::Visible | style::Child | style::Tabstop),
+			"Child | Visible | Tabstop"
+		);
+		// composite flags first, the first of two aliases wins
+		assert_eq!(
+			debug(style::OverlappedWindow | style::Visible),
+			"OverlappedWindow | Visible"
+		);
+		assert_eq!(debug(style::Caption | style::SysMenu), "Caption | SysMenu");
+		assert_eq!(debug(style::Type::empty()), "Overlapped");
+		assert_eq!(debug(style::Child | style::Type(0x4)), "Child | 0x4");
+
+		assert_eq!(format!("{:?}", test_flags::Type::empty()), "(empty)");
+		assert_eq!(format!("{:?}", test_flags::B), "B");
+		assert_eq!(format!("{:?}", test_flags::Type(0xB)), "Both | 0x8");
+	}
+
+	#[test]
+	fn parse() {
+		for s in [
+			"Child | Visible | Tabstop",
+			"OverlappedWindow | Visible",
+			"Overlapped",
+			"Child | 0x4",
+		] {
+			let style: style::Type = s.parse().unwrap();
+			assert_eq!(debug(style), s);
+		}
+		assert_eq!(
+			"Visible|Child".parse::<style::Type>(),
+			Ok(style::Child | style::Visible)
+		);
+		assert_eq!(" ".parse::<style::Type>(), Ok(style::Type::empty()));
+
+		let e = "Child | Bogus".parse::<style::Type>().unwrap_err();
+		assert_eq!(
+			e,
+			ParseFlagsError {
+				flag: "Bogus".to_owned()
+			}
+		);
+		assert_eq!(e.to_string(), "unknown flag \"Bogus\"");
+		assert!("0xZZ".parse::<style::Type>().is_err());
+	}
+}
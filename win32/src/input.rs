@@ -1,15 +1,9 @@
-use windows::Win32::{
-	Foundation::{HINSTANCE, HWND},
-	UI::WindowsAndMessaging::{CreateWindowExW, WINDOW_STYLE},
-};
+use windows::Win32::Foundation::{HINSTANCE, HWND};
 
-use crate::{
-	assert::{assert_ne, Result, WithLastWin32Error},
-	wide_string::ToWide,
-	window,
-};
+use crate::{assert::Result, window};
 
-const EDIT_CLASS: &str = "EDIT";
+mod edit;
+pub use edit::*;
 
 pub fn create_text_input(
 	owner: HWND,
@@ -19,33 +13,47 @@ pub fn create_text_input(
 	y: i32,
 	width: i32,
 	height: i32,
-) -> Result<()> {
-	let styles = TryInto::<WINDOW_STYLE>::try_into(style::Left)
-		.expect("cannot cast to WINDOW_STYLE")
-		| (window::style::Visible | window::style::Child | window::style::Overlapped).0;
-	let ex_styles = window::ex_style::ClientEdge
-		| window::ex_style::Left
-		| window::ex_style::LtrReading
-		| window::ex_style::RightScrollbar;
-	let control = unsafe {
-		CreateWindowExW(
-			ex_styles.0,
-			EDIT_CLASS.to_wide().as_pwstr(),
-			text.to_wide().as_pwstr(),
-			styles,
-			x,
-			y,
-			width,
-			height,
-			owner,
-			None,
-			h_instance,
-			std::ptr::null(),
-		)
-	};
-	assert_ne(control, 0, "failed to create edit control").with_last_win32_err()?;
-
-	Ok(())
+) -> Result<HWND> {
+	let edit = EditBuilder::new(owner)
+		.h_instance(h_instance)
+		.style(style::Type::empty().with_align(TextAlign::Left))
+		.text(text)
+		.at(x, y)
+		.size(width, height)
+		.create()?;
+	Ok(edit.h_window())
+}
+
+// messages sent to edit controls
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod message {
+	use windows::Win32::UI::Controls::*;
+	pub type Type = u32;
+
+	pub const CanUndo: Type = EM_CANUNDO;
+	pub const EmptyUndoBuffer: Type = EM_EMPTYUNDOBUFFER;
+	pub const GetCueBanner: Type = EM_GETCUEBANNER;
+	pub const GetLimitText: Type = EM_GETLIMITTEXT;
+	pub const GetLine: Type = EM_GETLINE;
+	pub const GetLineCount: Type = EM_GETLINECOUNT;
+	pub const GetMargins: Type = EM_GETMARGINS;
+	pub const GetModify: Type = EM_GETMODIFY;
+	pub const GetPasswordChar: Type = EM_GETPASSWORDCHAR;
+	pub const GetSel: Type = EM_GETSEL;
+	pub const LineFromChar: Type = EM_LINEFROMCHAR;
+	pub const LineIndex: Type = EM_LINEINDEX;
+	pub const LineLength: Type = EM_LINELENGTH;
+	pub const ReplaceSel: Type = EM_REPLACESEL;
+	pub const ScrollCaret: Type = EM_SCROLLCARET;
+	pub const SetCueBanner: Type = EM_SETCUEBANNER;
+	pub const SetLimitText: Type = EM_SETLIMITTEXT;
+	pub const SetMargins: Type = EM_SETMARGINS;
+	pub const SetModify: Type = EM_SETMODIFY;
+	pub const SetPasswordChar: Type = EM_SETPASSWORDCHAR;
+	pub const SetReadonly: Type = EM_SETREADONLY;
+	pub const SetSel: Type = EM_SETSEL;
+	pub const Undo: Type = EM_UNDO;
 }
 
 #[allow(dead_code)]
@@ -83,7 +91,6 @@ pub mod feature {
 // ???
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
-
 pub mod eds {
 	use windows::Win32::UI::WindowsAndMessaging::*;
 	pub type Type = u32;
@@ -95,7 +102,6 @@ pub mod eds {
 // ???
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
-
 pub mod eimes {
 	use windows::Win32::UI::WindowsAndMessaging::*;
 	pub type Type = u32;
@@ -129,7 +135,6 @@ pub mod event {
 // ???
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
-
 pub mod end_session {
 	use windows::Win32::UI::WindowsAndMessaging::*;
 	pub type Type = u32;
@@ -142,21 +147,88 @@ pub mod end_session {
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
 pub mod style {
+	use super::TextAlign;
+	use crate::flags::flags;
 	use windows::Win32::UI::WindowsAndMessaging::*;
-	pub type Type = i32;
-
-	pub const AutoHScroll: Type = ES_AUTOHSCROLL;
-	pub const AutoVScroll: Type = ES_AUTOVSCROLL;
-	pub const Center: Type = ES_CENTER;
-	pub const Left: Type = ES_LEFT;
-	pub const Lowercase: Type = ES_LOWERCASE;
-	pub const Multiline: Type = ES_MULTILINE;
-	pub const NoHideSel: Type = ES_NOHIDESEL;
-	pub const Number: Type = ES_NUMBER;
-	pub const OemConvert: Type = ES_OEMCONVERT;
-	pub const Password: Type = ES_PASSWORD;
-	pub const Readonly: Type = ES_READONLY;
-	pub const Right: Type = ES_RIGHT;
-	pub const Uppercase: Type = ES_UPPERCASE;
-	pub const WantReturn: Type = ES_WANTRETURN;
+
+	// bits of the alignment, see `TextAlign`, ES_LEFT being 0
+	const ALIGN_MASK: u32 = (ES_LEFT | ES_CENTER | ES_RIGHT) as u32;
+
+	// the alignment of the text is kept out of the flags
+	flags! {
+		pub struct Type(pub WINDOW_STYLE);
+
+		AutoHScroll = ES_AUTOHSCROLL as u32;
+		AutoVScroll = ES_AUTOVSCROLL as u32;
+		Lowercase = ES_LOWERCASE as u32;
+		Multiline = ES_MULTILINE as u32;
+		NoHideSel = ES_NOHIDESEL as u32;
+		Number = ES_NUMBER as u32;
+		OemConvert = ES_OEMCONVERT as u32;
+		Password = ES_PASSWORD as u32;
+		Readonly = ES_READONLY as u32;
+		Uppercase = ES_UPPERCASE as u32;
+		WantReturn = ES_WANTRETURN as u32;
+	}
+
+	impl Type {
+		pub fn align(self) -> TextAlign {
+			match (self.0 & ALIGN_MASK) as i32 {
+				ES_CENTER => TextAlign::Center,
+				ES_RIGHT => TextAlign::Right,
+				// ES_CENTER | ES_RIGHT is drawn left aligned
+				_ => TextAlign::Left,
+			}
+		}
+
+		/// Same flags, with the alignment replaced by `align`.
+		pub fn with_align(self, align: TextAlign) -> Self {
+			let bits = match align {
+				TextAlign::Left => ES_LEFT,
+				TextAlign::Center => ES_CENTER,
+				TextAlign::Right => ES_RIGHT,
+			};
+			Self(self.0 & !ALIGN_MASK | bits as u32)
+		}
+	}
+}
+
+impl From<style::Type> for window::style::Type {
+	fn from(style: style::Type) -> Self {
+		Self(style.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn align() {
+		let flags = style::Multiline | style::Readonly;
+		for align in [TextAlign::Left, TextAlign::Center, TextAlign::Right] {
+			let style = flags.with_align(TextAlign::Right).with_align(align);
+			assert_eq!(style.align(), align);
+			// the flags are kept, the previous alignment is gone
+			assert_eq!(style.with_align(TextAlign::Left), flags);
+		}
+		assert_eq!(
+			format!("{:?}", flags.with_align(TextAlign::Left)),
+			"Multiline | Readonly"
+		);
+		assert!("Left".parse::<style::Type>().is_err());
+	}
+
+	#[test]
+	fn password_char_outside_bmp() {
+		// rejected before the control is created
+		let e = EditBuilder::new(0)
+			.password_char('💖')
+			.create()
+			.unwrap_err();
+		assert_eq!(
+			e.to_string(),
+			"password char '💖' is not a single UTF-16 unit"
+		);
+	}
 }
@@ -0,0 +1,446 @@
+// Handle of an EDIT control with typed access to its text, selection, options and lines, and the builder creating it.
+//
+// Positions and lengths are in UTF-16 code units, as counted by the control.
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+	Graphics::Gdi::InvalidateRect,
+	UI::WindowsAndMessaging::{DestroyWindow, SendMessageW, GWL_STYLE},
+};
+
+use super::{message, option, style};
+use crate::{
+	assert::{assert_eq, assert_ne, Result, WithLastWin32Error},
+	hiword, loword,
+	wide_string::ToWide,
+	window::{self, get_window_text, set_window_text, WindowBuilder},
+	window_long::get_window_long_ptr,
+};
+
+const EDIT_CLASS: &str = "EDIT";
+
+// 0 for no password char
+fn password_char_code(c: Option<char>) -> Result<u16> {
+	match c {
+		Some(c) => u16::try_from(u32::from(c))
+			.map_err(|_| format!("password char {:?} is not a single UTF-16 unit", c).into()),
+		None => Ok(0),
+	}
+}
+
+/// Alignment of the text, stored in the `ES_LEFT`, `ES_CENTER` and `ES_RIGHT` bits of the style. Unlike the flags of
+/// `style::Type`, alignments are values of these bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+	Left,
+	Center,
+	Right,
+}
+
+/// Conversion of the characters typed in the control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextCase {
+	Unchanged,
+	Lowercase,
+	Uppercase,
+}
+
+/// Handle of an edit control.
+#[derive(Debug)]
+pub struct Edit {
+	h_window: HWND,
+}
+
+impl Edit {
+	/// Wrap an existing edit control, e.g. the one of a combo box.
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+
+	pub fn h_window(&self) -> HWND {
+		self.h_window
+	}
+
+	fn send(&self, message: u32, wparam: WPARAM, lparam: LPARAM) -> isize {
+		unsafe { SendMessageW(self.h_window, message, wparam, lparam) }
+	}
+
+	pub fn style(&self) -> Result<style::Type> {
+		let bits = get_window_long_ptr(self.h_window, GWL_STYLE)?;
+		// control styles are the low word of the window style
+		Ok(style::Type(loword(bits as usize) as u32))
+	}
+
+	pub fn text(&self) -> Result<String> {
+		get_window_text(self.h_window)
+	}
+
+	/// Replace the whole text, which also clears the undo buffer.
+	pub fn set_text(&self, text: &str) -> Result<()> {
+		set_window_text(self.h_window, text)
+	}
+
+	/// Start and end of the selection, equal when nothing is selected, the caret being at the end.
+	pub fn selection(&self) -> (usize, usize) {
+		let mut start = 0u32;
+		let mut end = 0u32;
+		self.send(
+			message::GetSel,
+			&mut start as *mut _ as _,
+			&mut end as *mut _ as _,
+		);
+		(start as usize, end as usize)
+	}
+
+	/// Select from `start` to `end`, `end` being where the caret goes. `set_selection(n, n)` moves the caret.
+	pub fn set_selection(&self, start: usize, end: usize) {
+		self.send(message::SetSel, start, end as _);
+		self.send(message::ScrollCaret, 0, 0);
+	}
+
+	pub fn select_all(&self) {
+		self.send(message::SetSel, 0, -1);
+	}
+
+	/// Replace the selection with `text`, or insert it at the caret. It can be undone with `undo`.
+	pub fn replace_selection(&self, text: &str) {
+		let text = text.to_wide();
+		self.send(message::ReplaceSel, 1, text.as_pwstr().0 as _);
+	}
+
+	/// Largest number of characters the user can type, 0 for the system maximum.
+	pub fn set_limit(&self, limit: usize) {
+		self.send(message::SetLimitText, limit, 0);
+	}
+
+	pub fn limit(&self) -> usize {
+		self.send(message::GetLimitText, 0, 0) as usize
+	}
+
+	pub fn set_readonly(&self, readonly: bool) -> Result<()> {
+		assert_ne(
+			self.send(message::SetReadonly, readonly as _, 0),
+			0,
+			"failed to set edit control readonly",
+		)
+		.with_last_win32_err()
+	}
+
+	pub fn is_readonly(&self) -> Result<bool> {
+		Ok(self.style()?.contains(style::Readonly))
+	}
+
+	/// Character displayed in place of the typed ones, `None` to show the text. Only for single-line controls.
+	pub fn set_password_char(&self, c: Option<char>) -> Result<()> {
+		let code = password_char_code(c)?;
+		self.send(message::SetPasswordChar, code.into(), 0);
+		// the control isn't redrawn by the message
+		unsafe { InvalidateRect(self.h_window, std::ptr::null(), true) };
+		Ok(())
+	}
+
+	pub fn password_char(&self) -> Option<char> {
+		let code = self.send(message::GetPasswordChar, 0, 0) as u32;
+		match code {
+			0 => None,
+			code => char::from_u32(code),
+		}
+	}
+
+	/// Hint shown while the control is empty, also while focused with `show_when_focused`. Only for single-line
+	/// controls.
+	pub fn set_cue_banner(&self, text: &str, show_when_focused: bool) -> Result<()> {
+		let text = text.to_wide();
+		assert_ne(
+			self.send(
+				message::SetCueBanner,
+				show_when_focused as _,
+				text.as_pwstr().0 as _,
+			),
+			0,
+			"failed to set cue banner",
+		)
+		.with_last_win32_err()
+	}
+
+	/// Space between the border and the text, in pixels. `None` keeps the current margin.
+	pub fn set_margins(&self, left: Option<u16>, right: Option<u16>) {
+		let mut which = 0;
+		if left.is_some() {
+			which |= option::LeftMargin;
+		}
+		if right.is_some() {
+			which |= option::RightMargin;
+		}
+		let lparam = usize::from(left.unwrap_or(0)) | usize::from(right.unwrap_or(0)) << 16;
+		self.send(message::SetMargins, which as _, lparam as _);
+	}
+
+	/// Left and right margins, in pixels.
+	pub fn margins(&self) -> (u16, u16) {
+		let margins = self.send(message::GetMargins, 0, 0) as usize;
+		(loword(margins) as u16, hiword(margins) as u16)
+	}
+
+	pub fn can_undo(&self) -> bool {
+		self.send(message::CanUndo, 0, 0) != 0
+	}
+
+	/// Undo the last edit, an undo after an undo redoes it. Returns false if there was nothing to undo.
+	pub fn undo(&self) -> bool {
+		self.send(message::Undo, 0, 0) != 0
+	}
+
+	pub fn clear_undo(&self) {
+		self.send(message::EmptyUndoBuffer, 0, 0);
+	}
+
+	/// Number of lines, 1 for an empty or single-line control.
+	pub fn line_count(&self) -> usize {
+		self.send(message::GetLineCount, 0, 0) as usize
+	}
+
+	/// Line containing the character at `position`.
+	pub fn line_from_position(&self, position: usize) -> usize {
+		self.send(message::LineFromChar, position, 0) as usize
+	}
+
+	/// Position of the first character of `line`, `None` if there is no such line.
+	pub fn line_position(&self, line: usize) -> Option<usize> {
+		match self.send(message::LineIndex, line, 0) {
+			-1 => None,
+			position => Some(position as usize),
+		}
+	}
+
+	/// Text of `line`, without its line break.
+	pub fn line(&self, line: usize) -> Result<String> {
+		let position = self
+			.line_position(line)
+			.ok_or_else(|| format!("no line {} in edit control", line))?;
+		let len = self.send(message::LineLength, position, 0) as usize;
+		if len == 0 {
+			return Ok(String::new());
+		}
+		// the first unit of the buffer is its size, the line is not null terminated
+		let mut buf = vec![0u16; len];
+		buf[0] = u16::try_from(len).unwrap_or(u16::MAX);
+		let copied = self.send(message::GetLine, line, buf.as_mut_ptr() as _) as usize;
+		assert_eq(copied, len, "failed to get edit control line")?;
+		Ok(String::from_utf16_lossy(&buf[..copied]))
+	}
+
+	pub fn lines(&self) -> Result<Vec<String>> {
+		(0..self.line_count()).map(|line| self.line(line)).collect()
+	}
+}
+
+/// Options of an edit control, created as a child of `parent`.
+///
+/// ```ignore
+/// let edit = EditBuilder::new(h_window)
+///     .at(10, 10)
+///     .size(200, 24)
+///     .cue_banner("Search")
+///     .create()?;
+/// ```
+pub struct EditBuilder {
+	parent: HWND,
+	h_instance: Option<HINSTANCE>,
+	text: String,
+	x: i32,
+	y: i32,
+	width: i32,
+	height: i32,
+	id: Option<u16>,
+	style: style::Type,
+	limit: Option<usize>,
+	cue_banner: Option<String>,
+	password_char: Option<char>,
+}
+
+impl EditBuilder {
+	/// Single-line control, scrolling horizontally as the user types.
+	pub fn new(parent: HWND) -> Self {
+		Self {
+			parent,
+			h_instance: None,
+			text: String::new(),
+			x: 0,
+			y: 0,
+			width: 100,
+			height: 20,
+			id: None,
+			style: style::AutoHScroll.with_align(TextAlign::Left),
+			limit: None,
+			cue_banner: None,
+			password_char: None,
+		}
+	}
+
+	pub fn h_instance(mut self, h_instance: HINSTANCE) -> Self {
+		self.h_instance = Some(h_instance);
+		self
+	}
+
+	pub fn text(mut self, text: &str) -> Self {
+		self.text = text.to_owned();
+		self
+	}
+
+	pub fn at(mut self, x: i32, y: i32) -> Self {
+		self.x = x;
+		self.y = y;
+		self
+	}
+
+	pub fn size(mut self, width: i32, height: i32) -> Self {
+		self.width = width;
+		self.height = height;
+		self
+	}
+
+	/// Control id, sent with its notifications in `WM_COMMAND`.
+	pub fn id(mut self, id: u16) -> Self {
+		self.id = Some(id);
+		self
+	}
+
+	/// Replace all the control styles, the options below set them one by one.
+	pub fn style(mut self, style: style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	pub fn align(mut self, align: TextAlign) -> Self {
+		self.style = self.style.with_align(align);
+		self
+	}
+
+	pub fn case(mut self, case: TextCase) -> Self {
+		self.style.remove(style::Lowercase | style::Uppercase);
+		self.style.insert(match case {
+			TextCase::Unchanged => style::Type::empty(),
+			TextCase::Lowercase => style::Lowercase,
+			TextCase::Uppercase => style::Uppercase,
+		});
+		self
+	}
+
+	/// Several lines, scrolling vertically as the user types. Enter inserts a line break in dialogs with `want_return`.
+	pub fn multiline(mut self, multiline: bool) -> Self {
+		self.style
+			.set(style::Multiline | style::AutoVScroll, multiline);
+		self
+	}
+
+	pub fn want_return(mut self, want_return: bool) -> Self {
+		self.style.set(style::WantReturn, want_return);
+		self
+	}
+
+	pub fn auto_hscroll(mut self, auto_hscroll: bool) -> Self {
+		self.style.set(style::AutoHScroll, auto_hscroll);
+		self
+	}
+
+	pub fn auto_vscroll(mut self, auto_vscroll: bool) -> Self {
+		self.style.set(style::AutoVScroll, auto_vscroll);
+		self
+	}
+
+	/// Only digits can be typed, pasted text isn't checked.
+	pub fn number(mut self, number: bool) -> Self {
+		self.style.set(style::Number, number);
+		self
+	}
+
+	pub fn readonly(mut self, readonly: bool) -> Self {
+		self.style.set(style::Readonly, readonly);
+		self
+	}
+
+	/// Keep the selection visible when the control loses the focus.
+	pub fn no_hide_sel(mut self, no_hide_sel: bool) -> Self {
+		self.style.set(style::NoHideSel, no_hide_sel);
+		self
+	}
+
+	pub fn oem_convert(mut self, oem_convert: bool) -> Self {
+		self.style.set(style::OemConvert, oem_convert);
+		self
+	}
+
+	/// Mask the typed characters with the default password character.
+	pub fn password(mut self, password: bool) -> Self {
+		self.style.set(style::Password, password);
+		self
+	}
+
+	/// Mask the typed characters with `c`.
+	pub fn password_char(mut self, c: char) -> Self {
+		self.style.insert(style::Password);
+		self.password_char = Some(c);
+		self
+	}
+
+	pub fn limit(mut self, limit: usize) -> Self {
+		self.limit = Some(limit);
+		self
+	}
+
+	pub fn cue_banner(mut self, text: &str) -> Self {
+		self.cue_banner = Some(text.to_owned());
+		self
+	}
+
+	pub fn create(self) -> Result<Edit> {
+		// checked before the control exists
+		password_char_code(self.password_char)?;
+		let mut builder = WindowBuilder::child(EDIT_CLASS, self.parent)
+			.text(&self.text)
+			.style(
+				window::style::Type::from(self.style)
+					| window::style::Visible
+					| window::style::Child
+					| window::style::Overlapped
+					| window::style::Tabstop,
+			)
+			.ex_style(
+				window::ex_style::ClientEdge
+					| window::ex_style::Left
+					| window::ex_style::LtrReading
+					| window::ex_style::RightScrollbar,
+			)
+			.at(self.x, self.y)
+			.size(self.width, self.height);
+		if let Some(id) = self.id {
+			builder = builder.id(id);
+		}
+		if let Some(h_instance) = self.h_instance {
+			builder = builder.h_instance(h_instance);
+		}
+		let edit = Edit::from_raw(builder.create()?);
+
+		// not left on the parent if it can't be set up
+		if let Err(e) = self.set_up(&edit) {
+			unsafe { DestroyWindow(edit.h_window) };
+			return Err(e);
+		}
+		Ok(edit)
+	}
+
+	fn set_up(&self, edit: &Edit) -> Result<()> {
+		window::set_default_font(edit.h_window);
+		if let Some(limit) = self.limit {
+			edit.set_limit(limit);
+		}
+		if let Some(text) = &self.cue_banner {
+			edit.set_cue_banner(text, false)?;
+		}
+		if self.password_char.is_some() {
+			edit.set_password_char(self.password_char)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,123 @@
+// Parts shared by the COMBOBOX and LISTBOX controls. Their item messages return the same error values, CB_ERR and
+// LB_ERR being -1, CB_ERRSPACE and LB_ERRSPACE -2, and their builders place them the same way.
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+	UI::WindowsAndMessaging::{SendMessageW, LB_ERR, LB_ERRSPACE},
+};
+
+use crate::{assert::Result, window::WindowBuilder};
+
+// item messages of a COMBOBOX or LISTBOX
+pub(crate) trait ItemList {
+	fn list_window(&self) -> HWND;
+
+	fn send(&self, message: u32, wparam: WPARAM, lparam: LPARAM) -> isize {
+		unsafe { SendMessageW(self.list_window(), message, wparam, lparam) }
+	}
+
+	// index returned by a message, None for CB_ERR and LB_ERR
+	fn index(&self, message: u32, wparam: WPARAM, lparam: LPARAM) -> Option<usize> {
+		match self.send(message, wparam, lparam) {
+			r if r < 0 => None,
+			index => Some(index as usize),
+		}
+	}
+}
+
+pub(crate) fn item_index(result: isize, msg: &str) -> Result<usize> {
+	match result as i32 {
+		LB_ERRSPACE => Err(format!("{}: not enough space", msg).into()),
+		LB_ERR => Err(msg.into()),
+		_ => Ok(result as usize),
+	}
+}
+
+// parent, frame, id and instance of a control being built
+#[derive(Debug)]
+pub(crate) struct ChildFrame {
+	pub parent: HWND,
+	pub h_instance: Option<HINSTANCE>,
+	pub x: i32,
+	pub y: i32,
+	pub width: i32,
+	pub height: i32,
+	pub id: Option<u16>,
+}
+
+impl ChildFrame {
+	pub fn new(parent: HWND, width: i32, height: i32) -> Self {
+		Self {
+			parent,
+			h_instance: None,
+			x: 0,
+			y: 0,
+			width,
+			height,
+			id: None,
+		}
+	}
+
+	pub fn window_builder(&self, class_name: &str) -> WindowBuilder {
+		let mut builder = WindowBuilder::child(class_name, self.parent)
+			.at(self.x, self.y)
+			.size(self.width, self.height);
+		if let Some(id) = self.id {
+			builder = builder.id(id);
+		}
+		if let Some(h_instance) = self.h_instance {
+			builder = builder.h_instance(h_instance);
+		}
+		builder
+	}
+}
+
+// `h_instance`, `at`, `size` and `id` of a builder with a `frame: ChildFrame` field
+macro_rules! frame_setters {
+	() => {
+		pub fn h_instance(mut self, h_instance: HINSTANCE) -> Self {
+			self.frame.h_instance = Some(h_instance);
+			self
+		}
+
+		pub fn at(mut self, x: i32, y: i32) -> Self {
+			self.frame.x = x;
+			self.frame.y = y;
+			self
+		}
+
+		pub fn size(mut self, width: i32, height: i32) -> Self {
+			self.frame.width = width;
+			self.frame.height = height;
+			self
+		}
+
+		/// Control id, sent with its notifications in `WM_COMMAND`.
+		pub fn id(mut self, id: u16) -> Self {
+			self.frame.id = Some(id);
+			self
+		}
+	};
+}
+pub(crate) use frame_setters;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use windows::Win32::UI::WindowsAndMessaging::{CB_ERR, CB_ERRSPACE};
+
+	#[test]
+	fn item_indexes() {
+		// combo boxes go through the LB_ values
+		assert_eq!((CB_ERR, CB_ERRSPACE), (LB_ERR, LB_ERRSPACE));
+		assert_eq!(item_index(3, "no item").unwrap(), 3);
+		assert_eq!(
+			item_index(-1, "no item").unwrap_err().to_string(),
+			"no item"
+		);
+		assert_eq!(
+			item_index(-2, "failed to add").unwrap_err().to_string(),
+			"failed to add: not enough space"
+		);
+	}
+}
@@ -0,0 +1,546 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use super::{Align, Control, GridCell};
+use crate::button::CheckState;
+
+/// Value shared between a layout builder and the callbacks of its controls. Clones point to the same value: a callback
+/// updates it, then the builder reads the new value when the layout is built again, and `diff` tells which controls
+/// changed.
+pub struct Observable<T>(Rc<RefCell<T>>);
+
+impl<T> Observable<T> {
+	pub fn new(v: T) -> Self {
+		Self(Rc::new(RefCell::new(v)))
+	}
+
+	pub fn set(&self, v: T) {
+		*self.0.borrow_mut() = v;
+	}
+
+	pub fn update(&self, f: impl FnOnce(&mut T)) {
+		f(&mut self.0.borrow_mut());
+	}
+
+	pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+		f(&self.0.borrow())
+	}
+}
+
+impl<T: Clone> Observable<T> {
+	pub fn get(&self) -> T {
+		self.0.borrow().clone()
+	}
+}
+
+impl<T> Clone for Observable<T> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<T: Default> Default for Observable<T> {
+	fn default() -> Self {
+		Self::new(Default::default())
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for Observable<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_tuple("Observable").field(&self.0.borrow()).finish()
+	}
+}
+
+/// Change to a control backed by a window. `index` is the position of the control among the leaves of the tree, in the
+/// order `Screen` creates them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Patch {
+	SetText {
+		index: usize,
+		text: String,
+	},
+	SetEnabled {
+		index: usize,
+		enabled: bool,
+	},
+	SetCheck {
+		index: usize,
+		state: CheckState,
+	},
+	/// Selected option of a radio group or a combo box.
+	SetSelected {
+		index: usize,
+		selected: Option<usize>,
+	},
+	/// Selected options of a list box.
+	SetSelection {
+		index: usize,
+		selection: Vec<usize>,
+	},
+	/// Options of a combo box or a list box, which clears their selection.
+	SetOptions {
+		index: usize,
+		options: Vec<String>,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diff {
+	/// The trees have different controls, windows have to be created again.
+	Rebuild,
+	/// Same controls: `patches` to apply to their windows, and `relayout` if sizes or positions may have changed.
+	Update { patches: Vec<Patch>, relayout: bool },
+}
+
+/// Compare the tree a screen was rendered from with a new one built from updated state.
+pub fn diff(old: &Control, new: &Control) -> Diff {
+	let mut diff = Differ::default();
+	if diff.control(old, new) {
+		Diff::Update {
+			patches: diff.patches,
+			relayout: diff.relayout,
+		}
+	} else {
+		Diff::Rebuild
+	}
+}
+
+#[derive(Default)]
+struct Differ {
+	// index of the next leaf
+	index: usize,
+	patches: Vec<Patch>,
+	relayout: bool,
+}
+
+impl Differ {
+	// false if the trees don't have the same structure
+	fn control(&mut self, old: &Control, new: &Control) -> bool {
+		match (old, new) {
+			(Control::None, Control::None) => true,
+			(Control::VStack(a), Control::VStack(b)) => {
+				self.relayout |= (a.padding, a.dimension, a.spacing, a.align, a.justify)
+					!= (b.padding, b.dimension, b.spacing, b.align, b.justify);
+				self.items(&a.items, &b.items)
+			}
+			(Control::HStack(a), Control::HStack(b)) => {
+				self.relayout |= (a.padding, a.dimension, a.spacing, a.align, a.justify)
+					!= (b.padding, b.dimension, b.spacing, b.align, b.justify);
+				self.items(&a.items, &b.items)
+			}
+			(Control::Grid(a), Control::Grid(b)) => {
+				self.relayout |= (a.padding, a.dimension, a.row_gap, a.column_gap)
+					!= (b.padding, b.dimension, b.row_gap, b.column_gap)
+					|| a.rows != b.rows
+					|| a.columns != b.columns;
+				a.cells.len() == b.cells.len()
+					&& a.cells.iter().zip(&b.cells).all(|(a, b)| {
+						self.relayout |= placement(a) != placement(b);
+						self.control(&a.control, &b.control)
+					})
+			}
+			(Control::Button(a), Control::Button(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				// the style of the window changes
+				if a.default_button != b.default_button {
+					return false;
+				}
+				self.leaf((a.id, a.disabled), (b.id, b.disabled))
+					.map(|index| self.text(index, &a.title, &b.title))
+					.is_some()
+			}
+			(Control::InputText(a), Control::InputText(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				self.leaf((a.id, a.disabled), (b.id, b.disabled))
+					.map(|index| self.text(index, &a.text, &b.text))
+					.is_some()
+			}
+			(Control::CheckBox(a), Control::CheckBox(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				// the style of the window changes between two and three states
+				if a.tri_state != b.tri_state {
+					return false;
+				}
+				let index = match self.leaf((a.id, a.disabled), (b.id, b.disabled)) {
+					Some(index) => index,
+					None => return false,
+				};
+				self.text(index, &a.title, &b.title);
+				if a.state != b.state {
+					self.patches.push(Patch::SetCheck {
+						index,
+						state: b.state,
+					});
+				}
+				true
+			}
+			(Control::RadioGroup(a), Control::RadioGroup(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				// a window per option
+				if a.options != b.options {
+					return false;
+				}
+				let index = match self.leaf((a.id, a.disabled), (b.id, b.disabled)) {
+					Some(index) => index,
+					None => return false,
+				};
+				if a.selected != b.selected {
+					self.patches.push(Patch::SetSelected {
+						index,
+						selected: b.selected,
+					});
+				}
+				true
+			}
+			(Control::GroupBox(a), Control::GroupBox(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				self.leaf((a.id, a.disabled), (b.id, b.disabled))
+					.map(|index| self.text(index, &a.title, &b.title))
+					.is_some()
+			}
+			(Control::ComboBox(a), Control::ComboBox(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				// the style of the window changes
+				if a.editable != b.editable {
+					return false;
+				}
+				let index = match self.leaf((a.id, a.disabled), (b.id, b.disabled)) {
+					Some(index) => index,
+					None => return false,
+				};
+				let options = self.options(index, &a.options, &b.options);
+				if options || a.selected != b.selected {
+					self.patches.push(Patch::SetSelected {
+						index,
+						selected: b.selected,
+					});
+				}
+				true
+			}
+			(Control::ListBox(a), Control::ListBox(b)) => {
+				self.relayout |= (a.dimension, a.margin) != (b.dimension, b.margin);
+				// the style of the window changes
+				if a.multi_select != b.multi_select {
+					return false;
+				}
+				let index = match self.leaf((a.id, a.disabled), (b.id, b.disabled)) {
+					Some(index) => index,
+					None => return false,
+				};
+				let options = self.options(index, &a.options, &b.options);
+				if options || a.selection != b.selection {
+					self.patches.push(Patch::SetSelection {
+						index,
+						selection: b.selection.clone(),
+					});
+				}
+				true
+			}
+			_ => false,
+		}
+	}
+
+	fn items(&mut self, old: &[Control], new: &[Control]) -> bool {
+		old.len() == new.len() && old.iter().zip(new).all(|(a, b)| self.control(a, b))
+	}
+
+	// index of the leaf, None if its window has to be created again
+	fn leaf(
+		&mut self,
+		(old_id, old_disabled): (Option<u16>, bool),
+		(new_id, new_disabled): (Option<u16>, bool),
+	) -> Option<usize> {
+		// callbacks are routed by id, changing it means creating the window again
+		if old_id != new_id {
+			return None;
+		}
+		let index = self.index;
+		self.index += 1;
+		if old_disabled != new_disabled {
+			self.patches.push(Patch::SetEnabled {
+				index,
+				enabled: !new_disabled,
+			});
+		}
+		Some(index)
+	}
+
+	fn text(&mut self, index: usize, old: &str, new: &str) {
+		if old != new {
+			self.patches.push(Patch::SetText {
+				index,
+				text: new.to_owned(),
+			});
+		}
+	}
+
+	// true if the options changed, the selection then has to be set again
+	fn options(&mut self, index: usize, old: &[String], new: &[String]) -> bool {
+		if old == new {
+			return false;
+		}
+		self.patches.push(Patch::SetOptions {
+			index,
+			options: new.to_vec(),
+		});
+		true
+	}
+}
+
+fn placement(cell: &GridCell) -> (usize, usize, usize, usize, Align, Align) {
+	(
+		cell.row,
+		cell.column,
+		cell.row_span,
+		cell.column_span,
+		cell.h_align,
+		cell.v_align,
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::layout::{
+		Button, CheckBox, ComboBox, DimensionBuilder, GroupBox, HStack, InputText, ListBox,
+		RadioGroup, VStack,
+	};
+
+	fn update(patches: Vec<Patch>) -> Diff {
+		Diff::Update {
+			patches,
+			relayout: false,
+		}
+	}
+
+	fn stack(items: Vec<Control>) -> Control {
+		VStack::new()
+			.items(vec![Control::None, HStack::new().items(items).done()])
+			.done()
+	}
+
+	fn options(options: &[&str]) -> Vec<String> {
+		options.iter().map(|o| (*o).to_owned()).collect()
+	}
+
+	#[test]
+	fn no_op() {
+		let tree = || {
+			stack(vec![
+				Button::new("Save").on_click(|_: &mut ()| {}).done(),
+				InputText::new("text").done(),
+				CheckBox::new("Check").checked(true).done(),
+				RadioGroup::new(&["a", "b"]).selected(Some(1)).done(),
+				GroupBox::new("Group").done(),
+				ComboBox::new(&["a", "b"]).selected(Some(0)).done(),
+				ListBox::new(&["a", "b"]).selection(vec![1]).done(),
+			])
+		};
+		// callbacks are new closures, they don't count as changes
+		assert_eq!(diff(&tree(), &tree()), update(vec![]));
+		assert_eq!(diff(&Control::None, &Control::None), update(vec![]));
+	}
+
+	#[test]
+	fn text() {
+		let old = stack(vec![
+			Button::new("Save").done(),
+			InputText::new("a").done(),
+			GroupBox::new("Group").done(),
+			CheckBox::new("Check").done(),
+		]);
+		let new = stack(vec![
+			Button::new("Save as").done(),
+			InputText::new("a").done(),
+			GroupBox::new("Other").done(),
+			CheckBox::new("Checked").done(),
+		]);
+		assert_eq!(
+			diff(&old, &new),
+			update(vec![
+				Patch::SetText {
+					index: 0,
+					text: "Save as".to_owned()
+				},
+				Patch::SetText {
+					index: 2,
+					text: "Other".to_owned()
+				},
+				Patch::SetText {
+					index: 3,
+					text: "Checked".to_owned()
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn checked() {
+		let old = stack(vec![
+			CheckBox::new("a").done(),
+			CheckBox::new("b").checked(true).done(),
+			CheckBox::new("c").tri_state(true).done(),
+		]);
+		let new = stack(vec![
+			CheckBox::new("a").checked(true).done(),
+			CheckBox::new("b").checked(true).done(),
+			CheckBox::new("c")
+				.tri_state(true)
+				.state(CheckState::Indeterminate)
+				.done(),
+		]);
+		assert_eq!(
+			diff(&old, &new),
+			update(vec![
+				Patch::SetCheck {
+					index: 0,
+					state: CheckState::Checked
+				},
+				Patch::SetCheck {
+					index: 2,
+					state: CheckState::Indeterminate
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn selection() {
+		let old = stack(vec![
+			RadioGroup::new(&["a", "b"]).selected(Some(0)).done(),
+			ComboBox::new(&["a", "b"]).done(),
+			ListBox::new(&["a", "b"]).selection(vec![0]).done(),
+		]);
+		let new = stack(vec![
+			RadioGroup::new(&["a", "b"]).done(),
+			ComboBox::new(&["a", "b"]).selected(Some(1)).done(),
+			ListBox::new(&["a", "b"]).selection(vec![0, 1]).done(),
+		]);
+		assert_eq!(
+			diff(&old, &new),
+			update(vec![
+				Patch::SetSelected {
+					index: 0,
+					selected: None
+				},
+				Patch::SetSelected {
+					index: 1,
+					selected: Some(1)
+				},
+				Patch::SetSelection {
+					index: 2,
+					selection: vec![0, 1]
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn options_reset_selection() {
+		let old = stack(vec![
+			ComboBox::new(&["a", "b"]).selected(Some(1)).done(),
+			ListBox::new(&["a"]).selection(vec![0]).done(),
+		]);
+		// same selection, it still has to be set again once options are replaced
+		let new = stack(vec![
+			ComboBox::new(&["a", "b", "c"]).selected(Some(1)).done(),
+			ListBox::new(&["b"]).selection(vec![0]).done(),
+		]);
+		assert_eq!(
+			diff(&old, &new),
+			update(vec![
+				Patch::SetOptions {
+					index: 0,
+					options: options(&["a", "b", "c"])
+				},
+				Patch::SetSelected {
+					index: 0,
+					selected: Some(1)
+				},
+				Patch::SetOptions {
+					index: 1,
+					options: options(&["b"])
+				},
+				Patch::SetSelection {
+					index: 1,
+					selection: vec![0]
+				},
+			])
+		);
+
+		// a radio group has a window per option
+		let old = stack(vec![RadioGroup::new(&["a"]).done()]);
+		let new = stack(vec![RadioGroup::new(&["a", "b"]).done()]);
+		assert_eq!(diff(&old, &new), Diff::Rebuild);
+	}
+
+	#[test]
+	fn enabled() {
+		let old = stack(vec![
+			Button::new("a").disabled(true).done(),
+			InputText::new("b").done(),
+		]);
+		let new = stack(vec![
+			Button::new("a").done(),
+			InputText::new("b").disabled(true).done(),
+		]);
+		assert_eq!(
+			diff(&old, &new),
+			update(vec![
+				Patch::SetEnabled {
+					index: 0,
+					enabled: true
+				},
+				Patch::SetEnabled {
+					index: 1,
+					enabled: false
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn relayout() {
+		let old = stack(vec![Button::new("a").width(10).done()]);
+		let new = stack(vec![Button::new("b").width(20).done()]);
+		assert_eq!(
+			diff(&old, &new),
+			Diff::Update {
+				patches: vec![Patch::SetText {
+					index: 0,
+					text: "b".to_owned()
+				}],
+				relayout: true
+			}
+		);
+	}
+
+	#[test]
+	fn rebuild() {
+		let cases = [
+			(Button::new("a").done(), Button::new("a").id(3).done()),
+			(
+				Button::new("a").done(),
+				Button::new("a").default_button(true).done(),
+			),
+			(
+				CheckBox::new("a").done(),
+				CheckBox::new("a").tri_state(true).done(),
+			),
+			(
+				ComboBox::new(&[]).done(),
+				ComboBox::new(&[]).editable(true).done(),
+			),
+			(
+				ListBox::new(&[]).done(),
+				ListBox::new(&[]).multi_select(true).done(),
+			),
+			(Button::new("a").done(), GroupBox::new("a").done()),
+		];
+		for (old, new) in cases {
+			assert_eq!(diff(&stack(vec![old]), &stack(vec![new])), Diff::Rebuild);
+		}
+		let old = stack(vec![Button::new("a").done()]);
+		let new = stack(vec![Button::new("a").done(), Button::new("b").done()]);
+		assert_eq!(diff(&old, &new), Diff::Rebuild);
+	}
+}
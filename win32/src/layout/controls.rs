@@ -1,20 +1,57 @@
+use std::{any::Any, rc::Rc};
+
 use derive::{DimensionBuilder, MarginBuilder, PaddingBuilder};
 
 use super::margins::{Dimension, DimensionBuilder, Margin, MarginBuilder, Padding, PaddingBuilder};
+use crate::{assert::Result, button::CheckState};
 
 pub enum Control {
 	None,
 	VStack(VStack),
 	HStack(HStack),
+	Grid(Grid),
 	InputText(InputText),
 	Button(Button),
+	CheckBox(CheckBox),
+	RadioGroup(RadioGroup),
+	GroupBox(GroupBox),
+	ComboBox(ComboBox),
+	ListBox(ListBox),
+}
+
+/// Placement of stack items on the cross axis: horizontally for a `VStack`, vertically for an `HStack`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+	#[default]
+	Start,
+	Center,
+	End,
+	Stretch,
+	/// Line up the text of the items. Without font metrics the baseline of a control is approximated as its vertical
+	/// center, and the one of a stack as the baseline of its first item. Same as `Start` in a `VStack`.
+	Baseline,
+}
+
+/// Distribution of the free space left on the main axis of a stack, once items have been sized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Justify {
+	#[default]
+	Start,
+	Center,
+	End,
+	SpaceBetween,
+	SpaceAround,
+	SpaceEvenly,
 }
 
-#[derive(Default, PaddingBuilder)]
+#[derive(Default, PaddingBuilder, DimensionBuilder)]
 pub struct VStack {
 	pub padding: Padding,
+	pub dimension: Dimension,
 	pub items: Vec<Control>,
 	pub spacing: i32,
+	pub align: Align,
+	pub justify: Justify,
 }
 
 impl VStack {
@@ -32,16 +69,29 @@ impl VStack {
 		self
 	}
 
+	pub fn align(mut self, v: Align) -> Self {
+		self.align = v;
+		self
+	}
+
+	pub fn justify(mut self, v: Justify) -> Self {
+		self.justify = v;
+		self
+	}
+
 	pub fn done(self) -> Control {
 		Control::VStack(self)
 	}
 }
 
-#[derive(Default, PaddingBuilder)]
+#[derive(Default, PaddingBuilder, DimensionBuilder)]
 pub struct HStack {
 	pub padding: Padding,
+	pub dimension: Dimension,
 	pub items: Vec<Control>,
 	pub spacing: i32,
+	pub align: Align,
+	pub justify: Justify,
 }
 
 impl HStack {
@@ -59,16 +109,168 @@ impl HStack {
 		self
 	}
 
+	pub fn align(mut self, v: Align) -> Self {
+		self.align = v;
+		self
+	}
+
+	pub fn justify(mut self, v: Justify) -> Self {
+		self.justify = v;
+		self
+	}
+
 	pub fn done(self) -> Control {
 		Control::HStack(self)
 	}
 }
 
+/// Size of a grid row or column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+	Fixed(i32),
+	/// Fits the largest control placed in the track. Controls spanning several tracks are not taken into account.
+	Auto,
+	/// Share of the space left once fixed and auto tracks are sized, e.g. `Fraction(1.0)` and `Fraction(2.0)` split it
+	/// in thirds.
+	Fraction(f32),
+}
+
+#[derive(Default, PaddingBuilder, DimensionBuilder)]
+pub struct Grid {
+	pub padding: Padding,
+	pub dimension: Dimension,
+	// cells outside of the declared tracks get Auto tracks
+	pub rows: Vec<Track>,
+	pub columns: Vec<Track>,
+	pub row_gap: i32,
+	pub column_gap: i32,
+	pub cells: Vec<GridCell>,
+}
+
+impl Grid {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	pub fn rows(mut self, v: Vec<Track>) -> Self {
+		self.rows = v;
+		self
+	}
+
+	pub fn columns(mut self, v: Vec<Track>) -> Self {
+		self.columns = v;
+		self
+	}
+
+	pub fn row_gap(mut self, v: i32) -> Self {
+		self.row_gap = v;
+		self
+	}
+
+	pub fn column_gap(mut self, v: i32) -> Self {
+		self.column_gap = v;
+		self
+	}
+
+	pub fn cells(mut self, v: Vec<GridCell>) -> Self {
+		self.cells = v;
+		self
+	}
+
+	pub fn cell(mut self, row: usize, column: usize, control: Control) -> Self {
+		self.cells.push(GridCell::new(row, column, control));
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::Grid(self)
+	}
+}
+
+pub struct GridCell {
+	pub row: usize,
+	pub column: usize,
+	pub row_span: usize,
+	pub column_span: usize,
+	// alignment of the control in the cell, Baseline behaves like Start
+	pub h_align: Align,
+	pub v_align: Align,
+	pub control: Control,
+}
+
+impl GridCell {
+	pub fn new(row: usize, column: usize, control: Control) -> Self {
+		Self {
+			row,
+			column,
+			row_span: 1,
+			column_span: 1,
+			h_align: Default::default(),
+			v_align: Default::default(),
+			control,
+		}
+	}
+
+	pub fn row_span(mut self, v: usize) -> Self {
+		self.row_span = v;
+		self
+	}
+
+	pub fn column_span(mut self, v: usize) -> Self {
+		self.column_span = v;
+		self
+	}
+
+	pub fn h_align(mut self, v: Align) -> Self {
+		self.h_align = v;
+		self
+	}
+
+	pub fn v_align(mut self, v: Align) -> Self {
+		self.v_align = v;
+		self
+	}
+}
+
+/// Called with the app state, e.g. the `S` of a `SimpleApp<S>`, when a button is clicked.
+pub type ClickHandler = Rc<dyn Fn(&mut dyn Any) -> Result<()>>;
+
+/// Called with the app state and the new text when the text of an input changes.
+pub type ChangeHandler = Rc<dyn Fn(&mut dyn Any, &str) -> Result<()>>;
+
+/// Called with the app state and the new state of a check box when it is clicked.
+pub type ToggleHandler = Rc<dyn Fn(&mut dyn Any, CheckState) -> Result<()>>;
+
+/// Called with the app state and the index of the option selected in a radio group or a combo box.
+pub type SelectHandler = Rc<dyn Fn(&mut dyn Any, usize) -> Result<()>>;
+
+/// Called with the app state and the indexes of the options selected in a list box.
+pub type SelectionHandler = Rc<dyn Fn(&mut dyn Any, &[usize]) -> Result<()>>;
+
+/// Control ids assigned by `Screen` start here, explicit ids should be below. All control ids are below
+/// `MENU_ID_START`, rendering fails otherwise.
+pub const AUTO_ID_START: u16 = 1000;
+
+// callbacks are stored for any state type and check the one they are called with
+fn downcast_state<A: 'static>(state: &mut dyn Any) -> Result<&mut A> {
+	state.downcast_mut::<A>().ok_or_else(|| {
+		format!(
+			"control callback expects a state of type {}",
+			std::any::type_name::<A>()
+		)
+		.into()
+	})
+}
+
 #[derive(Default, MarginBuilder, DimensionBuilder)]
 pub struct InputText {
 	pub text: String,
 	pub dimension: Dimension,
 	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	pub on_change: Option<ChangeHandler>,
 }
 
 impl InputText {
@@ -79,6 +281,25 @@ impl InputText {
 		}
 	}
 
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	/// Called on `EN_CHANGE`, e.g. `on_change(|state: &mut MyState, text| ...)`.
+	pub fn on_change<A: 'static>(mut self, f: impl Fn(&mut A, &str) + 'static) -> Self {
+		self.on_change = Some(Rc::new(move |state, text| {
+			f(downcast_state(state)?, text);
+			Ok(())
+		}));
+		self
+	}
+
 	pub fn done(self) -> Control {
 		Control::InputText(self)
 	}
@@ -89,6 +310,12 @@ pub struct Button {
 	pub title: String,
 	pub dimension: Dimension,
 	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	// drawn as the button Enter clicks in dialogs
+	pub default_button: bool,
+	pub on_click: Option<ClickHandler>,
 }
 
 impl Button {
@@ -99,7 +326,292 @@ impl Button {
 		}
 	}
 
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn default_button(mut self, v: bool) -> Self {
+		self.default_button = v;
+		self
+	}
+
+	/// Called on `BN_CLICKED`, e.g. `on_click(|state: &mut MyState| ...)`.
+	pub fn on_click<A: 'static>(mut self, f: impl Fn(&mut A) + 'static) -> Self {
+		self.on_click = Some(Rc::new(move |state| {
+			f(downcast_state(state)?);
+			Ok(())
+		}));
+		self
+	}
+
 	pub fn done(self) -> Control {
 		Control::Button(self)
 	}
 }
+
+#[derive(Default, MarginBuilder, DimensionBuilder)]
+pub struct CheckBox {
+	pub title: String,
+	pub dimension: Dimension,
+	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	pub state: CheckState,
+	pub tri_state: bool,
+	pub on_toggle: Option<ToggleHandler>,
+}
+
+impl CheckBox {
+	pub fn new(title: &str) -> Self {
+		Self {
+			title: title.to_owned(),
+			..Default::default()
+		}
+	}
+
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn checked(mut self, v: bool) -> Self {
+		self.state = v.into();
+		self
+	}
+
+	pub fn state(mut self, v: CheckState) -> Self {
+		self.state = v;
+		self
+	}
+
+	/// Clicks cycle through checked, indeterminate and unchecked.
+	pub fn tri_state(mut self, v: bool) -> Self {
+		self.tri_state = v;
+		self
+	}
+
+	/// Called on `BN_CLICKED` with the new state, e.g. `on_toggle(|state: &mut MyState, check| ...)`.
+	pub fn on_toggle<A: 'static>(mut self, f: impl Fn(&mut A, CheckState) + 'static) -> Self {
+		self.on_toggle = Some(Rc::new(move |state, check| {
+			f(downcast_state(state)?, check);
+			Ok(())
+		}));
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::CheckBox(self)
+	}
+}
+
+/// Radio buttons stacked vertically in the frame of the control, one per option, at most one of them selected.
+#[derive(Default, MarginBuilder, DimensionBuilder)]
+pub struct RadioGroup {
+	pub options: Vec<String>,
+	pub dimension: Dimension,
+	pub margin: Margin,
+	// GWL_ID shared by the buttons, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	pub selected: Option<usize>,
+	pub on_select: Option<SelectHandler>,
+}
+
+impl RadioGroup {
+	pub fn new(options: &[&str]) -> Self {
+		Self {
+			options: options.iter().map(|o| (*o).to_owned()).collect(),
+			..Default::default()
+		}
+	}
+
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn selected(mut self, v: Option<usize>) -> Self {
+		self.selected = v;
+		self
+	}
+
+	/// Called on `BN_CLICKED` with the index of the option, e.g. `on_select(|state: &mut MyState, index| ...)`.
+	pub fn on_select<A: 'static>(mut self, f: impl Fn(&mut A, usize) + 'static) -> Self {
+		self.on_select = Some(Rc::new(move |state, index| {
+			f(downcast_state(state)?, index);
+			Ok(())
+		}));
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::RadioGroup(self)
+	}
+}
+
+/// Titled frame. It doesn't contain other controls: place them over it, e.g. in the same grid cell.
+#[derive(Default, MarginBuilder, DimensionBuilder)]
+pub struct GroupBox {
+	pub title: String,
+	pub dimension: Dimension,
+	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+}
+
+impl GroupBox {
+	pub fn new(title: &str) -> Self {
+		Self {
+			title: title.to_owned(),
+			..Default::default()
+		}
+	}
+
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::GroupBox(self)
+	}
+}
+
+/// Drop-down list of options, the frame of the control being the one of the closed field.
+#[derive(Default, MarginBuilder, DimensionBuilder)]
+pub struct ComboBox {
+	pub options: Vec<String>,
+	pub dimension: Dimension,
+	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	pub selected: Option<usize>,
+	// the user can type a text that isn't an option
+	pub editable: bool,
+	pub on_select: Option<SelectHandler>,
+}
+
+impl ComboBox {
+	pub fn new(options: &[&str]) -> Self {
+		Self {
+			options: options.iter().map(|o| (*o).to_owned()).collect(),
+			..Default::default()
+		}
+	}
+
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn selected(mut self, v: Option<usize>) -> Self {
+		self.selected = v;
+		self
+	}
+
+	pub fn editable(mut self, v: bool) -> Self {
+		self.editable = v;
+		self
+	}
+
+	/// Called on `CBN_SELCHANGE` with the index of the option, e.g. `on_select(|state: &mut MyState, index| ...)`.
+	pub fn on_select<A: 'static>(mut self, f: impl Fn(&mut A, usize) + 'static) -> Self {
+		self.on_select = Some(Rc::new(move |state, index| {
+			f(downcast_state(state)?, index);
+			Ok(())
+		}));
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::ComboBox(self)
+	}
+}
+
+/// Scrolling list of options, one or several of them selected.
+#[derive(Default, MarginBuilder, DimensionBuilder)]
+pub struct ListBox {
+	pub options: Vec<String>,
+	pub dimension: Dimension,
+	pub margin: Margin,
+	// GWL_ID of the control, assigned when rendered if not set
+	pub id: Option<u16>,
+	pub disabled: bool,
+	// indexes of the selected options, at most one without multi_select
+	pub selection: Vec<usize>,
+	// shift and ctrl extend the selection
+	pub multi_select: bool,
+	pub on_select: Option<SelectionHandler>,
+}
+
+impl ListBox {
+	pub fn new(options: &[&str]) -> Self {
+		Self {
+			options: options.iter().map(|o| (*o).to_owned()).collect(),
+			..Default::default()
+		}
+	}
+
+	pub fn id(mut self, v: u16) -> Self {
+		self.id = Some(v);
+		self
+	}
+
+	pub fn disabled(mut self, v: bool) -> Self {
+		self.disabled = v;
+		self
+	}
+
+	pub fn selection(mut self, v: Vec<usize>) -> Self {
+		self.selection = v;
+		self
+	}
+
+	pub fn multi_select(mut self, v: bool) -> Self {
+		self.multi_select = v;
+		self
+	}
+
+	/// Called on `LBN_SELCHANGE` with the indexes of the selected options, e.g.
+	/// `on_select(|state: &mut MyState, selection| ...)`.
+	pub fn on_select<A: 'static>(mut self, f: impl Fn(&mut A, &[usize]) + 'static) -> Self {
+		self.on_select = Some(Rc::new(move |state, selection| {
+			f(downcast_state(state)?, selection);
+			Ok(())
+		}));
+		self
+	}
+
+	pub fn done(self) -> Control {
+		Control::ListBox(self)
+	}
+}
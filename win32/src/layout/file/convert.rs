@@ -0,0 +1,829 @@
+// Mapping between parsed values and `Control` trees. Numbers and booleans are also accepted as strings, since XML
+// attributes have no other type.
+
+use super::{LayoutFileError, Node, ParseResult, Position, Value};
+use crate::{
+	button::CheckState,
+	layout::{
+		Align, Button, CheckBox, ComboBox, Control, Dimension, Grid, GridCell, GroupBox, HStack,
+		InputText, Justify, ListBox, Margin, Padding, RadioGroup, Track, VStack,
+	},
+};
+
+type Table = Vec<(String, Node)>;
+
+pub(super) fn to_control(node: &Node) -> ParseResult<Control> {
+	parse_control(node, None)
+}
+
+pub(super) fn from_control(control: &Control) -> Node {
+	node(Value::Table(write_control(control)))
+}
+
+// placement of a grid item, read from the same table as the control
+struct Placement {
+	row: usize,
+	column: usize,
+	row_span: usize,
+	column_span: usize,
+	h_align: Align,
+	v_align: Align,
+}
+
+impl Default for Placement {
+	fn default() -> Self {
+		Self {
+			row: 0,
+			column: 0,
+			row_span: 1,
+			column_span: 1,
+			h_align: Default::default(),
+			v_align: Default::default(),
+		}
+	}
+}
+
+fn parse_control(node: &Node, mut placement: Option<&mut Placement>) -> ParseResult<Control> {
+	let table = as_table(node)?;
+	let type_node = table
+		.iter()
+		.find(|(k, _)| k == "type")
+		.map(|(_, v)| v)
+		.ok_or_else(|| LayoutFileError::new("missing key \"type\"", node.position))?;
+
+	let mut dimension = Dimension::default();
+	let mut padding = Padding::default();
+	let mut margin = Margin::default();
+	let mut stack = VStack::new();
+	let mut grid = Grid::new();
+	let mut text = String::new();
+	let mut id = None;
+	let mut disabled = false;
+	let mut default_button = false;
+	let mut check = CheckBox::new("");
+	let mut options = vec![];
+	let mut selected = None;
+	let mut combo = ComboBox::new(&[]);
+	let mut list = ListBox::new(&[]);
+
+	let type_name = as_string(type_node)?;
+	let (container, stack_keys, grid_keys, text_key) = match type_name.as_str() {
+		"None" => (false, false, false, None),
+		"VStack" | "HStack" => (true, true, false, None),
+		"Grid" => (true, false, true, None),
+		"Button" | "CheckBox" | "GroupBox" => (false, false, false, Some("title")),
+		"InputText" => (false, false, false, Some("text")),
+		"RadioGroup" | "ComboBox" | "ListBox" => (false, false, false, None),
+		_ => {
+			return Err(LayoutFileError::new(
+				&format!("unknown control type {:?}", type_name),
+				type_node.position,
+			))
+		}
+	};
+	let button_keys = type_name == "Button";
+	let check_keys = type_name == "CheckBox";
+	// options instead of a text
+	let options_keys = matches!(type_name.as_str(), "RadioGroup" | "ComboBox" | "ListBox");
+	// a list box has a selection rather than a selected option
+	let selected_keys = options_keys && type_name != "ListBox";
+	let combo_keys = type_name == "ComboBox";
+	let list_keys = type_name == "ListBox";
+	let leaf = text_key.is_some() || options_keys;
+
+	for (key, value) in table {
+		let key = key.as_str();
+		if key == "type" || (type_name != "None" && set_dimension(&mut dimension, key, value)?) {
+			continue;
+		}
+		if let Some(placement) = placement.as_deref_mut() {
+			if set_placement(placement, key, value)? {
+				continue;
+			}
+		}
+		match key {
+			"padding" if container => {
+				let [top, right, bottom, left] = as_sides(value)?;
+				padding = Padding {
+					top,
+					right,
+					bottom,
+					left,
+				};
+			}
+			"margin" if leaf => {
+				let [top, right, bottom, left] = as_sides(value)?;
+				margin = Margin {
+					top,
+					right,
+					bottom,
+					left,
+				};
+			}
+			"id" if leaf => {
+				let v = as_int(value)?;
+				id = Some(v.try_into().map_err(|_| {
+					LayoutFileError::new("control id out of range", value.position)
+				})?);
+			}
+			"disabled" if leaf => disabled = as_bool(value)?,
+			"default" if button_keys => default_button = as_bool(value)?,
+			"checked" if check_keys => check.state = as_bool(value)?.into(),
+			"indeterminate" if check_keys => {
+				if as_bool(value)? {
+					check.state = CheckState::Indeterminate;
+				}
+			}
+			"tri_state" if check_keys => check.tri_state = as_bool(value)?,
+			"options" if options_keys => options = as_options(value)?,
+			"selected" if selected_keys => selected = Some(as_index(value)?),
+			"editable" if combo_keys => combo.editable = as_bool(value)?,
+			"selection" if list_keys => list.selection = as_indexes(value)?,
+			"multi_select" if list_keys => list.multi_select = as_bool(value)?,
+			"spacing" if stack_keys => stack.spacing = as_int(value)?,
+			"align" if stack_keys => stack.align = as_align(value)?,
+			"justify" if stack_keys => stack.justify = as_justify(value)?,
+			"items" if stack_keys => {
+				stack.items = as_array(value)?
+					.iter()
+					.map(|item| parse_control(item, None))
+					.collect::<ParseResult<_>>()?;
+			}
+			"rows" if grid_keys => grid.rows = as_tracks(value)?,
+			"columns" if grid_keys => grid.columns = as_tracks(value)?,
+			"row_gap" if grid_keys => grid.row_gap = as_int(value)?,
+			"column_gap" if grid_keys => grid.column_gap = as_int(value)?,
+			"items" if grid_keys => {
+				for item in as_array(value)? {
+					let mut cell = Placement::default();
+					let control = parse_control(item, Some(&mut cell))?;
+					grid.cells.push(
+						GridCell::new(cell.row, cell.column, control)
+							.row_span(cell.row_span)
+							.column_span(cell.column_span)
+							.h_align(cell.h_align)
+							.v_align(cell.v_align),
+					);
+				}
+			}
+			_ if Some(key) == text_key => text = as_string(value)?,
+			_ => {
+				return Err(LayoutFileError::new(
+					&format!("unknown key {:?} for {}", key, type_name),
+					value.position,
+				))
+			}
+		}
+	}
+
+	let control = match type_name.as_str() {
+		"VStack" => Control::VStack(VStack {
+			padding,
+			dimension,
+			..stack
+		}),
+		"HStack" => Control::HStack(HStack {
+			padding,
+			dimension,
+			items: stack.items,
+			spacing: stack.spacing,
+			align: stack.align,
+			justify: stack.justify,
+		}),
+		"Grid" => Control::Grid(Grid {
+			padding,
+			dimension,
+			..grid
+		}),
+		"Button" => Control::Button(Button {
+			title: text,
+			dimension,
+			margin,
+			id,
+			disabled,
+			default_button,
+			..Default::default()
+		}),
+		"InputText" => Control::InputText(InputText {
+			text,
+			dimension,
+			margin,
+			id,
+			disabled,
+			..Default::default()
+		}),
+		"CheckBox" => Control::CheckBox(CheckBox {
+			title: text,
+			dimension,
+			margin,
+			id,
+			disabled,
+			..check
+		}),
+		"RadioGroup" => Control::RadioGroup(RadioGroup {
+			options,
+			dimension,
+			margin,
+			id,
+			disabled,
+			selected,
+			..Default::default()
+		}),
+		"ComboBox" => Control::ComboBox(ComboBox {
+			options,
+			dimension,
+			margin,
+			id,
+			disabled,
+			selected,
+			..combo
+		}),
+		"ListBox" => Control::ListBox(ListBox {
+			options,
+			dimension,
+			margin,
+			id,
+			disabled,
+			..list
+		}),
+		"GroupBox" => Control::GroupBox(GroupBox {
+			title: text,
+			dimension,
+			margin,
+			id,
+			disabled,
+		}),
+		_ => Control::None,
+	};
+	Ok(control)
+}
+
+fn set_dimension(d: &mut Dimension, key: &str, value: &Node) -> ParseResult<bool> {
+	match key {
+		"width" => d.width = as_int(value)?,
+		"height" => d.height = as_int(value)?,
+		"grow" => d.grow = as_float(value)?,
+		"shrink" => d.shrink = as_float(value)?,
+		"fill" => d.fill = as_bool(value)?,
+		"min_width" => d.min_width = Some(as_int(value)?),
+		"max_width" => d.max_width = Some(as_int(value)?),
+		"min_height" => d.min_height = Some(as_int(value)?),
+		"max_height" => d.max_height = Some(as_int(value)?),
+		_ => return Ok(false),
+	}
+	Ok(true)
+}
+
+fn set_placement(p: &mut Placement, key: &str, value: &Node) -> ParseResult<bool> {
+	match key {
+		"row" => p.row = as_index(value)?,
+		"column" => p.column = as_index(value)?,
+		"row_span" => p.row_span = as_index(value)?,
+		"column_span" => p.column_span = as_index(value)?,
+		"h_align" => p.h_align = as_align(value)?,
+		"v_align" => p.v_align = as_align(value)?,
+		_ => return Ok(false),
+	}
+	Ok(true)
+}
+
+fn mismatch(expected: &str, node: &Node) -> LayoutFileError {
+	LayoutFileError::new(
+		&format!("expected {}, found {}", expected, node.type_name()),
+		node.position,
+	)
+}
+
+fn invalid(expected: &str, s: &str, position: Position) -> LayoutFileError {
+	LayoutFileError::new(&format!("expected {}, found {:?}", expected, s), position)
+}
+
+fn as_table(node: &Node) -> ParseResult<&Table> {
+	match &node.value {
+		Value::Table(table) => Ok(table),
+		_ => Err(mismatch("a table", node)),
+	}
+}
+
+fn as_array(node: &Node) -> ParseResult<&Vec<Node>> {
+	match &node.value {
+		Value::Array(items) => Ok(items),
+		_ => Err(mismatch("an array", node)),
+	}
+}
+
+fn as_string(node: &Node) -> ParseResult<String> {
+	match &node.value {
+		Value::String(s) => Ok(s.clone()),
+		_ => Err(mismatch("a string", node)),
+	}
+}
+
+fn as_int(node: &Node) -> ParseResult<i32> {
+	match &node.value {
+		Value::Int(i) => (*i)
+			.try_into()
+			.map_err(|_| LayoutFileError::new("integer out of range", node.position)),
+		Value::String(s) => s
+			.trim()
+			.parse()
+			.map_err(|_| invalid("an integer", s, node.position)),
+		_ => Err(mismatch("an integer", node)),
+	}
+}
+
+fn as_index(node: &Node) -> ParseResult<usize> {
+	let i = as_int(node)?;
+	i.try_into()
+		.map_err(|_| LayoutFileError::new("expected a positive integer", node.position))
+}
+
+fn as_float(node: &Node) -> ParseResult<f32> {
+	match &node.value {
+		Value::Int(i) => Ok(*i as f32),
+		Value::Float(f) => Ok(*f as f32),
+		Value::String(s) => s
+			.trim()
+			.parse()
+			.map_err(|_| invalid("a number", s, node.position)),
+		_ => Err(mismatch("a number", node)),
+	}
+}
+
+fn as_bool(node: &Node) -> ParseResult<bool> {
+	match &node.value {
+		Value::Bool(b) => Ok(*b),
+		Value::String(s) => match s.trim() {
+			"true" => Ok(true),
+			"false" => Ok(false),
+			_ => Err(invalid("a boolean", s, node.position)),
+		},
+		_ => Err(mismatch("a boolean", node)),
+	}
+}
+
+// padding and margins: one value for all sides, "vertical horizontal", "top right bottom left" or a table
+fn as_sides(node: &Node) -> ParseResult<[i32; 4]> {
+	match &node.value {
+		Value::Int(_) => {
+			let v = as_int(node)?;
+			Ok([v; 4])
+		}
+		Value::String(s) => {
+			let values = s
+				.split_whitespace()
+				.map(|part| part.parse())
+				.collect::<Result<Vec<i32>, _>>()
+				.map_err(|_| invalid("1, 2 or 4 integers", s, node.position))?;
+			match values[..] {
+				[all] => Ok([all; 4]),
+				[vertical, horizontal] => Ok([vertical, horizontal, vertical, horizontal]),
+				[top, right, bottom, left] => Ok([top, right, bottom, left]),
+				_ => Err(invalid("1, 2 or 4 integers", s, node.position)),
+			}
+		}
+		Value::Table(table) => {
+			let mut sides = [0; 4];
+			for (key, value) in table {
+				let index = match key.as_str() {
+					"top" => 0,
+					"right" => 1,
+					"bottom" => 2,
+					"left" => 3,
+					_ => {
+						return Err(LayoutFileError::new(
+							&format!("unknown side {:?}", key),
+							value.position,
+						))
+					}
+				};
+				sides[index] = as_int(value)?;
+			}
+			Ok(sides)
+		}
+		_ => Err(mismatch("an integer, a string or a table", node)),
+	}
+}
+
+// tracks are written like "auto 1fr 50", or as an array of such tokens and integers
+fn as_tracks(node: &Node) -> ParseResult<Vec<Track>> {
+	match &node.value {
+		Value::String(s) => s
+			.split_whitespace()
+			.map(|token| as_track(token, node.position))
+			.collect(),
+		Value::Array(items) => items
+			.iter()
+			.map(|item| match &item.value {
+				Value::Int(_) => Ok(Track::Fixed(as_int(item)?)),
+				Value::String(s) => as_track(s.trim(), item.position),
+				_ => Err(mismatch("a track", item)),
+			})
+			.collect(),
+		_ => Err(mismatch("a string or an array of tracks", node)),
+	}
+}
+
+// options of radio groups, combo boxes and list boxes are an array of strings, kept as they are
+fn as_options(node: &Node) -> ParseResult<Vec<String>> {
+	as_array(node)?.iter().map(as_string).collect()
+}
+
+// selections are written like "0 2", or as an array of integers
+fn as_indexes(node: &Node) -> ParseResult<Vec<usize>> {
+	match &node.value {
+		Value::Int(_) => Ok(vec![as_index(node)?]),
+		Value::String(s) => s
+			.split_whitespace()
+			.map(|token| {
+				token
+					.parse()
+					.map_err(|_| invalid("a positive integer", token, node.position))
+			})
+			.collect(),
+		Value::Array(items) => items.iter().map(as_index).collect(),
+		_ => Err(mismatch(
+			"an integer, a string or an array of integers",
+			node,
+		)),
+	}
+}
+
+fn as_track(token: &str, position: Position) -> ParseResult<Track> {
+	let track = if token == "auto" {
+		Some(Track::Auto)
+	} else if let Some(fraction) = token.strip_suffix("fr") {
+		fraction.parse().ok().map(Track::Fraction)
+	} else {
+		token.parse().ok().map(Track::Fixed)
+	};
+	track.ok_or_else(|| invalid("a track (auto, <n>fr or <pixels>)", token, position))
+}
+
+const ALIGNS: [(Align, &str); 5] = [
+	(Align::Start, "start"),
+	(Align::Center, "center"),
+	(Align::End, "end"),
+	(Align::Stretch, "stretch"),
+	(Align::Baseline, "baseline"),
+];
+
+const JUSTIFIES: [(Justify, &str); 6] = [
+	(Justify::Start, "start"),
+	(Justify::Center, "center"),
+	(Justify::End, "end"),
+	(Justify::SpaceBetween, "space-between"),
+	(Justify::SpaceAround, "space-around"),
+	(Justify::SpaceEvenly, "space-evenly"),
+];
+
+fn as_align(node: &Node) -> ParseResult<Align> {
+	let s = as_string(node)?;
+	ALIGNS
+		.iter()
+		.find(|(_, name)| *name == s)
+		.map(|(align, _)| *align)
+		.ok_or_else(|| invalid("an alignment", &s, node.position))
+}
+
+fn as_justify(node: &Node) -> ParseResult<Justify> {
+	let s = as_string(node)?;
+	JUSTIFIES
+		.iter()
+		.find(|(_, name)| *name == s)
+		.map(|(justify, _)| *justify)
+		.ok_or_else(|| invalid("a justification", &s, node.position))
+}
+
+fn align_name(align: Align) -> &'static str {
+	ALIGNS.iter().find(|(a, _)| *a == align).unwrap().1
+}
+
+fn justify_name(justify: Justify) -> &'static str {
+	JUSTIFIES.iter().find(|(j, _)| *j == justify).unwrap().1
+}
+
+fn node(value: Value) -> Node {
+	Node::new(value, Position::default())
+}
+
+fn push(table: &mut Table, key: &str, value: Value) {
+	table.push((key.to_owned(), node(value)));
+}
+
+fn write_control(control: &Control) -> Table {
+	let mut table = vec![];
+	match control {
+		Control::None => push(&mut table, "type", Value::String("None".to_owned())),
+		Control::VStack(stack) => {
+			push(&mut table, "type", Value::String("VStack".to_owned()));
+			write_stack(
+				&mut table,
+				(stack.padding, stack.dimension),
+				(stack.spacing, stack.align, stack.justify),
+				&stack.items,
+			);
+		}
+		Control::HStack(stack) => {
+			push(&mut table, "type", Value::String("HStack".to_owned()));
+			write_stack(
+				&mut table,
+				(stack.padding, stack.dimension),
+				(stack.spacing, stack.align, stack.justify),
+				&stack.items,
+			);
+		}
+		Control::Grid(grid) => {
+			push(&mut table, "type", Value::String("Grid".to_owned()));
+			write_padding(&mut table, grid.padding);
+			write_dimension(&mut table, &grid.dimension);
+			if !grid.rows.is_empty() {
+				push(&mut table, "rows", Value::String(tracks_text(&grid.rows)));
+			}
+			if !grid.columns.is_empty() {
+				push(
+					&mut table,
+					"columns",
+					Value::String(tracks_text(&grid.columns)),
+				);
+			}
+			if grid.row_gap != 0 {
+				push(&mut table, "row_gap", Value::Int(grid.row_gap.into()));
+			}
+			if grid.column_gap != 0 {
+				push(&mut table, "column_gap", Value::Int(grid.column_gap.into()));
+			}
+			if !grid.cells.is_empty() {
+				let items = grid
+					.cells
+					.iter()
+					.map(|cell| node(Value::Table(write_cell(cell))));
+				push(&mut table, "items", Value::Array(items.collect()));
+			}
+		}
+		Control::Button(button) => {
+			push(&mut table, "type", Value::String("Button".to_owned()));
+			push(&mut table, "title", Value::String(button.title.clone()));
+			if button.default_button {
+				push(&mut table, "default", Value::Bool(true));
+			}
+			write_id(&mut table, button.id, button.disabled);
+			write_margin(&mut table, button.margin);
+			write_dimension(&mut table, &button.dimension);
+		}
+		Control::InputText(input) => {
+			push(&mut table, "type", Value::String("InputText".to_owned()));
+			push(&mut table, "text", Value::String(input.text.clone()));
+			write_id(&mut table, input.id, input.disabled);
+			write_margin(&mut table, input.margin);
+			write_dimension(&mut table, &input.dimension);
+		}
+		Control::CheckBox(check) => {
+			push(&mut table, "type", Value::String("CheckBox".to_owned()));
+			push(&mut table, "title", Value::String(check.title.clone()));
+			match check.state {
+				CheckState::Unchecked => {}
+				CheckState::Checked => push(&mut table, "checked", Value::Bool(true)),
+				CheckState::Indeterminate => push(&mut table, "indeterminate", Value::Bool(true)),
+			}
+			if check.tri_state {
+				push(&mut table, "tri_state", Value::Bool(true));
+			}
+			write_id(&mut table, check.id, check.disabled);
+			write_margin(&mut table, check.margin);
+			write_dimension(&mut table, &check.dimension);
+		}
+		Control::RadioGroup(radio) => {
+			push(&mut table, "type", Value::String("RadioGroup".to_owned()));
+			push(&mut table, "options", options_value(&radio.options));
+			if let Some(selected) = radio.selected {
+				push(&mut table, "selected", Value::Int(selected as i64));
+			}
+			write_id(&mut table, radio.id, radio.disabled);
+			write_margin(&mut table, radio.margin);
+			write_dimension(&mut table, &radio.dimension);
+		}
+		Control::GroupBox(group) => {
+			push(&mut table, "type", Value::String("GroupBox".to_owned()));
+			push(&mut table, "title", Value::String(group.title.clone()));
+			write_id(&mut table, group.id, group.disabled);
+			write_margin(&mut table, group.margin);
+			write_dimension(&mut table, &group.dimension);
+		}
+		Control::ComboBox(combo) => {
+			push(&mut table, "type", Value::String("ComboBox".to_owned()));
+			push(&mut table, "options", options_value(&combo.options));
+			if let Some(selected) = combo.selected {
+				push(&mut table, "selected", Value::Int(selected as i64));
+			}
+			if combo.editable {
+				push(&mut table, "editable", Value::Bool(true));
+			}
+			write_id(&mut table, combo.id, combo.disabled);
+			write_margin(&mut table, combo.margin);
+			write_dimension(&mut table, &combo.dimension);
+		}
+		Control::ListBox(list) => {
+			push(&mut table, "type", Value::String("ListBox".to_owned()));
+			push(&mut table, "options", options_value(&list.options));
+			if !list.selection.is_empty() {
+				let selection: Vec<String> = list.selection.iter().map(|i| i.to_string()).collect();
+				push(&mut table, "selection", Value::String(selection.join(" ")));
+			}
+			if list.multi_select {
+				push(&mut table, "multi_select", Value::Bool(true));
+			}
+			write_id(&mut table, list.id, list.disabled);
+			write_margin(&mut table, list.margin);
+			write_dimension(&mut table, &list.dimension);
+		}
+	}
+	table
+}
+
+fn write_stack(
+	table: &mut Table,
+	(padding, dimension): (Padding, Dimension),
+	(spacing, align, justify): (i32, Align, Justify),
+	items: &[Control],
+) {
+	write_padding(table, padding);
+	write_dimension(table, &dimension);
+	if spacing != 0 {
+		push(table, "spacing", Value::Int(spacing.into()));
+	}
+	if align != Align::Start {
+		push(table, "align", Value::String(align_name(align).to_owned()));
+	}
+	if justify != Justify::Start {
+		push(
+			table,
+			"justify",
+			Value::String(justify_name(justify).to_owned()),
+		);
+	}
+	if !items.is_empty() {
+		let items = items
+			.iter()
+			.map(|item| node(Value::Table(write_control(item))));
+		push(table, "items", Value::Array(items.collect()));
+	}
+}
+
+fn write_cell(cell: &GridCell) -> Table {
+	let mut table = write_control(&cell.control);
+	push(&mut table, "row", Value::Int(cell.row as i64));
+	push(&mut table, "column", Value::Int(cell.column as i64));
+	if cell.row_span != 1 {
+		push(&mut table, "row_span", Value::Int(cell.row_span as i64));
+	}
+	if cell.column_span != 1 {
+		push(
+			&mut table,
+			"column_span",
+			Value::Int(cell.column_span as i64),
+		);
+	}
+	if cell.h_align != Align::Start {
+		push(
+			&mut table,
+			"h_align",
+			Value::String(align_name(cell.h_align).to_owned()),
+		);
+	}
+	if cell.v_align != Align::Start {
+		push(
+			&mut table,
+			"v_align",
+			Value::String(align_name(cell.v_align).to_owned()),
+		);
+	}
+	table
+}
+
+fn options_value(options: &[String]) -> Value {
+	let options = options.iter().map(|o| node(Value::String(o.clone())));
+	Value::Array(options.collect())
+}
+
+fn write_id(table: &mut Table, id: Option<u16>, disabled: bool) {
+	if let Some(id) = id {
+		push(table, "id", Value::Int(id.into()));
+	}
+	if disabled {
+		push(table, "disabled", Value::Bool(true));
+	}
+}
+
+fn write_padding(table: &mut Table, p: Padding) {
+	write_sides(table, "padding", [p.top, p.right, p.bottom, p.left]);
+}
+
+fn write_margin(table: &mut Table, m: Margin) {
+	write_sides(table, "margin", [m.top, m.right, m.bottom, m.left]);
+}
+
+fn write_sides(table: &mut Table, key: &str, sides: [i32; 4]) {
+	let [top, right, bottom, left] = sides;
+	if sides == [0; 4] {
+		return;
+	}
+	if sides == [top; 4] {
+		push(table, key, Value::Int(top.into()));
+	} else {
+		let text = format!("{} {} {} {}", top, right, bottom, left);
+		push(table, key, Value::String(text));
+	}
+}
+
+fn write_dimension(table: &mut Table, d: &Dimension) {
+	if d.width != 0 {
+		push(table, "width", Value::Int(d.width.into()));
+	}
+	if d.height != 0 {
+		push(table, "height", Value::Int(d.height.into()));
+	}
+	if d.grow != 0.0 {
+		push(table, "grow", Value::Float(d.grow.into()));
+	}
+	if d.shrink != 0.0 {
+		push(table, "shrink", Value::Float(d.shrink.into()));
+	}
+	if d.fill {
+		push(table, "fill", Value::Bool(true));
+	}
+	let bounds = [
+		("min_width", d.min_width),
+		("max_width", d.max_width),
+		("min_height", d.min_height),
+		("max_height", d.max_height),
+	];
+	for (key, v) in bounds {
+		if let Some(v) = v {
+			push(table, key, Value::Int(v.into()));
+		}
+	}
+}
+
+fn tracks_text(tracks: &[Track]) -> String {
+	let tokens: Vec<String> = tracks
+		.iter()
+		.map(|track| match track {
+			Track::Fixed(v) => v.to_string(),
+			Track::Auto => "auto".to_owned(),
+			Track::Fraction(f) => format!("{}fr", f),
+		})
+		.collect();
+	tokens.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{load_from_str, save_to_string, Format};
+	use crate::layout::{ComboBox, Control, ListBox, RadioGroup};
+
+	fn options(control: &Control) -> &[String] {
+		match control {
+			Control::RadioGroup(radio) => &radio.options,
+			Control::ComboBox(combo) => &combo.options,
+			Control::ListBox(list) => &list.options,
+			_ => panic!("not a control with options"),
+		}
+	}
+
+	#[test]
+	fn options_round_trip() {
+		let cases: [&[&str]; 4] = [&["a|b", " c "], &[""], &[], &["<\"&'>", "\t💖\n"]];
+		for format in [Format::Json, Format::Toml, Format::Xml] {
+			for case in cases {
+				for control in [
+					RadioGroup::new(case).done(),
+					ComboBox::new(case).done(),
+					ListBox::new(case).done(),
+				] {
+					let saved = save_to_string(format, &control);
+					let loaded = load_from_str(format, &saved).unwrap();
+					assert_eq!(
+						options(&loaded),
+						options(&control),
+						"{:?}: {}",
+						format,
+						saved
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn options_are_not_split() {
+		let loaded = load_from_str(Format::Json, r#"{"type": "ComboBox", "options": ["a|b"]}"#);
+		assert_eq!(options(&loaded.unwrap()), ["a|b"]);
+		let loaded = load_from_str(
+			Format::Xml,
+			"<ListBox>\n\t<option value=\" x \" />\n\t<option value=\"\" />\n</ListBox>",
+		);
+		assert_eq!(options(&loaded.unwrap()), [" x ", ""]);
+		assert!(load_from_str(Format::Toml, "type = \"RadioGroup\"\noptions = \"a|b\"\n").is_err());
+	}
+}
@@ -0,0 +1,266 @@
+use super::{
+	escape_string, parse_number, read_hex, simple_escape, write_float, Cursor, LayoutFileError,
+	Node, ParseResult, Position, Value,
+};
+
+pub(super) fn parse(s: &str) -> ParseResult<Node> {
+	let mut cursor = Cursor::new(s);
+	skip_whitespace(&mut cursor);
+	let node = parse_value(&mut cursor)?;
+	skip_whitespace(&mut cursor);
+	if cursor.peek().is_some() {
+		return Err(cursor.error("unexpected content after the root value"));
+	}
+	Ok(node)
+}
+
+fn skip_whitespace(cursor: &mut Cursor) {
+	cursor.take_while(|c| c.is_whitespace());
+}
+
+fn parse_value(cursor: &mut Cursor) -> ParseResult<Node> {
+	let position = cursor.position;
+	let value = match cursor.peek() {
+		Some('{') => parse_object(cursor)?,
+		Some('[') => parse_array(cursor)?,
+		Some('"') => Value::String(parse_string(cursor)?),
+		Some('t') => {
+			cursor.eat_str("true")?;
+			Value::Bool(true)
+		}
+		Some('f') => {
+			cursor.eat_str("false")?;
+			Value::Bool(false)
+		}
+		Some(c) if c == '-' || c.is_ascii_digit() => {
+			let token = cursor.take_while(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+			parse_number(&token, position)?
+		}
+		Some(c) => return Err(cursor.error(&format!("unexpected character {:?}", c))),
+		None => return Err(cursor.error("unexpected end of input")),
+	};
+	Ok(Node::new(value, position))
+}
+
+fn parse_object(cursor: &mut Cursor) -> ParseResult<Value> {
+	cursor.expect('{')?;
+	let mut entries = vec![];
+	skip_whitespace(cursor);
+	if cursor.eat('}') {
+		return Ok(Value::Table(entries));
+	}
+	loop {
+		skip_whitespace(cursor);
+		let position = cursor.position;
+		let key = parse_string(cursor)?;
+		if entries.iter().any(|(k, _)| *k == key) {
+			return Err(super::LayoutFileError::new(
+				&format!("duplicate key {:?}", key),
+				position,
+			));
+		}
+		skip_whitespace(cursor);
+		cursor.expect(':')?;
+		skip_whitespace(cursor);
+		entries.push((key, parse_value(cursor)?));
+		skip_whitespace(cursor);
+		if cursor.eat('}') {
+			return Ok(Value::Table(entries));
+		}
+		cursor.expect(',')?;
+	}
+}
+
+fn parse_array(cursor: &mut Cursor) -> ParseResult<Value> {
+	cursor.expect('[')?;
+	let mut items = vec![];
+	skip_whitespace(cursor);
+	if cursor.eat(']') {
+		return Ok(Value::Array(items));
+	}
+	loop {
+		skip_whitespace(cursor);
+		items.push(parse_value(cursor)?);
+		skip_whitespace(cursor);
+		if cursor.eat(']') {
+			return Ok(Value::Array(items));
+		}
+		cursor.expect(',')?;
+	}
+}
+
+fn parse_string(cursor: &mut Cursor) -> ParseResult<String> {
+	cursor.expect('"')?;
+	let mut s = String::new();
+	loop {
+		match cursor.bump() {
+			Some('"') => return Ok(s),
+			Some('\\') => s.push(read_escape(cursor)?),
+			Some('\n') | None => return Err(cursor.error("unterminated string")),
+			Some(c) => s.push(c),
+		}
+	}
+}
+
+// the backslash has already been read
+fn read_escape(cursor: &mut Cursor) -> ParseResult<char> {
+	let position = cursor.position;
+	match cursor.bump() {
+		Some('/') => Ok('/'),
+		Some('u') => read_unicode_escape(cursor, position),
+		Some(c) => simple_escape(c)
+			.ok_or_else(|| LayoutFileError::new("invalid escape sequence", position)),
+		None => Err(LayoutFileError::new("invalid escape sequence", position)),
+	}
+}
+
+// characters outside the BMP are escaped as a UTF-16 surrogate pair, e.g. "\uD83D\uDC96"
+fn read_unicode_escape(cursor: &mut Cursor, position: Position) -> ParseResult<char> {
+	let invalid = || LayoutFileError::new("invalid unicode escape", position);
+	let code = match read_hex(cursor, 4)? {
+		high @ 0xD800..=0xDBFF => {
+			if !(cursor.eat('\\') && cursor.eat('u')) {
+				return Err(invalid());
+			}
+			match read_hex(cursor, 4)? {
+				low @ 0xDC00..=0xDFFF => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+				_ => return Err(invalid()),
+			}
+		}
+		code => code,
+	};
+	char::from_u32(code).ok_or_else(invalid)
+}
+
+pub(super) fn write(node: &Node) -> String {
+	let mut out = String::new();
+	write_value(node, 0, &mut out);
+	out.push('\n');
+	out
+}
+
+fn write_value(node: &Node, depth: usize, out: &mut String) {
+	match &node.value {
+		Value::Bool(b) => out.push_str(&b.to_string()),
+		Value::Int(i) => out.push_str(&i.to_string()),
+		Value::Float(f) => out.push_str(&write_float(*f)),
+		Value::String(s) => escape_string(s, out),
+		Value::Array(items) => {
+			if items.is_empty() {
+				out.push_str("[]");
+				return;
+			}
+			out.push('[');
+			for (i, item) in items.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				newline(depth + 1, out);
+				write_value(item, depth + 1, out);
+			}
+			newline(depth, out);
+			out.push(']');
+		}
+		Value::Table(entries) => {
+			if entries.is_empty() {
+				out.push_str("{}");
+				return;
+			}
+			out.push('{');
+			for (i, (key, value)) in entries.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				newline(depth + 1, out);
+				escape_string(key, out);
+				out.push_str(": ");
+				write_value(value, depth + 1, out);
+			}
+			newline(depth, out);
+			out.push('}');
+		}
+	}
+}
+
+fn newline(depth: usize, out: &mut String) {
+	out.push('\n');
+	for _ in 0..depth {
+		out.push('\t');
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{
+		load_from_str,
+		tests::{assert_round_trip, at, every_control, load_error, TRICKY_TEXT},
+		Format,
+	};
+	use crate::layout::{Button, Control};
+
+	#[test]
+	fn round_trip() {
+		assert_round_trip(Format::Json, &every_control());
+		assert_round_trip(Format::Json, &Control::None);
+	}
+
+	#[test]
+	fn escapes() {
+		let saved = super::super::save_to_string(Format::Json, &Button::new(TRICKY_TEXT).done());
+		assert!(saved.contains(r#""Say \"hi\" <&> 'x' \\ \t tab\nline 💖 é \u0001""#));
+		let loaded = load_from_str(
+			Format::Json,
+			r#"{"type": "Button", "title": "é\/\"\t💖\uD83D\uDC96"}"#,
+		)
+		.unwrap();
+		match loaded {
+			Control::Button(button) => assert_eq!(button.title, "é/\"\t💖💖"),
+			_ => panic!("not a button"),
+		}
+	}
+
+	#[test]
+	fn syntax_errors() {
+		let e = load_error(
+			Format::Json,
+			"{\n\t\"type\": \"Button\",\n\t\"title\": \"Save\" \"id\": 1\n}",
+		);
+		assert_eq!(e.msg, "expected ',', found '\"'");
+		assert_eq!(e.position, at(3, 18));
+
+		let e = load_error(Format::Json, r#"{"type": "a\qb"}"#);
+		assert_eq!(e.msg, "invalid escape sequence");
+		assert_eq!(e.position, at(1, 13));
+
+		let e = load_error(
+			Format::Json,
+			"{\"type\": \"VStack\",\n \"type\": \"HStack\"}",
+		);
+		assert_eq!(e.msg, "duplicate key \"type\"");
+		assert_eq!(e.position, at(2, 2));
+
+		// a high surrogate needs a low one after it
+		for text in [r"\uD83D", r"\uD83Dx", r"\uD83D\u0041", r"\uDC96"] {
+			let e = load_error(Format::Json, &format!(r#"{{"type": "{}"}}"#, text));
+			assert_eq!(e.msg, "invalid unicode escape");
+			assert_eq!(e.position, at(1, 12));
+		}
+
+		let e = load_error(Format::Json, "{\"type\": \"None\"} []");
+		assert_eq!(e.position, at(1, 18));
+	}
+
+	#[test]
+	fn conversion_errors() {
+		let e = load_error(Format::Json, "{\n  \"type\": \"Buton\"\n}");
+		assert_eq!(e.msg, "unknown control type \"Buton\"");
+		assert_eq!(e.position, at(2, 11));
+
+		let e = load_error(
+			Format::Json,
+			"{\"type\": \"VStack\", \"items\": [\n\t{\"type\": \"Button\", \"id\": -1}\n]}",
+		);
+		assert_eq!(e.msg, "control id out of range");
+		assert_eq!(e.position, at(2, 27));
+	}
+}
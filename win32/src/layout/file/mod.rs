@@ -0,0 +1,381 @@
+// Textual descriptions of `Control` trees.
+//
+// All formats are parsed into the same `Value` tree, and `convert` maps it to and from controls. A control is a table
+// with a `type` key naming it, the other keys are its options:
+//
+//   VStack / HStack: padding, spacing, align, justify, items, dimension keys
+//   Grid: padding, rows, columns, row_gap, column_gap, items, dimension keys
+//   Button: title, default, id, disabled, margin, dimension keys
+//   InputText: text, id, disabled, margin, dimension keys
+//   CheckBox: title, checked, indeterminate, tri_state, id, disabled, margin, dimension keys
+//   RadioGroup: options, selected, id, disabled, margin, dimension keys
+//   GroupBox: title, id, disabled, margin, dimension keys
+//   ComboBox: options, selected, editable, id, disabled, margin, dimension keys
+//   ListBox: options, selection, multi_select, id, disabled, margin, dimension keys
+//
+// Dimension keys are width, height, grow, shrink, fill, min_width, max_width, min_height and max_height. Grid items
+// also take row, column, row_span, column_span, h_align and v_align. In XML the element name is the type, other keys are
+// attributes, items are child elements and the options of a RadioGroup, ComboBox or ListBox are `<option value=".." />`
+// child elements. Callbacks cannot be described and are left unset.
+
+mod convert;
+mod json;
+mod toml;
+mod xml;
+
+use std::fmt;
+
+use super::Control;
+use crate::assert::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Json,
+	Toml,
+	Xml,
+}
+
+/// Parse a `Control` tree. Errors are `LayoutFileError`s pointing at the faulty line and column.
+pub fn load_from_str(format: Format, s: &str) -> Result<Control> {
+	let value = match format {
+		Format::Json => json::parse(s)?,
+		Format::Toml => toml::parse(s)?,
+		Format::Xml => xml::parse(s)?,
+	};
+	Ok(convert::to_control(&value)?)
+}
+
+/// Describe a `Control` tree in the given format, `load_from_str` reads it back.
+pub fn save_to_string(format: Format, root: &Control) -> String {
+	let value = convert::from_control(root);
+	match format {
+		Format::Json => json::write(&value),
+		Format::Toml => toml::write(&value),
+		Format::Xml => xml::write(&value),
+	}
+}
+
+/// 1-based position in the parsed text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+	pub line: usize,
+	pub column: usize,
+}
+
+#[derive(Debug)]
+pub struct LayoutFileError {
+	pub msg: String,
+	pub position: Position,
+}
+
+impl LayoutFileError {
+	fn new(msg: &str, position: Position) -> Self {
+		Self {
+			msg: msg.to_owned(),
+			position,
+		}
+	}
+}
+
+impl fmt::Display for LayoutFileError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{} (line {}, column {})",
+			self.msg, self.position.line, self.position.column
+		)
+	}
+}
+
+impl std::error::Error for LayoutFileError {}
+
+type ParseResult<T> = std::result::Result<T, LayoutFileError>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	String(String),
+	Array(Vec<Node>),
+	// keys keep their order so that a saved tree reads like the original
+	Table(Vec<(String, Node)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+	value: Value,
+	position: Position,
+}
+
+impl Node {
+	fn new(value: Value, position: Position) -> Self {
+		Self { value, position }
+	}
+
+	fn type_name(&self) -> &'static str {
+		match self.value {
+			Value::Bool(_) => "boolean",
+			Value::Int(_) => "integer",
+			Value::Float(_) => "float",
+			Value::String(_) => "string",
+			Value::Array(_) => "array",
+			Value::Table(_) => "table",
+		}
+	}
+}
+
+// character reader shared by the parsers, keeps track of the current position
+struct Cursor<'a> {
+	chars: std::iter::Peekable<std::str::Chars<'a>>,
+	position: Position,
+}
+
+impl<'a> Cursor<'a> {
+	fn new(s: &'a str) -> Self {
+		Self {
+			chars: s.chars().peekable(),
+			position: Position { line: 1, column: 1 },
+		}
+	}
+
+	fn peek(&mut self) -> Option<char> {
+		self.chars.peek().copied()
+	}
+
+	fn bump(&mut self) -> Option<char> {
+		let c = self.chars.next()?;
+		if c == '\n' {
+			self.position.line += 1;
+			self.position.column = 1;
+		} else {
+			self.position.column += 1;
+		}
+		Some(c)
+	}
+
+	fn eat(&mut self, c: char) -> bool {
+		if self.peek() == Some(c) {
+			self.bump();
+			return true;
+		}
+		false
+	}
+
+	fn expect(&mut self, c: char) -> ParseResult<()> {
+		match self.peek() {
+			Some(found) if found == c => {
+				self.bump();
+				Ok(())
+			}
+			Some(found) => Err(self.error(&format!("expected {:?}, found {:?}", c, found))),
+			None => Err(self.error(&format!("expected {:?}, found end of input", c))),
+		}
+	}
+
+	fn eat_str(&mut self, s: &str) -> ParseResult<()> {
+		for c in s.chars() {
+			self.expect(c)?;
+		}
+		Ok(())
+	}
+
+	fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+		let mut s = String::new();
+		while let Some(c) = self.peek() {
+			if !f(c) {
+				break;
+			}
+			s.push(c);
+			self.bump();
+		}
+		s
+	}
+
+	fn error(&self, msg: &str) -> LayoutFileError {
+		LayoutFileError::new(msg, self.position)
+	}
+}
+
+// parse a number token shared by JSON and TOML, `_` separators must already be removed
+fn parse_number(token: &str, position: Position) -> ParseResult<Value> {
+	let is_float = token.contains(['.', 'e', 'E']);
+	let parsed = if is_float {
+		token.parse().map(Value::Float).ok()
+	} else {
+		token.parse().map(Value::Int).ok()
+	};
+	parsed.ok_or_else(|| LayoutFileError::new(&format!("invalid number {:?}", token), position))
+}
+
+fn write_float(f: f64) -> String {
+	// keep a decimal point so the value reads back as a float
+	if f.fract() == 0.0 && f.is_finite() {
+		format!("{:.1}", f)
+	} else {
+		format!("{}", f)
+	}
+}
+
+fn escape_string(s: &str, out: &mut String) {
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04X}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
+
+// character of the backslash escapes shared by JSON and TOML basic strings, the ones followed by a single character
+fn simple_escape(c: char) -> Option<char> {
+	let c = match c {
+		'"' => '"',
+		'\\' => '\\',
+		'b' => '\u{8}',
+		'f' => '\u{c}',
+		'n' => '\n',
+		'r' => '\r',
+		't' => '\t',
+		_ => return None,
+	};
+	Some(c)
+}
+
+// code of a unicode escape, after its `\u` or `\U`
+fn read_hex(cursor: &mut Cursor, digits: usize) -> ParseResult<u32> {
+	let mut code = 0;
+	for _ in 0..digits {
+		let digit = cursor
+			.bump()
+			.and_then(|c| c.to_digit(16))
+			.ok_or_else(|| cursor.error("invalid unicode escape"))?;
+		code = code * 16 + digit;
+	}
+	Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		button::CheckState,
+		layout::{
+			Align, Button, CheckBox, ComboBox, DimensionBuilder, Grid, GridCell, GroupBox, HStack,
+			InputText, Justify, ListBox, MarginBuilder, PaddingBuilder, RadioGroup, Track, VStack,
+		},
+	};
+
+	// text with characters every format has to escape
+	pub(super) const TRICKY_TEXT: &str = "Say \"hi\" <&> 'x' \\ \t tab\nline 💖 é \u{1}";
+
+	/// Tree with every control type and every key set to a value other than its default.
+	pub(super) fn every_control() -> Control {
+		let grid = Grid::new()
+			.rows(vec![Track::Auto, Track::Fraction(1.5), Track::Fixed(40)])
+			.columns(vec![Track::Fixed(100), Track::Fraction(1.0)])
+			.row_gap(4)
+			.column_gap(6)
+			.cells(vec![
+				GridCell::new(0, 0, GroupBox::new("Group").id(7).done())
+					.row_span(2)
+					.column_span(2)
+					.h_align(Align::Stretch)
+					.v_align(Align::End),
+				GridCell::new(2, 1, Control::None).h_align(Align::Center),
+			])
+			.top_padding(1)
+			.max_height(300)
+			.done();
+		let row = HStack::new()
+			.spacing(3)
+			.align(Align::Baseline)
+			.justify(Justify::SpaceEvenly)
+			.items(vec![
+				Button::new(TRICKY_TEXT)
+					.id(1)
+					.default_button(true)
+					.left_margin(2)
+					.width(80)
+					.done(),
+				InputText::new("ünïcödé ✓")
+					.disabled(true)
+					.grow(1.5)
+					.shrink(0.5)
+					.min_width(20)
+					.done(),
+			])
+			.horizontal_padding(8)
+			.done();
+		VStack::new()
+			.spacing(5)
+			.align(Align::Center)
+			.justify(Justify::SpaceBetween)
+			.items(vec![
+				row,
+				CheckBox::new("Checked")
+					.checked(true)
+					.vertical_margin(4)
+					.done(),
+				CheckBox::new("Mixed")
+					.tri_state(true)
+					.state(CheckState::Indeterminate)
+					.done(),
+				RadioGroup::new(&["Small", "Medium", "Large"])
+					.selected(Some(1))
+					.height(60)
+					.done(),
+				ComboBox::new(&["Red", "Green"])
+					.selected(Some(0))
+					.editable(true)
+					.id(9)
+					.fill()
+					.done(),
+				ListBox::new(&["One", "Two", "Three"])
+					.selection(vec![0, 2])
+					.multi_select(true)
+					.min_height(50)
+					.max_width(200)
+					.done(),
+				grid,
+			])
+			.vertical_padding(10)
+			.horizontal_padding(10)
+			.done()
+	}
+
+	/// Save `control`, load it back and check that nothing was lost.
+	pub(super) fn assert_round_trip(format: Format, control: &Control) {
+		let saved = save_to_string(format, control);
+		let loaded = load_from_str(format, &saved)
+			.unwrap_or_else(|e| panic!("failed to load {:?}: {}\n{}", format, e, saved));
+		assert_eq!(
+			convert::from_control(&loaded),
+			convert::from_control(control),
+			"{}",
+			saved
+		);
+		// saving again gives the same text
+		assert_eq!(save_to_string(format, &loaded), saved);
+	}
+
+	/// Error of loading `s`, which has to be a `LayoutFileError`.
+	pub(super) fn load_error(format: Format, s: &str) -> LayoutFileError {
+		match load_from_str(format, s) {
+			Ok(_) => panic!("loaded invalid {:?}: {}", format, s),
+			Err(e) => *e
+				.downcast::<LayoutFileError>()
+				.expect("not a LayoutFileError"),
+		}
+	}
+
+	pub(super) fn at(line: usize, column: usize) -> Position {
+		Position { line, column }
+	}
+}
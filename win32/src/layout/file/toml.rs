@@ -0,0 +1,431 @@
+// Subset of TOML used by layout files: bare and quoted keys, dotted keys, tables, arrays of tables, strings, integers,
+// floats, booleans, arrays and inline tables. Dates and multi-line strings are not supported.
+
+use super::{
+	escape_string, parse_number, read_hex, simple_escape, write_float, Cursor, LayoutFileError,
+	Node, ParseResult, Position, Value,
+};
+
+type Table = Vec<(String, Node)>;
+type Key = Vec<(String, Position)>;
+
+pub(super) fn parse(s: &str) -> ParseResult<Node> {
+	let mut cursor = Cursor::new(s);
+	let mut root: Table = vec![];
+	// header of the table key/values are added to, arrays of tables resolve to their last element
+	let mut current: Key = vec![];
+
+	loop {
+		skip_blank(&mut cursor);
+		match cursor.peek() {
+			None => break,
+			Some('[') => {
+				cursor.bump();
+				let array = cursor.eat('[');
+				skip_spaces(&mut cursor);
+				let key = parse_key(&mut cursor)?;
+				skip_spaces(&mut cursor);
+				cursor.expect(']')?;
+				if array {
+					cursor.expect(']')?;
+					let (last, parent) = key.split_last().expect("keys are never empty");
+					let parent = table_at(&mut root, parent)?;
+					push_array_table(parent, last)?;
+				} else {
+					table_at(&mut root, &key)?;
+				}
+				current = key;
+			}
+			Some(_) => {
+				let key = parse_key(&mut cursor)?;
+				skip_spaces(&mut cursor);
+				cursor.expect('=')?;
+				skip_spaces(&mut cursor);
+				let value = parse_value(&mut cursor)?;
+				let table = table_at(&mut root, &current)?;
+				insert(table, &key, value)?;
+			}
+		}
+		end_of_line(&mut cursor)?;
+	}
+
+	Ok(Node::new(
+		Value::Table(root),
+		Position { line: 1, column: 1 },
+	))
+}
+
+fn skip_spaces(cursor: &mut Cursor) {
+	cursor.take_while(|c| c == ' ' || c == '\t');
+}
+
+// skip whitespace, newlines and comments
+fn skip_blank(cursor: &mut Cursor) {
+	loop {
+		cursor.take_while(char::is_whitespace);
+		if cursor.peek() != Some('#') {
+			return;
+		}
+		cursor.take_while(|c| c != '\n');
+	}
+}
+
+fn end_of_line(cursor: &mut Cursor) -> ParseResult<()> {
+	skip_spaces(cursor);
+	if cursor.peek() == Some('#') {
+		cursor.take_while(|c| c != '\n');
+	}
+	match cursor.peek() {
+		None | Some('\n') | Some('\r') => Ok(()),
+		Some(c) => Err(cursor.error(&format!("expected end of line, found {:?}", c))),
+	}
+}
+
+fn is_bare_key_char(c: char) -> bool {
+	c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn parse_key(cursor: &mut Cursor) -> ParseResult<Key> {
+	let mut key = vec![];
+	loop {
+		skip_spaces(cursor);
+		let position = cursor.position;
+		let part = match cursor.peek() {
+			Some('"') => parse_basic_string(cursor)?,
+			Some('\'') => parse_literal_string(cursor)?,
+			_ => cursor.take_while(is_bare_key_char),
+		};
+		if part.is_empty() {
+			return Err(LayoutFileError::new("expected a key", position));
+		}
+		key.push((part, position));
+		skip_spaces(cursor);
+		if !cursor.eat('.') {
+			return Ok(key);
+		}
+	}
+}
+
+// table at `key` from `root`, missing tables are created
+fn table_at<'a>(root: &'a mut Table, key: &[(String, Position)]) -> ParseResult<&'a mut Table> {
+	let ((name, position), rest) = match key.split_first() {
+		Some(split) => split,
+		None => return Ok(root),
+	};
+	let index = match root.iter().position(|(k, _)| k == name) {
+		Some(index) => index,
+		None => {
+			root.push((name.clone(), Node::new(Value::Table(vec![]), *position)));
+			root.len() - 1
+		}
+	};
+	let node = &mut root[index].1;
+	let table = match &mut node.value {
+		Value::Table(table) => table,
+		Value::Array(items) => match items.last_mut() {
+			Some(Node {
+				value: Value::Table(table),
+				..
+			}) => table,
+			_ => return Err(not_a_table(name, *position)),
+		},
+		_ => return Err(not_a_table(name, *position)),
+	};
+	table_at(table, rest)
+}
+
+fn not_a_table(name: &str, position: Position) -> LayoutFileError {
+	LayoutFileError::new(&format!("key {:?} is not a table", name), position)
+}
+
+fn push_array_table(parent: &mut Table, (name, position): &(String, Position)) -> ParseResult<()> {
+	let new_table = Node::new(Value::Table(vec![]), *position);
+	match parent.iter_mut().find(|(k, _)| k == name) {
+		Some((_, node)) => match &mut node.value {
+			Value::Array(items) => items.push(new_table),
+			_ => {
+				return Err(LayoutFileError::new(
+					&format!("key {:?} is not an array of tables", name),
+					*position,
+				))
+			}
+		},
+		None => parent.push((
+			name.clone(),
+			Node::new(Value::Array(vec![new_table]), *position),
+		)),
+	}
+	Ok(())
+}
+
+fn insert(table: &mut Table, key: &[(String, Position)], value: Node) -> ParseResult<()> {
+	let (last, parent) = key.split_last().expect("keys are never empty");
+	let table = table_at(table, parent)?;
+	if table.iter().any(|(k, _)| *k == last.0) {
+		return Err(LayoutFileError::new(
+			&format!("duplicate key {:?}", last.0),
+			last.1,
+		));
+	}
+	table.push((last.0.clone(), value));
+	Ok(())
+}
+
+fn parse_value(cursor: &mut Cursor) -> ParseResult<Node> {
+	let position = cursor.position;
+	let value = match cursor.peek() {
+		Some('"') => Value::String(parse_basic_string(cursor)?),
+		Some('\'') => Value::String(parse_literal_string(cursor)?),
+		Some('[') => parse_array(cursor)?,
+		Some('{') => parse_inline_table(cursor)?,
+		Some(c) if c.is_ascii_alphanumeric() || c == '+' || c == '-' => {
+			let token = cursor.take_while(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+			match token.as_str() {
+				"true" => Value::Bool(true),
+				"false" => Value::Bool(false),
+				_ => parse_number(&token.replace('_', ""), position)?,
+			}
+		}
+		Some(c) => return Err(cursor.error(&format!("unexpected character {:?}", c))),
+		None => return Err(cursor.error("unexpected end of input")),
+	};
+	Ok(Node::new(value, position))
+}
+
+fn parse_array(cursor: &mut Cursor) -> ParseResult<Value> {
+	cursor.expect('[')?;
+	let mut items = vec![];
+	loop {
+		skip_blank(cursor);
+		if cursor.eat(']') {
+			return Ok(Value::Array(items));
+		}
+		items.push(parse_value(cursor)?);
+		skip_blank(cursor);
+		if cursor.eat(']') {
+			return Ok(Value::Array(items));
+		}
+		cursor.expect(',')?;
+	}
+}
+
+fn parse_inline_table(cursor: &mut Cursor) -> ParseResult<Value> {
+	cursor.expect('{')?;
+	let mut table = vec![];
+	skip_spaces(cursor);
+	if cursor.eat('}') {
+		return Ok(Value::Table(table));
+	}
+	loop {
+		let key = parse_key(cursor)?;
+		cursor.expect('=')?;
+		skip_spaces(cursor);
+		let value = parse_value(cursor)?;
+		insert(&mut table, &key, value)?;
+		skip_spaces(cursor);
+		if cursor.eat('}') {
+			return Ok(Value::Table(table));
+		}
+		cursor.expect(',')?;
+		skip_spaces(cursor);
+	}
+}
+
+fn parse_basic_string(cursor: &mut Cursor) -> ParseResult<String> {
+	cursor.expect('"')?;
+	let mut s = String::new();
+	loop {
+		match cursor.bump() {
+			Some('"') => return Ok(s),
+			Some('\\') => s.push(read_escape(cursor)?),
+			Some('\n') | None => return Err(cursor.error("unterminated string")),
+			Some(c) => s.push(c),
+		}
+	}
+}
+
+fn parse_literal_string(cursor: &mut Cursor) -> ParseResult<String> {
+	cursor.expect('\'')?;
+	let s = cursor.take_while(|c| c != '\'' && c != '\n');
+	cursor.expect('\'')?;
+	Ok(s)
+}
+
+// the backslash has already been read
+fn read_escape(cursor: &mut Cursor) -> ParseResult<char> {
+	let position = cursor.position;
+	let code = match cursor.bump() {
+		Some('u') => read_hex(cursor, 4)?,
+		Some('U') => read_hex(cursor, 8)?,
+		Some(c) => {
+			return simple_escape(c)
+				.ok_or_else(|| LayoutFileError::new("invalid escape sequence", position))
+		}
+		None => return Err(LayoutFileError::new("invalid escape sequence", position)),
+	};
+	// surrogates aren't scalar values, TOML has no pairs of them
+	char::from_u32(code).ok_or_else(|| LayoutFileError::new("invalid unicode escape", position))
+}
+
+pub(super) fn write(node: &Node) -> String {
+	let mut out = String::new();
+	if let Value::Table(table) = &node.value {
+		write_table(table, "", &mut out);
+	}
+	out
+}
+
+fn is_array_of_tables(value: &Value) -> bool {
+	match value {
+		Value::Array(items) => {
+			!items.is_empty()
+				&& items
+					.iter()
+					.all(|item| matches!(item.value, Value::Table(_)))
+		}
+		_ => false,
+	}
+}
+
+fn write_table(table: &Table, path: &str, out: &mut String) {
+	// key/values have to come before the headers of nested tables
+	for (key, node) in table {
+		if !is_array_of_tables(&node.value) {
+			write_key(key, out);
+			out.push_str(" = ");
+			write_inline(node, out);
+			out.push('\n');
+		}
+	}
+	for (key, node) in table {
+		if let Value::Array(items) = &node.value {
+			if !is_array_of_tables(&node.value) {
+				continue;
+			}
+			let mut item_path = path.to_owned();
+			if !item_path.is_empty() {
+				item_path.push('.');
+			}
+			write_key(key, &mut item_path);
+			for item in items {
+				if let Value::Table(item) = &item.value {
+					out.push_str(&format!("\n[[{}]]\n", item_path));
+					write_table(item, &item_path, out);
+				}
+			}
+		}
+	}
+}
+
+fn write_key(key: &str, out: &mut String) {
+	if !key.is_empty() && key.chars().all(is_bare_key_char) {
+		out.push_str(key);
+	} else {
+		escape_string(key, out);
+	}
+}
+
+fn write_inline(node: &Node, out: &mut String) {
+	match &node.value {
+		Value::Bool(b) => out.push_str(&b.to_string()),
+		Value::Int(i) => out.push_str(&i.to_string()),
+		Value::Float(f) => out.push_str(&write_float(*f)),
+		Value::String(s) => escape_string(s, out),
+		Value::Array(items) => {
+			out.push('[');
+			for (i, item) in items.iter().enumerate() {
+				if i > 0 {
+					out.push_str(", ");
+				}
+				write_inline(item, out);
+			}
+			out.push(']');
+		}
+		Value::Table(table) => {
+			out.push_str("{ ");
+			for (i, (key, value)) in table.iter().enumerate() {
+				if i > 0 {
+					out.push_str(", ");
+				}
+				write_key(key, out);
+				out.push_str(" = ");
+				write_inline(value, out);
+			}
+			out.push_str(" }");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{
+		load_from_str, save_to_string,
+		tests::{assert_round_trip, at, every_control, load_error, TRICKY_TEXT},
+		Format,
+	};
+	use crate::layout::{Control, InputText};
+
+	#[test]
+	fn round_trip() {
+		assert_round_trip(Format::Toml, &every_control());
+		assert_round_trip(Format::Toml, &Control::None);
+	}
+
+	#[test]
+	fn escapes() {
+		let saved = save_to_string(Format::Toml, &InputText::new(TRICKY_TEXT).done());
+		assert!(saved.contains(r#"text = "Say \"hi\" <&> 'x' \\ \t tab\nline 💖 é \u0001""#));
+		let loaded = load_from_str(
+			Format::Toml,
+			"type = 'InputText' # literal\n\"text\" = \"\\u00e9 'quoted' 💖 \\U0001F496\"\n",
+		)
+		.unwrap();
+		match loaded {
+			Control::InputText(input) => assert_eq!(input.text, "é 'quoted' 💖 💖"),
+			_ => panic!("not an input"),
+		}
+
+		// JSON only
+		let e = load_error(Format::Toml, "type = \"a\\/b\"\n");
+		assert_eq!(e.msg, "invalid escape sequence");
+		assert_eq!(e.position, at(1, 11));
+		let e = load_error(Format::Toml, "type = \"\\uD83D\\uDC96\"\n");
+		assert_eq!(e.msg, "invalid unicode escape");
+		assert_eq!(e.position, at(1, 10));
+		let e = load_error(Format::Toml, "type = \"\\U00110000\"\n");
+		assert_eq!(e.msg, "invalid unicode escape");
+	}
+
+	#[test]
+	fn syntax_errors() {
+		let e = load_error(Format::Toml, "type = \"Button\"\ntitle = \"Save\" extra\n");
+		assert_eq!(e.msg, "expected end of line, found 'e'");
+		assert_eq!(e.position, at(2, 16));
+
+		let e = load_error(Format::Toml, "type = \"VStack\"\ntype = \"HStack\"\n");
+		assert_eq!(e.msg, "duplicate key \"type\"");
+		assert_eq!(e.position, at(2, 1));
+
+		let e = load_error(
+			Format::Toml,
+			"type = \"VStack\"\n\n[[items]]\ntype = \"Button\n",
+		);
+		assert_eq!(e.msg, "unterminated string");
+		assert_eq!(e.position, at(5, 1));
+
+		let e = load_error(Format::Toml, "type = \"VStack\"\nitems = 1\n[[items]]\n");
+		assert_eq!(e.msg, "key \"items\" is not an array of tables");
+		assert_eq!(e.position, at(3, 3));
+	}
+
+	#[test]
+	fn conversion_errors() {
+		let e = load_error(Format::Toml, "type = \"Button\"\ncolor = \"red\"\n");
+		assert_eq!(e.msg, "unknown key \"color\" for Button");
+		assert_eq!(e.position, at(2, 9));
+
+		let e = load_error(Format::Toml, "type = \"VStack\"\nalign = \"middle\"\n");
+		assert_eq!(e.msg, "expected an alignment, found \"middle\"");
+		assert_eq!(e.position, at(2, 9));
+	}
+}
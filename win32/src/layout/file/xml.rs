@@ -0,0 +1,395 @@
+// Subset of XML used by layout files: elements, attributes, comments and the standard entities. Elements are converted
+// to tables, with their name as `type`, their attributes as string values and their child elements as `items`, except
+// for `<option value=".." />` children which are collected as an `options` array of strings.
+
+use super::{write_float, Cursor, LayoutFileError, Node, ParseResult, Value};
+
+pub(super) fn parse(s: &str) -> ParseResult<Node> {
+	let mut cursor = Cursor::new(s);
+	skip_misc(&mut cursor)?;
+	if cursor.peek() != Some('<') {
+		return Err(cursor.error("expected a root element"));
+	}
+	let root = parse_element(&mut cursor)?;
+	skip_misc(&mut cursor)?;
+	if cursor.peek().is_some() {
+		return Err(cursor.error("unexpected content after the root element"));
+	}
+	Ok(root)
+}
+
+// skip whitespace, comments, the xml declaration and doctype
+fn skip_misc(cursor: &mut Cursor) -> ParseResult<()> {
+	loop {
+		cursor.take_while(char::is_whitespace);
+		if !skip_markup(cursor)? {
+			return Ok(());
+		}
+	}
+}
+
+// skip a comment, processing instruction or declaration, returns false if the next markup is an element
+fn skip_markup(cursor: &mut Cursor) -> ParseResult<bool> {
+	// cursor cannot look two characters ahead, peek on a copy
+	let mut ahead = cursor.chars.clone();
+	if ahead.next() != Some('<') {
+		return Ok(false);
+	}
+	match ahead.next() {
+		Some('?') => {
+			skip_until(cursor, "?>")?;
+			Ok(true)
+		}
+		Some('!') => {
+			let comment = ahead.next() == Some('-');
+			skip_until(cursor, if comment { "-->" } else { ">" })?;
+			Ok(true)
+		}
+		_ => Ok(false),
+	}
+}
+
+fn skip_until(cursor: &mut Cursor, end: &str) -> ParseResult<()> {
+	let mut seen = String::new();
+	while !seen.ends_with(end) {
+		match cursor.bump() {
+			Some(c) => seen.push(c),
+			None => return Err(cursor.error(&format!("expected {:?}", end))),
+		}
+	}
+	Ok(())
+}
+
+fn is_name_char(c: char) -> bool {
+	c.is_alphanumeric() || "_-.:".contains(c)
+}
+
+fn parse_name(cursor: &mut Cursor) -> ParseResult<String> {
+	let name = cursor.take_while(is_name_char);
+	if name.is_empty() {
+		return Err(cursor.error("expected a name"));
+	}
+	Ok(name)
+}
+
+fn parse_element(cursor: &mut Cursor) -> ParseResult<Node> {
+	let position = cursor.position;
+	cursor.expect('<')?;
+	let name_position = cursor.position;
+	let name = parse_name(cursor)?;
+	let mut table = vec![(
+		"type".to_owned(),
+		Node::new(Value::String(name.clone()), name_position),
+	)];
+
+	loop {
+		cursor.take_while(char::is_whitespace);
+		match cursor.peek() {
+			Some('/') => {
+				cursor.bump();
+				cursor.expect('>')?;
+				return Ok(Node::new(Value::Table(table), position));
+			}
+			Some('>') => {
+				cursor.bump();
+				break;
+			}
+			_ => {
+				let attr_position = cursor.position;
+				let attr = parse_name(cursor)?;
+				if table.iter().any(|(k, _)| *k == attr) {
+					return Err(LayoutFileError::new(
+						&format!("duplicate attribute {:?}", attr),
+						attr_position,
+					));
+				}
+				cursor.take_while(char::is_whitespace);
+				cursor.expect('=')?;
+				cursor.take_while(char::is_whitespace);
+				let value_position = cursor.position;
+				let value = parse_attribute_value(cursor)?;
+				table.push((attr, Node::new(Value::String(value), value_position)));
+			}
+		}
+	}
+
+	let mut items = vec![];
+	let mut options = vec![];
+	loop {
+		cursor.take_while(char::is_whitespace);
+		if skip_markup(cursor)? {
+			continue;
+		}
+		match cursor.peek() {
+			Some('<') => {
+				let mut ahead = cursor.chars.clone();
+				ahead.next();
+				if ahead.next() == Some('/') {
+					break;
+				}
+				let child = parse_element(cursor)?;
+				match option_value(&child)? {
+					Some(value) => options.push(value),
+					None => items.push(child),
+				}
+			}
+			Some(_) => return Err(cursor.error("unexpected text content")),
+			None => return Err(cursor.error(&format!("unclosed element {:?}", name))),
+		}
+	}
+
+	cursor.eat_str("</")?;
+	let close_position = cursor.position;
+	let close = parse_name(cursor)?;
+	if close != name {
+		return Err(LayoutFileError::new(
+			&format!("expected </{}>, found </{}>", name, close),
+			close_position,
+		));
+	}
+	cursor.take_while(char::is_whitespace);
+	cursor.expect('>')?;
+
+	if !options.is_empty() {
+		let options_position = options[0].position;
+		table.push((
+			"options".to_owned(),
+			Node::new(Value::Array(options), options_position),
+		));
+	}
+	if !items.is_empty() {
+		let items_position = items[0].position;
+		table.push((
+			"items".to_owned(),
+			Node::new(Value::Array(items), items_position),
+		));
+	}
+	Ok(Node::new(Value::Table(table), position))
+}
+
+// the value of an `<option value=".." />` element, None for other elements
+fn option_value(element: &Node) -> ParseResult<Option<Node>> {
+	let table = match &element.value {
+		Value::Table(table) => table,
+		_ => return Ok(None),
+	};
+	let mut value = None;
+	for (key, node) in table {
+		match (key.as_str(), &node.value) {
+			("type", Value::String(name)) if name == "option" => {}
+			("type", _) => return Ok(None),
+			("value", _) => value = Some(node.clone()),
+			_ => {
+				return Err(LayoutFileError::new(
+					&format!("unknown key {:?} for option", key),
+					node.position,
+				))
+			}
+		}
+	}
+	value
+		.map(Some)
+		.ok_or_else(|| LayoutFileError::new("missing key \"value\" for option", element.position))
+}
+
+fn parse_attribute_value(cursor: &mut Cursor) -> ParseResult<String> {
+	let quote = match cursor.bump() {
+		Some(c) if c == '"' || c == '\'' => c,
+		_ => return Err(cursor.error("expected a quoted attribute value")),
+	};
+	let mut s = String::new();
+	loop {
+		match cursor.bump() {
+			Some(c) if c == quote => return Ok(s),
+			Some('&') => s.push(parse_entity(cursor)?),
+			Some('<') => return Err(cursor.error("unexpected '<' in attribute value")),
+			Some(c) => s.push(c),
+			None => return Err(cursor.error("unterminated attribute value")),
+		}
+	}
+}
+
+fn parse_entity(cursor: &mut Cursor) -> ParseResult<char> {
+	let position = cursor.position;
+	let entity = cursor.take_while(|c| c != ';' && c != '<' && !c.is_whitespace());
+	cursor.expect(';')?;
+	let c = match entity.as_str() {
+		"lt" => Some('<'),
+		"gt" => Some('>'),
+		"amp" => Some('&'),
+		"quot" => Some('"'),
+		"apos" => Some('\''),
+		_ => match entity.strip_prefix("#x") {
+			Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+			None => entity
+				.strip_prefix('#')
+				.and_then(|dec| dec.parse().ok())
+				.and_then(char::from_u32),
+		},
+	};
+	c.ok_or_else(|| LayoutFileError::new(&format!("unknown entity &{};", entity), position))
+}
+
+pub(super) fn write(node: &Node) -> String {
+	let mut out = String::new();
+	write_element(node, 0, &mut out);
+	out
+}
+
+fn write_element(node: &Node, depth: usize, out: &mut String) {
+	let table = match &node.value {
+		Value::Table(table) => table,
+		_ => return,
+	};
+	let name = match table.iter().find(|(k, _)| k == "type") {
+		Some((
+			_,
+			Node {
+				value: Value::String(name),
+				..
+			},
+		)) => name.as_str(),
+		_ => return,
+	};
+
+	indent(depth, out);
+	out.push('<');
+	out.push_str(name);
+	let mut children = None;
+	let mut options = None;
+	for (key, value) in table {
+		match &value.value {
+			_ if key == "type" => {}
+			Value::Array(items) if key == "items" => children = Some(items),
+			Value::Array(items) if key == "options" => options = Some(items),
+			_ => {
+				if let Some(text) = attribute_text(&value.value) {
+					out.push_str(&format!(" {}=\"{}\"", key, escape_attribute(&text)));
+				}
+			}
+		}
+	}
+
+	let options = options.map(Vec::as_slice).unwrap_or_default();
+	let children = children.map(Vec::as_slice).unwrap_or_default();
+	if options.is_empty() && children.is_empty() {
+		out.push_str(" />\n");
+		return;
+	}
+	out.push_str(">\n");
+	for option in options {
+		if let Some(text) = attribute_text(&option.value) {
+			indent(depth + 1, out);
+			out.push_str(&format!(
+				"<option value=\"{}\" />\n",
+				escape_attribute(&text)
+			));
+		}
+	}
+	for item in children {
+		write_element(item, depth + 1, out);
+	}
+	indent(depth, out);
+	out.push_str(&format!("</{}>\n", name));
+}
+
+fn attribute_text(value: &Value) -> Option<String> {
+	match value {
+		Value::Bool(b) => Some(b.to_string()),
+		Value::Int(i) => Some(i.to_string()),
+		Value::Float(f) => Some(write_float(*f)),
+		Value::String(s) => Some(s.clone()),
+		Value::Array(items) => {
+			let parts: Option<Vec<String>> =
+				items.iter().map(|i| attribute_text(&i.value)).collect();
+			parts.map(|p| p.join(" "))
+		}
+		Value::Table(_) => None,
+	}
+}
+
+fn escape_attribute(s: &str) -> String {
+	s.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+fn indent(depth: usize, out: &mut String) {
+	for _ in 0..depth {
+		out.push('\t');
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{
+		load_from_str, save_to_string,
+		tests::{assert_round_trip, at, every_control, load_error, TRICKY_TEXT},
+		Format,
+	};
+	use crate::layout::{Control, GroupBox};
+
+	#[test]
+	fn round_trip() {
+		assert_round_trip(Format::Xml, &every_control());
+		assert_round_trip(Format::Xml, &Control::None);
+	}
+
+	#[test]
+	fn escapes() {
+		let saved = save_to_string(Format::Xml, &GroupBox::new(TRICKY_TEXT).done());
+		assert!(saved
+			.contains("title=\"Say &quot;hi&quot; &lt;&amp;&gt; 'x' \\ \t tab\nline 💖 é \u{1}\""));
+		let loaded = load_from_str(
+			Format::Xml,
+			"<?xml version=\"1.0\"?>\n<!-- a group -->\n<GroupBox title='&#233;&#x1F496;&apos;' />",
+		)
+		.unwrap();
+		match loaded {
+			Control::GroupBox(group) => assert_eq!(group.title, "é💖'"),
+			_ => panic!("not a group box"),
+		}
+	}
+
+	#[test]
+	fn syntax_errors() {
+		let e = load_error(Format::Xml, "<VStack>\n\t<Button title=\"a\" />\n</HStack>");
+		assert_eq!(e.msg, "expected </VStack>, found </HStack>");
+		assert_eq!(e.position, at(3, 3));
+
+		let e = load_error(Format::Xml, "<Button title=\"a &nbsp; b\" />");
+		assert_eq!(e.msg, "unknown entity &nbsp;");
+		assert_eq!(e.position, at(1, 19));
+
+		let e = load_error(Format::Xml, "<VStack>\n");
+		assert_eq!(e.msg, "unclosed element \"VStack\"");
+		assert_eq!(e.position, at(2, 1));
+
+		let e = load_error(Format::Xml, "<Button title=\"a\" title=\"b\" />");
+		assert_eq!(e.msg, "duplicate attribute \"title\"");
+		assert_eq!(e.position, at(1, 19));
+	}
+
+	#[test]
+	fn conversion_errors() {
+		let e = load_error(Format::Xml, "<VStack>\n  <Button id=\"x\" />\n</VStack>");
+		assert_eq!(e.msg, "expected an integer, found \"x\"");
+		assert_eq!(e.position, at(2, 14));
+
+		let e = load_error(Format::Xml, "<Grid>\n\t<Label row=\"0\" />\n</Grid>");
+		assert_eq!(e.msg, "unknown control type \"Label\"");
+		assert_eq!(e.position, at(2, 3));
+
+		let e = load_error(Format::Xml, "<ComboBox>\n\t<option />\n</ComboBox>");
+		assert_eq!(e.msg, "missing key \"value\" for option");
+		assert_eq!(e.position, at(2, 2));
+
+		let e = load_error(
+			Format::Xml,
+			"<ListBox>\n\t<option value=\"a\" id=\"1\" />\n</ListBox>",
+		);
+		assert_eq!(e.msg, "unknown key \"id\" for option");
+		assert_eq!(e.position, at(2, 23));
+	}
+}
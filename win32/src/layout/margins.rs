@@ -1,28 +1,115 @@
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Dimension {
 	pub height: i32,
 	pub width: i32,
+	// share of the free space along the parent stack axis this control takes
+	pub grow: f32,
+	// share of the missing space along the parent stack axis this control gives up, relative to its size
+	pub shrink: f32,
+	// stretch to the parent stack size on the cross axis
+	pub fill: bool,
+	pub min_width: Option<i32>,
+	pub max_width: Option<i32>,
+	pub min_height: Option<i32>,
+	pub max_height: Option<i32>,
+}
+
+impl Dimension {
+	pub fn clamp_width(&self, v: i32) -> i32 {
+		clamp(v, self.min_width, self.max_width)
+	}
+
+	pub fn clamp_height(&self, v: i32) -> i32 {
+		clamp(v, self.min_height, self.max_height)
+	}
+}
+
+fn clamp(v: i32, min: Option<i32>, max: Option<i32>) -> i32 {
+	let v = match max {
+		Some(max) if v > max => max,
+		_ => v,
+	};
+	match min {
+		Some(min) if v < min => min,
+		_ => v,
+	}
 }
 
 pub trait DimensionBuilder
 where
 	Self: Sized,
 {
-	fn set_height(&mut self, v: i32);
-	fn set_width(&mut self, v: i32);
+	fn get_dimension(&self) -> Dimension;
+	fn set_dimension(&mut self, d: Dimension);
 
 	fn height(mut self, v: i32) -> Self {
-		self.set_height(v);
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { height: v, ..d });
 		self
 	}
 
 	fn width(mut self, v: i32) -> Self {
-		self.set_width(v);
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { width: v, ..d });
+		self
+	}
+
+	fn grow(mut self, v: f32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { grow: v, ..d });
+		self
+	}
+
+	fn shrink(mut self, v: f32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { shrink: v, ..d });
+		self
+	}
+
+	fn fill(mut self) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension { fill: true, ..d });
+		self
+	}
+
+	fn min_width(mut self, v: i32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension {
+			min_width: Some(v),
+			..d
+		});
+		self
+	}
+
+	fn max_width(mut self, v: i32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension {
+			max_width: Some(v),
+			..d
+		});
+		self
+	}
+
+	fn min_height(mut self, v: i32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension {
+			min_height: Some(v),
+			..d
+		});
+		self
+	}
+
+	fn max_height(mut self, v: i32) -> Self {
+		let d = self.get_dimension();
+		self.set_dimension(Dimension {
+			max_height: Some(v),
+			..d
+		});
 		self
 	}
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Margin {
 	pub right: i32,
 	pub left: i32,
@@ -82,7 +169,7 @@ where
 	}
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Padding {
 	pub right: i32,
 	pub left: i32,
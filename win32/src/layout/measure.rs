@@ -0,0 +1,767 @@
+use std::{cmp, iter};
+
+use super::{Align, Control, Dimension, Grid, GridCell, Justify, Margin, Padding, Track};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+	pub width: i32,
+	pub height: i32,
+}
+
+impl Size {
+	pub fn new(width: i32, height: i32) -> Self {
+		Self { width, height }
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+	pub x: i32,
+	pub y: i32,
+	pub width: i32,
+	pub height: i32,
+}
+
+impl Rect {
+	pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+		Self {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+}
+
+/// Computed geometry of a `Control` tree. Each node mirrors the control at the same position in the tree: `frame` is
+/// the rect a leaf control occupies (margins excluded), or the rect of a container (padding included).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LayoutTree {
+	pub frame: Rect,
+	pub children: Vec<LayoutTree>,
+}
+
+impl LayoutTree {
+	fn leaf(frame: Rect) -> Self {
+		Self {
+			frame,
+			children: vec![],
+		}
+	}
+
+	fn translate(&mut self, dx: i32, dy: i32) {
+		self.frame.x += dx;
+		self.frame.y += dy;
+		for child in &mut self.children {
+			child.translate(dx, dy);
+		}
+	}
+
+	// approximated text baseline, see Align::Baseline
+	fn baseline(&self) -> i32 {
+		match self.children.first() {
+			Some(first) => first.baseline(),
+			None => self.frame.y + self.frame.height / 2,
+		}
+	}
+}
+
+/// Compute the frame of every node of `root` without creating any window. The root is placed at the origin and fills
+/// `available`, usually the client area of the parent window.
+pub fn measure(root: &Control, available: Size) -> LayoutTree {
+	place(root, Rect::new(0, 0, available.width, available.height))
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+	Horizontal,
+	Vertical,
+}
+
+impl Axis {
+	fn main(self, s: Size) -> i32 {
+		match self {
+			Axis::Horizontal => s.width,
+			Axis::Vertical => s.height,
+		}
+	}
+
+	fn cross(self, s: Size) -> i32 {
+		match self {
+			Axis::Horizontal => s.height,
+			Axis::Vertical => s.width,
+		}
+	}
+
+	fn size(self, main: i32, cross: i32) -> Size {
+		match self {
+			Axis::Horizontal => Size::new(main, cross),
+			Axis::Vertical => Size::new(cross, main),
+		}
+	}
+}
+
+// common view over HStack and VStack
+struct Stack<'a> {
+	axis: Axis,
+	items: &'a [Control],
+	padding: Padding,
+	spacing: i32,
+	align: Align,
+	justify: Justify,
+}
+
+fn stack_of(control: &Control) -> Option<Stack<'_>> {
+	match control {
+		Control::HStack(stack) => Some(Stack {
+			axis: Axis::Horizontal,
+			items: &stack.items,
+			padding: stack.padding,
+			spacing: stack.spacing,
+			align: stack.align,
+			justify: stack.justify,
+		}),
+		Control::VStack(stack) => Some(Stack {
+			axis: Axis::Vertical,
+			items: &stack.items,
+			padding: stack.padding,
+			spacing: stack.spacing,
+			align: stack.align,
+			justify: stack.justify,
+		}),
+		_ => None,
+	}
+}
+
+fn dimension_of(control: &Control) -> Dimension {
+	match control {
+		Control::None => Default::default(),
+		Control::HStack(stack) => stack.dimension,
+		Control::VStack(stack) => stack.dimension,
+		Control::Grid(grid) => grid.dimension,
+		Control::Button(button) => button.dimension,
+		Control::InputText(input) => input.dimension,
+		Control::CheckBox(check) => check.dimension,
+		Control::RadioGroup(radio) => radio.dimension,
+		Control::GroupBox(group) => group.dimension,
+		Control::ComboBox(combo) => combo.dimension,
+		Control::ListBox(list) => list.dimension,
+	}
+}
+
+fn margin_of(control: &Control) -> Margin {
+	match control {
+		Control::Button(button) => button.margin,
+		Control::InputText(input) => input.margin,
+		Control::CheckBox(check) => check.margin,
+		Control::RadioGroup(radio) => radio.margin,
+		Control::GroupBox(group) => group.margin,
+		Control::ComboBox(combo) => combo.margin,
+		Control::ListBox(list) => list.margin,
+		_ => Default::default(),
+	}
+}
+
+fn margin_size(m: Margin) -> Size {
+	Size::new(m.left + m.right, m.top + m.bottom)
+}
+
+// size of the control before flex adjustments, margins excluded
+fn content_size(control: &Control) -> Size {
+	let d = dimension_of(control);
+	let (content, p) = if let Some(stack) = stack_of(control) {
+		(stack_content_size(&stack), stack.padding)
+	} else if let Control::Grid(grid) = control {
+		let columns = grid_tracks(grid, Axis::Horizontal, None);
+		let rows = grid_tracks(grid, Axis::Vertical, None);
+		let content = Size::new(
+			tracks_size(&columns, grid.column_gap),
+			tracks_size(&rows, grid.row_gap),
+		);
+		(content, grid.padding)
+	} else {
+		return Size::new(d.width, d.height);
+	};
+
+	Size::new(
+		// an explicit container dimension overrides the size of its content
+		if d.width > 0 {
+			d.width
+		} else {
+			content.width + p.left + p.right
+		},
+		if d.height > 0 {
+			d.height
+		} else {
+			content.height + p.top + p.bottom
+		},
+	)
+}
+
+fn stack_content_size(stack: &Stack) -> Size {
+	let axis = stack.axis;
+	let mut main = 0;
+	let mut cross = 0;
+	for (i, item) in stack.items.iter().enumerate() {
+		if i > 0 {
+			main += stack.spacing;
+		}
+		let outer = outer_size(item);
+		main += axis.main(outer);
+		cross = cmp::max(cross, axis.cross(outer));
+	}
+	axis.size(main, cross)
+}
+
+// size the control takes in its parent, margins included
+fn outer_size(control: &Control) -> Size {
+	let d = dimension_of(control);
+	let content = content_size(control);
+	let m = margin_size(margin_of(control));
+	Size::new(
+		d.clamp_width(content.width) + m.width,
+		d.clamp_height(content.height) + m.height,
+	)
+}
+
+// split `total` between `weights`, the parts always sum to `total`
+fn distribute(total: i32, weights: &[f32]) -> Vec<i32> {
+	let sum: f32 = weights.iter().sum();
+	let mut acc = 0.0;
+	let mut prev = 0;
+	weights
+		.iter()
+		.map(|w| {
+			acc += total as f32 * w / sum;
+			let next = acc.round() as i32;
+			let part = next - prev;
+			prev = next;
+			part
+		})
+		.collect()
+}
+
+fn place(control: &Control, outer: Rect) -> LayoutTree {
+	let m = margin_of(control);
+	let frame = Rect::new(
+		outer.x + m.left,
+		outer.y + m.top,
+		outer.width - m.left - m.right,
+		outer.height - m.top - m.bottom,
+	);
+	if let Control::Grid(grid) = control {
+		return place_grid(grid, frame);
+	}
+	let stack = match stack_of(control) {
+		Some(stack) => stack,
+		None => return LayoutTree::leaf(frame),
+	};
+	let axis = stack.axis;
+
+	let p = stack.padding;
+	let inner = Size::new(
+		frame.width - p.left - p.right,
+		frame.height - p.top - p.bottom,
+	);
+	let items = stack.items;
+	let bases: Vec<Size> = items.iter().map(outer_size).collect();
+	let dimensions: Vec<Dimension> = items.iter().map(dimension_of).collect();
+
+	let spacing = stack.spacing * cmp::max(items.len() as i32 - 1, 0);
+	let grow: Vec<f32> = dimensions.iter().map(|d| d.grow.max(0.0)).collect();
+	let shrink: Vec<f32> = dimensions
+		.iter()
+		.zip(&bases)
+		.map(|(d, b)| d.shrink.max(0.0) * axis.main(*b) as f32)
+		.collect();
+	// main size of an item kept within its min and max, margins included
+	let clamp_main = |i: usize, main: i32| {
+		let m = axis.main(margin_size(margin_of(&items[i])));
+		let d = dimensions[i];
+		let clamped = match axis {
+			Axis::Horizontal => d.clamp_width(main - m),
+			Axis::Vertical => d.clamp_height(main - m),
+		};
+		cmp::max(clamped, 0) + m
+	};
+
+	// free space is split between the items that grow or shrink, those reaching their min or max are frozen there and
+	// the split is done again between the others, with what the frozen items couldn't take or give
+	let mut mains: Vec<i32> = bases.iter().map(|b| axis.main(*b)).collect();
+	let mut frozen = vec![false; items.len()];
+	loop {
+		let used: i32 = (0..items.len())
+			.map(|i| match frozen[i] {
+				true => mains[i],
+				false => axis.main(bases[i]),
+			})
+			.sum();
+		let free = axis.main(inner) - used - spacing;
+		let weights: Vec<f32> = (0..items.len())
+			.map(|i| match (frozen[i], free > 0) {
+				(true, _) => 0.0,
+				(false, true) => grow[i],
+				(false, false) => shrink[i],
+			})
+			.collect();
+		if free == 0 || weights.iter().sum::<f32>() <= 0.0 {
+			break;
+		}
+
+		let deltas = distribute(free, &weights);
+		let mut done = true;
+		for i in 0..items.len() {
+			if frozen[i] {
+				continue;
+			}
+			let main = axis.main(bases[i]) + deltas[i];
+			mains[i] = clamp_main(i, main);
+			if mains[i] != main {
+				frozen[i] = true;
+				done = false;
+			}
+		}
+		if done {
+			break;
+		}
+		// the others start again from their base size
+		for i in (0..items.len()).filter(|i| !frozen[*i]) {
+			mains[i] = axis.main(bases[i]);
+		}
+	}
+
+	let sizes: Vec<Size> = items
+		.iter()
+		.enumerate()
+		.map(|(i, item)| {
+			let d = dimensions[i];
+			let m = margin_size(margin_of(item));
+			let main = mains[i];
+			let cross = if d.fill || stack.align == Align::Stretch {
+				axis.cross(inner)
+			} else {
+				axis.cross(bases[i])
+			};
+			let wanted = axis.size(main, cross);
+			Size::new(
+				cmp::max(d.clamp_width(wanted.width - m.width), 0) + m.width,
+				cmp::max(d.clamp_height(wanted.height - m.height), 0) + m.height,
+			)
+		})
+		.collect();
+
+	// free space left once items are sized goes to the slots before, between and after items
+	let remaining = axis.main(inner) - sizes.iter().map(|s| axis.main(*s)).sum::<i32>() - spacing;
+	let gaps = justify_gaps(stack.justify, remaining, items.len());
+
+	let mut cursor = gaps[0];
+	let mut children = Vec::with_capacity(items.len());
+	for (i, item) in items.iter().enumerate() {
+		if i > 0 {
+			cursor += stack.spacing + gaps[i];
+		}
+		let size = sizes[i];
+		let cross = match stack.align {
+			Align::Center => (axis.cross(inner) - axis.cross(size)) / 2,
+			Align::End => axis.cross(inner) - axis.cross(size),
+			Align::Start | Align::Stretch | Align::Baseline => 0,
+		};
+		let offset = axis.size(cursor, cross);
+		children.push(place(
+			item,
+			Rect::new(
+				frame.x + p.left + offset.width,
+				frame.y + p.top + offset.height,
+				size.width,
+				size.height,
+			),
+		));
+		cursor += axis.main(size);
+	}
+
+	if let (Align::Baseline, Axis::Horizontal) = (stack.align, axis) {
+		let baseline = children.iter().map(LayoutTree::baseline).max();
+		if let Some(baseline) = baseline {
+			for child in &mut children {
+				let dy = baseline - child.baseline();
+				child.translate(0, dy);
+			}
+		}
+	}
+
+	LayoutTree { frame, children }
+}
+
+// free space put before each of the `n` items, the last entry being the space after the last item
+fn justify_gaps(justify: Justify, remaining: i32, n: usize) -> Vec<i32> {
+	if remaining <= 0 || n == 0 {
+		return vec![0; n + 1];
+	}
+	let weights: Vec<f32> = (0..=n)
+		.map(|slot| {
+			let edge = slot == 0 || slot == n;
+			match justify {
+				Justify::Start => weight(slot == n),
+				Justify::Center => weight(edge),
+				Justify::End => weight(slot == 0),
+				Justify::SpaceBetween if n == 1 => weight(slot == n),
+				Justify::SpaceBetween => weight(!edge),
+				Justify::SpaceAround if edge => 0.5,
+				Justify::SpaceAround => 1.0,
+				Justify::SpaceEvenly => 1.0,
+			}
+		})
+		.collect();
+	distribute(remaining, &weights)
+}
+
+fn weight(slot_gets_space: bool) -> f32 {
+	if slot_gets_space {
+		1.0
+	} else {
+		0.0
+	}
+}
+
+// columns for the horizontal axis, rows for the vertical one. Fraction tracks are sized like Auto ones when there is no
+// `available` space to share.
+fn grid_tracks(grid: &Grid, axis: Axis, available: Option<i32>) -> Vec<i32> {
+	let (declared, gap) = match axis {
+		Axis::Horizontal => (&grid.columns, grid.column_gap),
+		Axis::Vertical => (&grid.rows, grid.row_gap),
+	};
+	let count = grid
+		.cells
+		.iter()
+		.map(|cell| {
+			let (start, span) = cell_span(cell, axis);
+			start + span
+		})
+		.chain(iter::once(declared.len()))
+		.max()
+		.unwrap_or(0);
+	let tracks: Vec<Track> = (0..count)
+		.map(|i| declared.get(i).copied().unwrap_or(Track::Auto))
+		.collect();
+
+	let mut sizes: Vec<i32> = tracks
+		.iter()
+		.enumerate()
+		.map(|(i, track)| match track {
+			Track::Fixed(v) => *v,
+			Track::Auto | Track::Fraction(_) => grid
+				.cells
+				.iter()
+				.filter(|cell| cell_span(cell, axis) == (i, 1))
+				.map(|cell| axis.main(outer_size(&cell.control)))
+				.max()
+				.unwrap_or(0),
+		})
+		.collect();
+
+	let fractions: Vec<f32> = tracks
+		.iter()
+		.map(|track| match track {
+			Track::Fraction(f) => f.max(0.0),
+			_ => 0.0,
+		})
+		.collect();
+	if let Some(available) = available {
+		if fractions.iter().sum::<f32>() > 0.0 {
+			let used: i32 = tracks
+				.iter()
+				.zip(&sizes)
+				.filter(|(track, _)| !matches!(track, Track::Fraction(_)))
+				.map(|(_, size)| size)
+				.sum::<i32>()
+				+ gap * cmp::max(count as i32 - 1, 0);
+			let shares = distribute(cmp::max(available - used, 0), &fractions);
+			for ((size, share), track) in sizes.iter_mut().zip(shares).zip(&tracks) {
+				if let Track::Fraction(_) = track {
+					*size = share;
+				}
+			}
+		}
+	}
+	sizes
+}
+
+fn cell_span(cell: &GridCell, axis: Axis) -> (usize, usize) {
+	match axis {
+		Axis::Horizontal => (cell.column, cmp::max(cell.column_span, 1)),
+		Axis::Vertical => (cell.row, cmp::max(cell.row_span, 1)),
+	}
+}
+
+fn tracks_size(sizes: &[i32], gap: i32) -> i32 {
+	sizes.iter().sum::<i32>() + gap * cmp::max(sizes.len() as i32 - 1, 0)
+}
+
+// offset of the first track of the span, and size of the span with the gaps inside it
+fn track_span(sizes: &[i32], gap: i32, (start, span): (usize, usize)) -> (i32, i32) {
+	let offset = sizes[..start].iter().map(|size| size + gap).sum();
+	(offset, tracks_size(&sizes[start..start + span], gap))
+}
+
+fn align_in(align: Align, start: i32, available: i32, size: i32) -> (i32, i32) {
+	match align {
+		Align::Start | Align::Baseline => (start, size),
+		Align::Center => (start + (available - size) / 2, size),
+		Align::End => (start + available - size, size),
+		Align::Stretch => (start, available),
+	}
+}
+
+fn place_grid(grid: &Grid, frame: Rect) -> LayoutTree {
+	let p = grid.padding;
+	let inner = Rect::new(
+		frame.x + p.left,
+		frame.y + p.top,
+		frame.width - p.left - p.right,
+		frame.height - p.top - p.bottom,
+	);
+	let columns = grid_tracks(grid, Axis::Horizontal, Some(inner.width));
+	let rows = grid_tracks(grid, Axis::Vertical, Some(inner.height));
+
+	let children = grid
+		.cells
+		.iter()
+		.map(|cell| {
+			let (x, width) =
+				track_span(&columns, grid.column_gap, cell_span(cell, Axis::Horizontal));
+			let (y, height) = track_span(&rows, grid.row_gap, cell_span(cell, Axis::Vertical));
+
+			let d = dimension_of(&cell.control);
+			let m = margin_size(margin_of(&cell.control));
+			let wanted = outer_size(&cell.control);
+			let (x, width) = align_in(cell.h_align, inner.x + x, width, wanted.width);
+			let (y, height) = align_in(cell.v_align, inner.y + y, height, wanted.height);
+			place(
+				&cell.control,
+				Rect::new(
+					x,
+					y,
+					cmp::max(d.clamp_width(width - m.width), 0) + m.width,
+					cmp::max(d.clamp_height(height - m.height), 0) + m.height,
+				),
+			)
+		})
+		.collect();
+
+	LayoutTree { frame, children }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::layout::{Button, DimensionBuilder, GroupBox, HStack, VStack};
+
+	fn frames(root: &Control, width: i32, height: i32) -> Vec<Rect> {
+		let tree = measure(root, Size::new(width, height));
+		tree.children.iter().map(|child| child.frame).collect()
+	}
+
+	fn button(width: i32, height: i32) -> Button {
+		Button::new("").width(width).height(height)
+	}
+
+	fn stretch(row: usize, column: usize) -> GridCell {
+		GridCell::new(row, column, GroupBox::new("").done())
+			.h_align(Align::Stretch)
+			.v_align(Align::Stretch)
+	}
+
+	#[test]
+	fn grow() {
+		let root = HStack::new()
+			.spacing(10)
+			.items(vec![
+				button(50, 20).grow(1.0).done(),
+				button(50, 20).grow(3.0).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 300, 100),
+			[Rect::new(0, 0, 98, 20), Rect::new(108, 0, 192, 20)]
+		);
+	}
+
+	#[test]
+	fn shrink() {
+		// items give up space relative to their size, those without shrink keep theirs
+		let root = HStack::new()
+			.items(vec![
+				button(100, 20).shrink(1.0).done(),
+				button(50, 20).done(),
+				button(50, 20).shrink(1.0).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 100, 20),
+			[
+				Rect::new(0, 0, 33, 20),
+				Rect::new(33, 0, 50, 20),
+				Rect::new(83, 0, 17, 20)
+			]
+		);
+	}
+
+	#[test]
+	fn min_max() {
+		let root = HStack::new()
+			.items(vec![
+				button(50, 20).grow(1.0).max_width(100).done(),
+				button(50, 20).grow(1.0).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 300, 100),
+			[Rect::new(0, 0, 100, 20), Rect::new(100, 0, 200, 20)]
+		);
+
+		let root = HStack::new()
+			.items(vec![
+				button(100, 20).shrink(1.0).min_width(80).done(),
+				button(100, 20).shrink(1.0).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 100, 20),
+			[Rect::new(0, 0, 80, 20), Rect::new(80, 0, 20, 20)]
+		);
+
+		// what the capped item can't take is split again between the others
+		let root = HStack::new()
+			.items(vec![
+				button(0, 20).grow(1.0).max_width(50).done(),
+				button(0, 20).grow(1.0).done(),
+				button(0, 20).grow(3.0).max_width(150).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 300, 20),
+			[
+				Rect::new(0, 0, 50, 20),
+				Rect::new(50, 0, 100, 20),
+				Rect::new(150, 0, 150, 20)
+			]
+		);
+
+		let root = VStack::new()
+			.items(vec![
+				button(0, 20).fill().max_width(120).done(),
+				button(10, 20).min_width(40).done(),
+				button(10, 20).min_height(30).max_height(25).done(),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 200, 200),
+			[
+				Rect::new(0, 0, 120, 20),
+				Rect::new(0, 20, 40, 20),
+				Rect::new(0, 40, 10, 30)
+			]
+		);
+	}
+
+	#[test]
+	fn justify() {
+		let cases = [
+			(Justify::Start, [0, 20]),
+			(Justify::Center, [30, 50]),
+			(Justify::End, [60, 80]),
+			(Justify::SpaceBetween, [0, 80]),
+			(Justify::SpaceAround, [15, 65]),
+			(Justify::SpaceEvenly, [20, 60]),
+		];
+		for (justify, [first, second]) in cases {
+			let root = HStack::new()
+				.justify(justify)
+				.items(vec![button(20, 10).done(), button(20, 10).done()])
+				.done();
+			assert_eq!(
+				frames(&root, 100, 10),
+				[Rect::new(first, 0, 20, 10), Rect::new(second, 0, 20, 10)],
+				"{:?}",
+				justify
+			);
+		}
+	}
+
+	#[test]
+	fn align() {
+		let cases = [
+			(Align::Start, Rect::new(0, 0, 20, 10)),
+			(Align::Center, Rect::new(40, 0, 20, 10)),
+			(Align::End, Rect::new(80, 0, 20, 10)),
+			(Align::Stretch, Rect::new(0, 0, 100, 10)),
+			// no baseline across a VStack
+			(Align::Baseline, Rect::new(0, 0, 20, 10)),
+		];
+		for (align, frame) in cases {
+			let root = VStack::new()
+				.align(align)
+				.items(vec![button(20, 10).done()])
+				.done();
+			assert_eq!(frames(&root, 100, 100), [frame], "{:?}", align);
+		}
+
+		// centers of both buttons on the same line
+		let root = HStack::new()
+			.align(Align::Baseline)
+			.items(vec![button(20, 10).done(), button(20, 30).done()])
+			.done();
+		assert_eq!(
+			frames(&root, 100, 100),
+			[Rect::new(0, 10, 20, 10), Rect::new(20, 0, 20, 30)]
+		);
+	}
+
+	#[test]
+	fn grid_spans() {
+		let root = Grid::new()
+			.columns(vec![Track::Fixed(50), Track::Fixed(30)])
+			.rows(vec![Track::Fixed(20), Track::Fixed(40), Track::Fixed(10)])
+			.column_gap(5)
+			.row_gap(10)
+			.cells(vec![
+				stretch(0, 0).column_span(2),
+				stretch(1, 0).row_span(2),
+				GridCell::new(1, 1, button(10, 10).done())
+					.h_align(Align::Center)
+					.v_align(Align::End),
+				GridCell::new(2, 1, button(10, 10).done()),
+			])
+			.done();
+		assert_eq!(
+			frames(&root, 200, 200),
+			[
+				Rect::new(0, 0, 85, 20),
+				Rect::new(0, 30, 50, 60),
+				Rect::new(65, 60, 10, 10),
+				Rect::new(55, 80, 10, 10)
+			]
+		);
+	}
+
+	#[test]
+	fn grid_fractions() {
+		let root = Grid::new()
+			.columns(vec![
+				Track::Fixed(50),
+				Track::Fraction(1.0),
+				Track::Fraction(3.0),
+			])
+			.rows(vec![Track::Fixed(10)])
+			.cells(vec![stretch(0, 0), stretch(0, 1), stretch(0, 2)])
+			.done();
+		assert_eq!(
+			frames(&root, 250, 10),
+			[
+				Rect::new(0, 0, 50, 10),
+				Rect::new(50, 0, 50, 10),
+				Rect::new(100, 0, 150, 10)
+			]
+		);
+	}
+}
@@ -1,6 +1,12 @@
+mod binding;
 mod controls;
+mod file;
 mod margins;
+mod measure;
 mod screen;
+pub use crate::layout::binding::*;
 pub use crate::layout::controls::*;
+pub use crate::layout::file::*;
 pub use crate::layout::margins::*;
+pub use crate::layout::measure::*;
 pub use crate::layout::screen::*;
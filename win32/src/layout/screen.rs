@@ -1,25 +1,85 @@
-use std::cmp;
+use std::{
+	any::Any,
+	collections::{HashMap, HashSet},
+	fmt,
+};
 
-use super::Control;
-use crate::{assert::Result, button, input::create_text_input};
-use windows::Win32::Foundation::{HINSTANCE, HWND};
+use super::{
+	diff, measure, ChangeHandler, ClickHandler, Control, Diff, LayoutTree, Patch, Rect,
+	SelectHandler, SelectionHandler, Size, ToggleHandler, AUTO_ID_START,
+};
+use crate::{
+	assert::{assert_eq, assert_ne, Result, WithLastWin32Error},
+	button::{ButtonBuilder, ButtonControl, CheckBox, CheckState, RadioGroup},
+	combo_box::{self, ComboBoxBuilder, ComboKind},
+	hiword,
+	input::{self, create_text_input},
+	list_box::{self, ListBoxBuilder, SelectionMode},
+	loword,
+	menu::MENU_ID_START,
+	wide_string::ToWide,
+	window::{self, get_window_text},
+	window_long::set_window_long,
+};
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, RECT, WPARAM},
+	UI::{
+		Input::KeyboardAndMouse::EnableWindow,
+		WindowsAndMessaging::{
+			BeginDeferWindowPos, DeferWindowPos, DestroyWindow, EndDeferWindowPos, GetClientRect,
+			SetWindowTextW, BN_CLICKED, GWL_ID, SWP_NOACTIVATE, SWP_NOZORDER,
+		},
+	},
+};
 
-#[derive(Default)]
-struct Offset {
-	left: i32,
-	top: i32,
+/// Callback of a control, ready to be called for a `WM_COMMAND` notification.
+pub enum Command {
+	Click(ClickHandler),
+	Change(ChangeHandler, String),
+	Toggle(ToggleHandler, CheckState),
+	Select(SelectHandler, usize),
+	Selection(SelectionHandler, Vec<usize>),
 }
 
-#[derive(Default)]
-struct Rect {
-	w: i32,
-	h: i32,
+impl Command {
+	pub fn invoke(&self, state: &mut dyn Any) -> Result<()> {
+		match self {
+			Command::Click(f) => f(state),
+			Command::Change(f, text) => f(state, text),
+			Command::Toggle(f, check) => f(state, *check),
+			Command::Select(f, index) => f(state, *index),
+			Command::Selection(f, selection) => f(state, selection),
+		}
+	}
+}
+
+enum Handler {
+	Click(ClickHandler),
+	Change(ChangeHandler),
+	Toggle(ToggleHandler),
+	Select(SelectHandler),
+	ComboSelect(SelectHandler),
+	ListSelect(SelectionHandler),
 }
 
+/// Owns a `Control` tree rendered as child windows of `h_window`, and keeps them in place when the window is resized.
 pub struct Screen {
 	h_instance: HINSTANCE,
 	h_window: HWND,
-	offset: Offset,
+	root: Control,
+	// windows created for the leaves of `root`, in tree order, a radio group has one per option
+	controls: Vec<Vec<HWND>>,
+	// callbacks by control id
+	handlers: HashMap<u16, Handler>,
+}
+
+impl fmt::Debug for Screen {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Screen")
+			.field("h_window", &self.h_window)
+			.field("controls", &self.controls)
+			.finish_non_exhaustive()
+	}
 }
 
 impl Screen {
@@ -27,114 +87,515 @@ impl Screen {
 		Self {
 			h_instance,
 			h_window,
-			offset: Default::default(),
+			root: Control::None,
+			controls: vec![],
+			handlers: HashMap::new(),
 		}
 	}
 
 	pub fn render(&mut self, root: Control) -> Result<()> {
-		let _rect = self.render_recurse(root)?;
+		let layout = measure(&root, self.client_size()?);
+		// fails on invalid ids, before anything is destroyed
+		let handlers = handlers_of(&root)?;
+		// controls from a previous render are replaced
+		for control in self.controls.drain(..).flatten() {
+			unsafe { DestroyWindow(control) };
+		}
+		self.handlers = handlers;
+		self.root = root;
+
+		let mut controls = vec![];
+		self.render_recurse(&self.root, &layout, &mut controls)?;
+		self.controls = controls;
 		Ok(())
 	}
 
-	fn render_recurse(&mut self, root: Control) -> Result<Rect> {
-		match root {
-			Control::None => Ok(Default::default()),
-			Control::HStack(stack) => {
-				let init_x = self.offset.left;
-				let init_y = self.offset.top;
-
-				self.offset.left += stack.padding.left;
-				self.offset.top += stack.padding.top;
-
-				let mut w = 0;
-				let mut h = 0;
-				for item in stack.items {
-					let rect = self.render_recurse(item)?;
-					// height of hstack should match the largest rendered control width
-					h = cmp::max(h, rect.h);
-					// width of hstack increased based on size of rendered control + spacing
-					w += rect.w + stack.spacing;
-
-					// global horizontal offset should now match initial horizontal position (x) + current vstack width (w)
-					self.offset.left = init_x + w;
+	/// Bring the rendered controls in line with `root`, typically built again from updated state. Only the windows of
+	/// controls that changed are touched, unless the structure of the tree changed.
+	pub fn update(&mut self, root: Control) -> Result<()> {
+		let (patches, relayout) = match diff(&self.root, &root) {
+			Diff::Rebuild => return self.render(root),
+			Diff::Update { patches, relayout } => (patches, relayout),
+		};
+		// callbacks are new closures, ids stay the same
+		let handlers = handlers_of(&root)?;
+		let mut leaves = vec![];
+		collect_leaves(&self.root, &mut leaves);
+		for patch in patches {
+			self.apply(patch, &leaves)?;
+		}
+		self.handlers = handlers;
+		self.root = root;
+		if relayout {
+			self.relayout()?;
+		}
+		Ok(())
+	}
+
+	// `leaves` of the tree the windows were rendered from
+	fn apply(&self, patch: Patch, leaves: &[&Control]) -> Result<()> {
+		match patch {
+			Patch::SetText { index, text } => {
+				let control = self.controls[index][0];
+				// setting the same text would move the caret of an input being edited
+				if get_window_text(control)? != text {
+					assert_eq(
+						unsafe { SetWindowTextW(control, text.to_wide().as_pwstr()) }.as_bool(),
+						true,
+						"failed to set control text",
+					)
+					.with_last_win32_err()?;
+				}
+			}
+			Patch::SetEnabled { index, enabled } => {
+				for control in &self.controls[index] {
+					unsafe { EnableWindow(*control, enabled) };
+				}
+			}
+			Patch::SetCheck { index, state } => {
+				CheckBox::from_raw(self.controls[index][0]).set_state(state);
+			}
+			Patch::SetSelected { index, selected } => match leaves[index] {
+				Control::ComboBox(_) => {
+					combo_box::ComboBox::from_raw(self.controls[index][0]).select(selected)?;
 				}
+				_ => RadioGroup::from_raw(self.controls[index].clone()).select(selected),
+			},
+			Patch::SetSelection { index, selection } => {
+				list_box::ListBox::from_raw(self.controls[index][0]).set_selection(&selection)?;
+			}
+			Patch::SetOptions { index, options } => {
+				let control = self.controls[index][0];
+				match leaves[index] {
+					Control::ComboBox(_) => {
+						combo_box::ComboBox::from_raw(control).set_items(&options)?
+					}
+					_ => list_box::ListBox::from_raw(control).set_items(&options)?,
+				}
+			}
+		}
+		Ok(())
+	}
 
-				// remove last spacing
-				w -= stack.spacing;
+	/// Callback registered for a `WM_COMMAND` notification sent by one of the controls, if any.
+	pub fn command(&self, wparam: WPARAM, lparam: LPARAM) -> Result<Option<Command>> {
+		let id: u16 = loword(wparam).try_into().expect("loword is 16 bits");
+		let notification: u32 = hiword(wparam).try_into().expect("hiword is 16 bits");
+		let command = match self.handlers.get(&id) {
+			Some(Handler::Click(f)) if notification == BN_CLICKED => {
+				Some(Command::Click(f.clone()))
+			}
+			Some(Handler::Change(f)) if notification == input::event::Change => {
+				// lparam is the handle of the control sending the notification
+				Some(Command::Change(f.clone(), get_window_text(lparam)?))
+			}
+			Some(Handler::Toggle(f)) if notification == BN_CLICKED => {
+				let state = CheckBox::from_raw(lparam).state();
+				Some(Command::Toggle(f.clone(), state))
+			}
+			Some(Handler::Select(f)) if notification == BN_CLICKED => {
+				// the buttons of a group share its id
+				let index = self
+					.controls
+					.iter()
+					.find_map(|windows| windows.iter().position(|w| *w == lparam));
+				index.map(|index| Command::Select(f.clone(), index))
+			}
+			Some(Handler::ComboSelect(f)) if notification == combo_box::event::SelChange => {
+				let selected = combo_box::ComboBox::from_raw(lparam).selected();
+				selected.map(|index| Command::Select(f.clone(), index))
+			}
+			Some(Handler::ListSelect(f)) if notification == list_box::event::SelChange => {
+				let selection = list_box::ListBox::from_raw(lparam).selection()?;
+				Some(Command::Selection(f.clone(), selection))
+			}
+			_ => None,
+		};
+		Ok(command)
+	}
 
-				// add padding to width and height
-				w += stack.padding.left + stack.padding.right;
-				h += stack.padding.top + stack.padding.bottom;
+	/// Recompute the layout against the current client area and move every control at once.
+	pub fn relayout(&self) -> Result<()> {
+		if self.controls.is_empty() {
+			return Ok(());
+		}
+
+		let layout = measure(&self.root, self.client_size()?);
+		let mut frames = Vec::with_capacity(self.controls.len());
+		leaf_frames(&self.root, &layout, &mut frames);
+		let mut leaves = Vec::with_capacity(self.controls.len());
+		collect_leaves(&self.root, &mut leaves);
 
-				// reset global offset
-				self.offset.left = init_x;
-				self.offset.top = init_y;
+		let count = self.controls.iter().map(Vec::len).sum::<usize>();
+		let count: i32 = count.try_into().expect("too many controls");
+		let mut hdwp = unsafe { BeginDeferWindowPos(count) };
+		assert_ne(hdwp, 0, "failed to begin deferred window positioning").with_last_win32_err()?;
+		let windows = self
+			.controls
+			.iter()
+			.zip(leaves)
+			.zip(frames)
+			.flat_map(|((windows, leaf), frame)| windows.iter().zip(window_frames(leaf, frame)));
+		for (control, frame) in windows {
+			hdwp = unsafe {
+				DeferWindowPos(
+					hdwp,
+					*control,
+					None,
+					frame.x,
+					frame.y,
+					frame.width,
+					frame.height,
+					SWP_NOZORDER | SWP_NOACTIVATE,
+				)
+			};
+			assert_ne(hdwp, 0, "failed to defer window position").with_last_win32_err()?;
+		}
+		assert_eq(
+			unsafe { EndDeferWindowPos(hdwp) }.as_bool(),
+			true,
+			"failed to end deferred window positioning",
+		)
+		.with_last_win32_err()?;
+		Ok(())
+	}
 
-				Ok(Rect { w, h })
+	/// Size of the client area of the parent window.
+	pub fn client_size(&self) -> Result<Size> {
+		let mut rect: RECT = Default::default();
+		assert_eq(
+			unsafe { GetClientRect(self.h_window, &mut rect as *mut _) }.as_bool(),
+			true,
+			"failed to get client rect",
+		)
+		.with_last_win32_err()?;
+		Ok(Size::new(rect.right - rect.left, rect.bottom - rect.top))
+	}
+
+	fn render_recurse(
+		&self,
+		control: &Control,
+		layout: &LayoutTree,
+		controls: &mut Vec<Vec<HWND>>,
+	) -> Result<()> {
+		let frame = layout.frame;
+		match control {
+			Control::None => {}
+			Control::HStack(stack) => {
+				for (item, child) in stack.items.iter().zip(&layout.children) {
+					self.render_recurse(item, child, controls)?;
+				}
 			}
 			Control::VStack(stack) => {
-				let init_x = self.offset.left;
-				let init_y = self.offset.top;
-
-				self.offset.left += stack.padding.left;
-				self.offset.top += stack.padding.top;
-
-				let mut w = 0;
-				let mut h = 0;
-				for item in stack.items {
-					let rect = self.render_recurse(item)?;
-					// width of vstack should match the largest rendered control width
-					w = cmp::max(w, rect.w);
-					// height of vstack increased based on size of rendered control + spacing
-					h += rect.h + stack.spacing;
-
-					// global vertical offset should now match initial vertical position (y) + current vstack height (h)
-					self.offset.top = init_y + h;
+				for (item, child) in stack.items.iter().zip(&layout.children) {
+					self.render_recurse(item, child, controls)?;
+				}
+			}
+			Control::Grid(grid) => {
+				for (cell, child) in grid.cells.iter().zip(&layout.children) {
+					self.render_recurse(&cell.control, child, controls)?;
 				}
-
-				// remove last spacing
-				h -= stack.spacing;
-
-				// add padding to width and height
-				w += stack.padding.left + stack.padding.right;
-				h += stack.padding.top + stack.padding.bottom;
-
-				// reset global offset
-				self.offset.left = init_x;
-				self.offset.top = init_y;
-
-				Ok(Rect { w, h })
 			}
 			Control::Button(button) => {
-				button::create(
-					self.h_window,
-					self.h_instance,
-					button.title.as_str(),
-					self.offset.left + button.margin.left,
-					self.offset.top + button.margin.top,
-					button.dimension.width,
-					button.dimension.height,
+				let control = self
+					.button_builder(&button.title, frame)
+					.create_push_button(button.default_button)?;
+				init_control(
+					control.h_window(),
+					control_id(button.id, controls.len())?,
+					button.disabled,
 				)?;
-				Ok(Rect {
-					w: button.dimension.width + button.margin.left + button.margin.right,
-					h: button.dimension.height + button.margin.top + button.margin.bottom,
-				})
+				controls.push(vec![control.h_window()]);
 			}
 			Control::InputText(input) => {
-				create_text_input(
+				let control = create_text_input(
 					self.h_window,
 					self.h_instance,
 					input.text.as_str(),
-					self.offset.left + input.margin.left,
-					self.offset.top + input.margin.top,
-					input.dimension.width,
-					input.dimension.height,
+					frame.x,
+					frame.y,
+					frame.width,
+					frame.height,
+				)?;
+				init_control(
+					control,
+					control_id(input.id, controls.len())?,
+					input.disabled,
 				)?;
-				Ok(Rect {
-					w: input.dimension.width + input.margin.left + input.margin.right,
-					h: input.dimension.height + input.margin.top + input.margin.bottom,
-				})
+				controls.push(vec![control]);
 			}
+			Control::CheckBox(check) => {
+				let control = self
+					.button_builder(&check.title, frame)
+					.create_check_box(check.tri_state)?;
+				control.set_state(check.state);
+				init_control(
+					control.h_window(),
+					control_id(check.id, controls.len())?,
+					check.disabled,
+				)?;
+				controls.push(vec![control.h_window()]);
+			}
+			Control::RadioGroup(radio) => {
+				let frames = window_frames(control, frame);
+				let builders = radio
+					.options
+					.iter()
+					.zip(frames)
+					.map(|(option, frame)| self.button_builder(option, frame))
+					.collect();
+				let group = RadioGroup::create(builders)?;
+				group.select(radio.selected);
+				let id = control_id(radio.id, controls.len())?;
+				for control in group.buttons() {
+					init_control(*control, id, radio.disabled)?;
+				}
+				controls.push(group.buttons().to_vec());
+			}
+			Control::GroupBox(group) => {
+				let control = self
+					.button_builder(&group.title, frame)
+					.create_group_box()?;
+				init_control(
+					control.h_window(),
+					control_id(group.id, controls.len())?,
+					group.disabled,
+				)?;
+				controls.push(vec![control.h_window()]);
+			}
+			Control::ComboBox(combo) => {
+				let window = window_frames(control, frame)[0];
+				let kind = match combo.editable {
+					true => ComboKind::DropDown,
+					false => ComboKind::DropDownList,
+				};
+				let control = ComboBoxBuilder::new(self.h_window)
+					.h_instance(self.h_instance)
+					.kind(kind)
+					.at(window.x, window.y)
+					.size(window.width, window.height)
+					.create()?;
+				control.set_items(&combo.options)?;
+				control.select(combo.selected)?;
+				init_control(
+					control.h_window(),
+					control_id(combo.id, controls.len())?,
+					combo.disabled,
+				)?;
+				controls.push(vec![control.h_window()]);
+			}
+			Control::ListBox(list) => {
+				let mode = match list.multi_select {
+					true => SelectionMode::Extended,
+					false => SelectionMode::Single,
+				};
+				let control = ListBoxBuilder::new(self.h_window)
+					.h_instance(self.h_instance)
+					.selection_mode(mode)
+					.at(frame.x, frame.y)
+					.size(frame.width, frame.height)
+					.create()?;
+				control.set_items(&list.options)?;
+				control.set_selection(&list.selection)?;
+				init_control(
+					control.h_window(),
+					control_id(list.id, controls.len())?,
+					list.disabled,
+				)?;
+				controls.push(vec![control.h_window()]);
+			}
+		}
+		Ok(())
+	}
+
+	fn button_builder(&self, text: &str, frame: Rect) -> ButtonBuilder {
+		ButtonBuilder::new(self.h_window, text)
+			.h_instance(self.h_instance)
+			.at(frame.x, frame.y)
+			.size(frame.width, frame.height)
+	}
+}
+
+fn init_control(control: HWND, id: u16, disabled: bool) -> Result<()> {
+	set_window_long(control, GWL_ID, id.into())?;
+	if disabled {
+		unsafe { EnableWindow(control, false) };
+	}
+	window::set_default_font(control);
+	Ok(())
+}
+
+// height of the list dropped down from a combo box, its window includes it
+const COMBO_LIST_HEIGHT: i32 = 200;
+
+// frames of the windows of a leaf, options of a radio group are stacked in its frame
+fn window_frames(leaf: &Control, frame: Rect) -> Vec<Rect> {
+	let count = match leaf {
+		Control::RadioGroup(radio) => radio.options.len(),
+		Control::ComboBox(_) => {
+			let height = frame.height + COMBO_LIST_HEIGHT;
+			return vec![Rect::new(frame.x, frame.y, frame.width, height)];
+		}
+		_ => 1,
+	};
+	if count <= 1 {
+		return vec![frame];
+	}
+	let n = count as i32;
+	(0..n)
+		.map(|i| {
+			let top = frame.y + frame.height * i / n;
+			let bottom = frame.y + frame.height * (i + 1) / n;
+			Rect::new(frame.x, top, frame.width, bottom - top)
+		})
+		.collect()
+}
+
+// id of the leaf at `index`, in the order render_recurse creates them. Ids from MENU_ID_START on are menu commands.
+fn control_id(id: Option<u16>, index: usize) -> Result<u16> {
+	if let Some(id) = id {
+		return match id < MENU_ID_START {
+			true => Ok(id),
+			false => Err(format!("control id {:#X} is in the range of menu ids", id).into()),
+		};
+	}
+	u16::try_from(index)
+		.ok()
+		.and_then(|index| AUTO_ID_START.checked_add(index))
+		.filter(|id| *id < MENU_ID_START)
+		.ok_or_else(|| "too many controls".into())
+}
+
+fn handlers_of(root: &Control) -> Result<HashMap<u16, Handler>> {
+	let mut leaves = vec![];
+	collect_leaves(root, &mut leaves);
+	let mut ids = HashSet::new();
+	let mut handlers = HashMap::new();
+	for (index, leaf) in leaves.into_iter().enumerate() {
+		let (id, handler) = match leaf {
+			Control::Button(button) => (button.id, button.on_click.clone().map(Handler::Click)),
+			Control::InputText(input) => (input.id, input.on_change.clone().map(Handler::Change)),
+			Control::CheckBox(check) => (check.id, check.on_toggle.clone().map(Handler::Toggle)),
+			Control::RadioGroup(radio) => (radio.id, radio.on_select.clone().map(Handler::Select)),
+			Control::GroupBox(group) => (group.id, None),
+			Control::ComboBox(combo) => {
+				(combo.id, combo.on_select.clone().map(Handler::ComboSelect))
+			}
+			Control::ListBox(list) => (list.id, list.on_select.clone().map(Handler::ListSelect)),
+			_ => continue,
+		};
+		// an explicit id may also be the one given to another control
+		let id = control_id(id, index)?;
+		if !ids.insert(id) {
+			return Err(format!("control id {:#X} is used by more than one control", id).into());
+		}
+		if let Some(handler) = handler {
+			handlers.insert(id, handler);
+		}
+	}
+	Ok(handlers)
+}
+
+fn collect_leaves<'a>(control: &'a Control, leaves: &mut Vec<&'a Control>) {
+	match control {
+		Control::None => {}
+		Control::HStack(stack) => stack
+			.items
+			.iter()
+			.for_each(|item| collect_leaves(item, leaves)),
+		Control::VStack(stack) => stack
+			.items
+			.iter()
+			.for_each(|item| collect_leaves(item, leaves)),
+		Control::Grid(grid) => grid
+			.cells
+			.iter()
+			.for_each(|cell| collect_leaves(&cell.control, leaves)),
+		Control::Button(_)
+		| Control::InputText(_)
+		| Control::CheckBox(_)
+		| Control::RadioGroup(_)
+		| Control::GroupBox(_)
+		| Control::ComboBox(_)
+		| Control::ListBox(_) => leaves.push(control),
+	}
+}
+
+// frames of the nodes backed by a window, in the order render_recurse creates them
+fn leaf_frames(control: &Control, layout: &LayoutTree, frames: &mut Vec<Rect>) {
+	match control {
+		Control::None => {}
+		Control::HStack(stack) => {
+			for (item, child) in stack.items.iter().zip(&layout.children) {
+				leaf_frames(item, child, frames);
+			}
+		}
+		Control::VStack(stack) => {
+			for (item, child) in stack.items.iter().zip(&layout.children) {
+				leaf_frames(item, child, frames);
+			}
+		}
+		Control::Grid(grid) => {
+			for (cell, child) in grid.cells.iter().zip(&layout.children) {
+				leaf_frames(&cell.control, child, frames);
+			}
+		}
+		Control::Button(_)
+		| Control::InputText(_)
+		| Control::CheckBox(_)
+		| Control::RadioGroup(_)
+		| Control::GroupBox(_)
+		| Control::ComboBox(_)
+		| Control::ListBox(_) => frames.push(layout.frame),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::layout::{Button, VStack};
+
+	#[test]
+	fn control_ids() {
+		assert_eq!(control_id(Some(7), 3).unwrap(), 7);
+		assert_eq!(control_id(None, 0).unwrap(), AUTO_ID_START);
+		assert_eq!(control_id(None, 5).unwrap(), AUTO_ID_START + 5);
+
+		let last = usize::from(MENU_ID_START - AUTO_ID_START) - 1;
+		assert_eq!(control_id(None, last).unwrap(), MENU_ID_START - 1);
+		for index in [last + 1, usize::from(u16::MAX), usize::MAX] {
+			let e = control_id(None, index).unwrap_err();
+			assert_eq!(e.to_string(), "too many controls");
+		}
+
+		let e = control_id(Some(MENU_ID_START), 0).unwrap_err();
+		assert_eq!(
+			e.to_string(),
+			"control id 0x8000 is in the range of menu ids"
+		);
+
+		let unique = VStack::new()
+			.items(vec![Button::new("").id(7).done(), Button::new("").done()])
+			.done();
+		assert!(handlers_of(&unique).is_ok());
+		let cases = [
+			(Some(7), Some(7), 7),
+			// the second button gets AUTO_ID_START + 1
+			(Some(AUTO_ID_START + 1), None, AUTO_ID_START + 1),
+		];
+		for (first, second, id) in cases {
+			let mut first_button = Button::new("").on_click(|_: &mut ()| {});
+			first_button.id = first;
+			let mut second_button = Button::new("");
+			second_button.id = second;
+			let root = VStack::new()
+				.items(vec![first_button.done(), second_button.done()])
+				.done();
+			let e = handlers_of(&root).err().expect("duplicate id");
+			assert_eq!(
+				e.to_string(),
+				format!("control id {:#X} is used by more than one control", id)
+			);
 		}
 	}
 }
@@ -1,13 +1,22 @@
 use windows::Win32::{Foundation::PWSTR, UI::WindowsAndMessaging::CW_USEDEFAULT};
 
+// Control handles, e.g. `input::Edit`, `button::PushButton`, `combo_box::ComboBox` or `list_box::ListBox`, only wrap
+// the HWND of a child window. Child windows are destroyed along with their parent, dropping a handle leaves the control
+// in place.
+
+pub mod accelerator;
 pub mod assert;
 pub mod button;
 pub mod class;
 pub mod color;
+pub mod combo_box;
 pub mod cursor;
+pub mod dialog;
+pub mod flags;
 pub mod icon;
 pub mod input;
 pub mod layout;
+pub mod list_box;
 pub mod macros;
 pub mod menu;
 pub mod message_box;
@@ -17,6 +26,7 @@ pub mod wide_string;
 pub mod window;
 pub mod window_long;
 
+mod item_list;
 mod simple_app;
 pub use crate::simple_app::*;
 
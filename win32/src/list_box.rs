@@ -0,0 +1,399 @@
+// Handle of a LISTBOX control with typed access to its items and selection, single or multiple, and the builder creating
+// it.
+//
+// Item texts are UTF-16 in the control, searches ignore case as the control does.
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND},
+	UI::{
+		Input::KeyboardAndMouse::EnableWindow,
+		WindowsAndMessaging::{DestroyWindow, GWL_STYLE},
+	},
+};
+
+use crate::{
+	assert::Result,
+	item_list::{frame_setters, item_index, ChildFrame, ItemList},
+	loword,
+	wide_string::ToWide,
+	window,
+	window_long::get_window_long_ptr,
+};
+
+const LIST_BOX_CLASS: &str = "LISTBOX";
+
+/// How many items the user can select.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+	Single,
+	/// Each click toggles the selection of an item.
+	Multiple,
+	/// Shift and Ctrl extend the selection, as in file lists.
+	Extended,
+}
+
+/// Handle of a list box.
+#[derive(Debug)]
+pub struct ListBox {
+	h_window: HWND,
+}
+
+impl ListBox {
+	pub fn from_raw(h_window: HWND) -> Self {
+		Self { h_window }
+	}
+
+	pub fn h_window(&self) -> HWND {
+		self.h_window
+	}
+
+	pub fn style(&self) -> Result<style::Type> {
+		let bits = get_window_long_ptr(self.h_window, GWL_STYLE)?;
+		// control styles are the low word of the window style
+		Ok(style::Type(loword(bits as usize) as u32))
+	}
+
+	pub fn is_multi_select(&self) -> Result<bool> {
+		Ok(self
+			.style()?
+			.intersects(style::MultipleSel | style::ExtendedSel))
+	}
+
+	pub fn len(&self) -> usize {
+		self.index(message::GetCount, 0, 0).unwrap_or(0)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Append an item, or insert it in order with the `Sort` style. Returns its index.
+	pub fn add(&self, text: &str) -> Result<usize> {
+		let text = text.to_wide();
+		let index = self.send(message::AddString, 0, text.as_pwstr().0 as _);
+		item_index(index, "failed to add list box item")
+	}
+
+	/// Insert an item at `index`, the list isn't sorted even with the `Sort` style.
+	pub fn insert(&self, index: usize, text: &str) -> Result<usize> {
+		let text = text.to_wide();
+		let index = self.send(message::InsertString, index, text.as_pwstr().0 as _);
+		item_index(index, "failed to insert list box item")
+	}
+
+	pub fn remove(&self, index: usize) -> Result<()> {
+		let remaining = self.send(message::DeleteString, index, 0);
+		item_index(remaining, "failed to remove list box item").map(|_| ())
+	}
+
+	pub fn clear(&self) {
+		self.send(message::ResetContent, 0, 0);
+	}
+
+	/// Replace all the items, the selection is cleared.
+	pub fn set_items(&self, items: &[String]) -> Result<()> {
+		self.clear();
+		for item in items {
+			self.add(item)?;
+		}
+		Ok(())
+	}
+
+	pub fn item(&self, index: usize) -> Result<String> {
+		let len = self.send(message::GetTextLen, index, 0);
+		let len = item_index(len, &format!("no item {} in list box", index))?;
+		let mut buf = vec![0u16; len + 1];
+		let copied = self.send(message::GetText, index, buf.as_mut_ptr() as _);
+		let copied = item_index(copied, "failed to get list box item")?;
+		Ok(String::from_utf16_lossy(&buf[..copied]))
+	}
+
+	pub fn items(&self) -> Result<Vec<String>> {
+		(0..self.len()).map(|index| self.item(index)).collect()
+	}
+
+	/// Index of the first item equal to `text`.
+	pub fn find_exact(&self, text: &str) -> Option<usize> {
+		let text = text.to_wide();
+		// wparam: search the whole list
+		self.index(message::FindStringExact, usize::MAX, text.as_pwstr().0 as _)
+	}
+
+	/// Index of the first item starting with `prefix`.
+	pub fn find_prefix(&self, prefix: &str) -> Option<usize> {
+		let prefix = prefix.to_wide();
+		self.index(message::FindString, usize::MAX, prefix.as_pwstr().0 as _)
+	}
+
+	/// Index of the selected item of a single-selection list box. Use `selection` for the others.
+	pub fn selected(&self) -> Option<usize> {
+		self.index(message::GetCurSel, 0, 0)
+	}
+
+	pub fn selected_text(&self) -> Result<Option<String>> {
+		self.selected().map(|index| self.item(index)).transpose()
+	}
+
+	/// Select the item at `index` of a single-selection list box, `None` clears the selection. Notifications are only
+	/// sent for changes made by the user.
+	pub fn select(&self, index: Option<usize>) -> Result<()> {
+		match index {
+			Some(index) => {
+				let selected = self.send(message::SetCurSel, index, 0);
+				item_index(selected, &format!("no item {} in list box", index)).map(|_| ())
+			}
+			None => {
+				// returns LB_ERR when clearing
+				self.send(message::SetCurSel, usize::MAX, 0);
+				Ok(())
+			}
+		}
+	}
+
+	/// Indexes of the selected items, in order, for any kind of list box.
+	pub fn selection(&self) -> Result<Vec<usize>> {
+		if !self.is_multi_select()? {
+			return Ok(self.selected().into_iter().collect());
+		}
+		let count = self.index(message::GetSelCount, 0, 0).unwrap_or(0);
+		if count == 0 {
+			return Ok(vec![]);
+		}
+		let mut buf = vec![0i32; count];
+		let copied = self.send(message::GetSelItems, count, buf.as_mut_ptr() as _);
+		let copied = item_index(copied, "failed to get list box selection")?;
+		Ok(buf[..copied].iter().map(|index| *index as usize).collect())
+	}
+
+	pub fn selected_items(&self) -> Result<Vec<String>> {
+		self.selection()?
+			.into_iter()
+			.map(|index| self.item(index))
+			.collect()
+	}
+
+	/// Select exactly the items at `indexes`, for any kind of list box. A single-selection list box only takes one.
+	pub fn set_selection(&self, indexes: &[usize]) -> Result<()> {
+		if !self.is_multi_select()? {
+			return match indexes {
+				[] => self.select(None),
+				[index] => self.select(Some(*index)),
+				_ => Err("a single-selection list box can't select several items".into()),
+			};
+		}
+		self.set_all_selected(false)?;
+		for index in indexes {
+			self.set_selected(*index, true)?;
+		}
+		Ok(())
+	}
+
+	pub fn is_selected(&self, index: usize) -> bool {
+		self.send(message::GetSel, index, 0) > 0
+	}
+
+	/// Add or remove an item from the selection of a multiple-selection list box.
+	pub fn set_selected(&self, index: usize, selected: bool) -> Result<()> {
+		let result = self.send(message::SetSel, selected as _, index as _);
+		item_index(result, &format!("no item {} in list box", index)).map(|_| ())
+	}
+
+	/// Select or unselect all the items of a multiple-selection list box.
+	pub fn set_all_selected(&self, selected: bool) -> Result<()> {
+		let result = self.send(message::SetSel, selected as _, -1);
+		item_index(result, "failed to select list box items").map(|_| ())
+	}
+
+	/// Scroll the list so that the item at `index` is at the top, or as close to it as possible.
+	pub fn scroll_to(&self, index: usize) -> Result<()> {
+		let result = self.send(message::SetTopIndex, index, 0);
+		item_index(result, &format!("no item {} in list box", index)).map(|_| ())
+	}
+
+	pub fn set_enabled(&self, enabled: bool) {
+		unsafe { EnableWindow(self.h_window, enabled) };
+	}
+}
+
+impl ItemList for ListBox {
+	fn list_window(&self) -> HWND {
+		self.h_window
+	}
+}
+
+/// Options of a list box, created as a child of `parent`.
+///
+/// ```ignore
+/// let files = ListBoxBuilder::new(h_window)
+///     .selection_mode(SelectionMode::Extended)
+///     .items(&["a.txt", "b.txt"])
+///     .at(10, 10)
+///     .size(200, 300)
+///     .create()?;
+/// ```
+pub struct ListBoxBuilder {
+	frame: ChildFrame,
+	mode: SelectionMode,
+	style: style::Type,
+	items: Vec<String>,
+	selection: Vec<usize>,
+}
+
+impl ListBoxBuilder {
+	/// Single-selection list box notifying its parent, items kept in the order they are added. Its height isn't rounded
+	/// to a whole number of items.
+	pub fn new(parent: HWND) -> Self {
+		Self {
+			frame: ChildFrame::new(parent, 100, 100),
+			mode: SelectionMode::Single,
+			style: style::Notify | style::NoIntegralHeight,
+			items: vec![],
+			selection: vec![],
+		}
+	}
+
+	frame_setters!();
+
+	pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Replace all the control styles, `Notify` is needed to get selection changes.
+	pub fn style(mut self, style: style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	/// Keep the items in alphabetical order.
+	pub fn sort(mut self, sort: bool) -> Self {
+		self.style.set(style::Sort, sort);
+		self
+	}
+
+	pub fn items(mut self, items: &[&str]) -> Self {
+		self.items = items.iter().map(|item| (*item).to_owned()).collect();
+		self
+	}
+
+	/// Items selected once created, at most one for a single-selection list box.
+	pub fn selection(mut self, indexes: &[usize]) -> Self {
+		self.selection = indexes.to_vec();
+		self
+	}
+
+	pub fn create(self) -> Result<ListBox> {
+		let mode = match self.mode {
+			SelectionMode::Single => style::Type::empty(),
+			SelectionMode::Multiple => style::MultipleSel,
+			SelectionMode::Extended => style::ExtendedSel,
+		};
+		let builder = self
+			.frame
+			.window_builder(LIST_BOX_CLASS)
+			.style(
+				window::style::Type::from(self.style | mode)
+					| window::style::Visible
+					| window::style::Child
+					| window::style::VScroll
+					| window::style::Tabstop,
+			)
+			.ex_style(window::ex_style::ClientEdge);
+		let list = ListBox::from_raw(builder.create()?);
+
+		// not left on the parent if it can't be set up
+		if let Err(e) = self.set_up(&list) {
+			unsafe { DestroyWindow(list.h_window) };
+			return Err(e);
+		}
+		Ok(list)
+	}
+
+	fn set_up(&self, list: &ListBox) -> Result<()> {
+		window::set_default_font(list.h_window);
+		for item in &self.items {
+			list.add(item)?;
+		}
+		list.set_selection(&self.selection)
+	}
+}
+
+impl From<style::Type> for window::style::Type {
+	fn from(style: style::Type) -> Self {
+		Self(style.0)
+	}
+}
+
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod style {
+	use crate::flags::flags;
+	use windows::Win32::UI::WindowsAndMessaging::*;
+
+	flags! {
+		pub struct Type(pub WINDOW_STYLE);
+
+		Standard = LBS_STANDARD as u32;
+		ComboBox = LBS_COMBOBOX as u32;
+		DisableNoScroll = LBS_DISABLENOSCROLL as u32;
+		ExtendedSel = LBS_EXTENDEDSEL as u32;
+		HasStrings = LBS_HASSTRINGS as u32;
+		MultiColumn = LBS_MULTICOLUMN as u32;
+		MultipleSel = LBS_MULTIPLESEL as u32;
+		NoData = LBS_NODATA as u32;
+		NoIntegralHeight = LBS_NOINTEGRALHEIGHT as u32;
+		NoRedraw = LBS_NOREDRAW as u32;
+		NoSel = LBS_NOSEL as u32;
+		Notify = LBS_NOTIFY as u32;
+		OwnerDrawFixed = LBS_OWNERDRAWFIXED as u32;
+		OwnerDrawVariable = LBS_OWNERDRAWVARIABLE as u32;
+		Sort = LBS_SORT as u32;
+		UseTabStops = LBS_USETABSTOPS as u32;
+		WantKeyboardInput = LBS_WANTKEYBOARDINPUT as u32;
+	}
+}
+
+// messages sent to list boxes
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod message {
+	use windows::Win32::UI::WindowsAndMessaging::*;
+	pub type Type = u32;
+
+	pub const AddString: Type = LB_ADDSTRING;
+	pub const DeleteString: Type = LB_DELETESTRING;
+	pub const FindString: Type = LB_FINDSTRING;
+	pub const FindStringExact: Type = LB_FINDSTRINGEXACT;
+	pub const GetCaretIndex: Type = LB_GETCARETINDEX;
+	pub const GetCount: Type = LB_GETCOUNT;
+	pub const GetCurSel: Type = LB_GETCURSEL;
+	pub const GetSel: Type = LB_GETSEL;
+	pub const GetSelCount: Type = LB_GETSELCOUNT;
+	pub const GetSelItems: Type = LB_GETSELITEMS;
+	pub const GetText: Type = LB_GETTEXT;
+	pub const GetTextLen: Type = LB_GETTEXTLEN;
+	pub const GetTopIndex: Type = LB_GETTOPINDEX;
+	pub const InsertString: Type = LB_INSERTSTRING;
+	pub const ResetContent: Type = LB_RESETCONTENT;
+	pub const SelectString: Type = LB_SELECTSTRING;
+	pub const SelItemRangeEx: Type = LB_SELITEMRANGEEX;
+	pub const SetCaretIndex: Type = LB_SETCARETINDEX;
+	pub const SetCurSel: Type = LB_SETCURSEL;
+	pub const SetSel: Type = LB_SETSEL;
+	pub const SetTopIndex: Type = LB_SETTOPINDEX;
+}
+
+// notifications sent to the parent in WM_COMMAND, with the `Notify` style
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
+pub mod event {
+	use windows::Win32::UI::WindowsAndMessaging::*;
+	pub type Type = u32;
+
+	pub const DblClk: Type = LBN_DBLCLK;
+	pub const ErrSpace: Type = LBN_ERRSPACE as u32;
+	pub const KillFocus: Type = LBN_KILLFOCUS;
+	pub const SelCancel: Type = LBN_SELCANCEL;
+	pub const SelChange: Type = LBN_SELCHANGE;
+	pub const SetFocus: Type = LBN_SETFOCUS;
+}
@@ -1,5 +1,478 @@
-#![allow(dead_code)]
-#![allow(non_upper_case_globals)]
+// Menu bars built from a description of their items, each item carrying a command of the app's type instead of a
+// hand-numbered id. `Menu` finds the items of those commands to check, uncheck, enable or disable them.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use windows::Win32::{
+	Foundation::HWND,
+	UI::WindowsAndMessaging::{
+		AppendMenuW, CheckMenuItem, CheckMenuRadioItem, CreateMenu, CreatePopupMenu, DestroyMenu,
+		DrawMenuBar, EnableMenuItem, GetMenu, GetMenuState, SetMenu, HMENU,
+	},
+};
+
+use crate::{
+	accelerator::{self, menu_text, AcceleratorTable, Shortcut},
+	assert::{assert_eq, assert_ne, Result, WithLastWin32Error},
+};
+
+/// Ids given to the items of a `MenuBar`, the ones below are free for controls and hand-made menus.
+pub const MENU_ID_START: u16 = 0x8000;
+
+// returned by CheckMenuItem and GetMenuState for an item that doesn't exist
+const NO_ITEM: u32 = u32::MAX;
+
+/// Item sending `command` when clicked.
+#[derive(Debug, Clone)]
+pub struct MenuItem<C> {
+	text: String,
+	command: C,
+	checked: bool,
+	radio: bool,
+	disabled: bool,
+	shortcut: Option<String>,
+}
+
+impl<C> MenuItem<C> {
+	pub fn new(text: &str, command: C) -> Self {
+		Self {
+			text: text.to_owned(),
+			command,
+			checked: false,
+			radio: false,
+			disabled: false,
+			shortcut: None,
+		}
+	}
+
+	pub fn checked(mut self, checked: bool) -> Self {
+		self.checked = checked;
+		self
+	}
+
+	/// Radio items next to each other form a group: checking one of them with `Menu::set_checked` unchecks the others.
+	/// A separator or another kind of item ends the group.
+	pub fn radio(mut self, radio: bool) -> Self {
+		self.radio = radio;
+		self
+	}
+
+	pub fn disabled(mut self, disabled: bool) -> Self {
+		self.disabled = disabled;
+		self
+	}
+
+	/// Keys sending the command without opening the menu, e.g. `"Ctrl+Shift+Z"`, shown after the text of the item. An
+	/// invalid shortcut fails `MenuBar::attach`.
+	pub fn shortcut(mut self, shortcut: &str) -> Self {
+		self.shortcut = Some(shortcut.to_owned());
+		self
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Entry<C> {
+	Item(MenuItem<C>),
+	Separator,
+	Submenu(String, Vec<Entry<C>>),
+}
+
+/// Items of a drop-down menu.
+#[derive(Debug, Clone)]
+pub struct Submenu<C> {
+	entries: Vec<Entry<C>>,
+}
+
+impl<C> Default for Submenu<C> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<C> Submenu<C> {
+	pub fn new() -> Self {
+		Self { entries: vec![] }
+	}
+
+	pub fn item(self, text: &str, command: C) -> Self {
+		self.push(MenuItem::new(text, command))
+	}
+
+	/// Add an item with options, e.g. `MenuItem::new("&Wrap", Cmd::Wrap).checked(true)`.
+	pub fn push(mut self, item: MenuItem<C>) -> Self {
+		self.entries.push(Entry::Item(item));
+		self
+	}
+
+	pub fn separator(mut self) -> Self {
+		self.entries.push(Entry::Separator);
+		self
+	}
+
+	/// Nested menu opening next to this one.
+	pub fn submenu(mut self, text: &str, items: impl FnOnce(Submenu<C>) -> Submenu<C>) -> Self {
+		let submenu = items(Submenu::new());
+		self.entries
+			.push(Entry::Submenu(text.to_owned(), submenu.entries));
+		self
+	}
+}
+
+/// Menu bar of a window, built once attached to it.
+///
+/// ```ignore
+/// let menu = MenuBar::new()
+///     .submenu("&File", |m| {
+///         m.push(MenuItem::new("&Save", Cmd::Save).shortcut("Ctrl+S"))
+///             .separator()
+///             .item("E&xit", Cmd::Exit)
+///     })
+///     .submenu("&View", |m| m.push(MenuItem::new("&Status bar", Cmd::StatusBar).checked(true)))
+///     .attach(h_window)?;
+/// menu.set_enabled(&Cmd::Save, false)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MenuBar<C> {
+	entries: Vec<Entry<C>>,
+}
+
+impl<C> Default for MenuBar<C> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<C> MenuBar<C> {
+	pub fn new() -> Self {
+		Self { entries: vec![] }
+	}
+
+	pub fn submenu(mut self, text: &str, items: impl FnOnce(Submenu<C>) -> Submenu<C>) -> Self {
+		let submenu = items(Submenu::new());
+		self.entries
+			.push(Entry::Submenu(text.to_owned(), submenu.entries));
+		self
+	}
+
+	/// Item shown on the bar itself, sending its command when clicked.
+	pub fn item(self, text: &str, command: C) -> Self {
+		self.push(MenuItem::new(text, command))
+	}
+
+	pub fn push(mut self, item: MenuItem<C>) -> Self {
+		self.entries.push(Entry::Item(item));
+		self
+	}
+
+	/// Create the menus and make them the menu bar of `h_window`, replacing and destroying the previous one. The
+	/// shortcuts of the items replace the accelerator table of the window, see `AcceleratorTable::attach`.
+	///
+	/// The `Menu` handles of the previous bar are stale from then on: their commands aren't found anymore and their
+	/// check and enable calls fail.
+	pub fn attach(self, h_window: HWND) -> Result<Menu<C>> {
+		let h_menu = unsafe { CreateMenu() };
+		assert_ne(h_menu, 0, "failed to create menu").with_last_win32_err()?;
+		let mut items = vec![];
+		let mut shortcuts = vec![];
+		let mut generation = 0;
+		let res = append_entries(h_menu, self.entries, &mut items, &mut shortcuts).and_then(|_| {
+			let table = match shortcuts.is_empty() {
+				true => None,
+				false => Some(AcceleratorTable::new(&shortcuts)?),
+			};
+			let previous = unsafe { GetMenu(h_window) };
+			assert_eq(
+				unsafe { SetMenu(h_window, h_menu) }.as_bool(),
+				true,
+				"failed to set window menu",
+			)
+			.with_last_win32_err()?;
+			if previous != 0 {
+				unsafe { DestroyMenu(previous) };
+			}
+			generation = next_generation(h_window);
+			match table {
+				Some(table) => table.attach(h_window),
+				None => accelerator::detach(h_window),
+			}
+			Ok(())
+		});
+		if let Err(e) = res {
+			unsafe { DestroyMenu(h_menu) };
+			return Err(e);
+		}
+
+		Ok(Menu {
+			h_window,
+			h_menu,
+			generation,
+			items: Rc::new(items),
+		})
+	}
+}
+
+thread_local! {
+	// bars attached to each window so far, a `Menu` of an older one is stale
+	static GENERATIONS: RefCell<BTreeMap<HWND, u64>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// never reset, a new window reusing the handle of a destroyed one doesn't bring its stale menus back
+fn next_generation(h_window: HWND) -> u64 {
+	GENERATIONS.with(|generations| {
+		let mut generations = generations.borrow_mut();
+		let generation = generations.entry(h_window).or_default();
+		*generation += 1;
+		*generation
+	})
+}
+
+#[derive(Debug)]
+struct ItemInfo<C> {
+	command: C,
+	id: u16,
+	// menu containing the item
+	parent: HMENU,
+	// ids of the first and last items of its radio group
+	radio_group: Option<(u16, u16)>,
+}
+
+fn next_id<C>(items: &[ItemInfo<C>]) -> Result<u16> {
+	u16::try_from(items.len())
+		.ok()
+		.and_then(|n| MENU_ID_START.checked_add(n))
+		.ok_or_else(|| "too many menu items".into())
+}
+
+fn append_entries<C>(
+	h_menu: HMENU,
+	entries: Vec<Entry<C>>,
+	items: &mut Vec<ItemInfo<C>>,
+	shortcuts: &mut Vec<(Shortcut, u16)>,
+) -> Result<()> {
+	// index in `items` of the first item of the current radio group
+	let mut group_start = None;
+	for entry in entries {
+		let radio = matches!(&entry, Entry::Item(item) if item.radio);
+		if !radio {
+			if let Some(start) = group_start.take() {
+				end_radio_group(&mut items[start..])?;
+			}
+		}
+		match entry {
+			Entry::Item(item) => {
+				let id = next_id(items)?;
+				let mut flags = item_flag::String;
+				if item.checked {
+					flags |= item_flag::Checked;
+				}
+				if item.disabled {
+					flags |= item_flag::Grayed;
+				}
+				let text = match &item.shortcut {
+					Some(shortcut) => {
+						let shortcut = shortcut.parse::<Shortcut>()?;
+						shortcuts.push((shortcut, id));
+						menu_text(&item.text, &shortcut)
+					}
+					None => item.text,
+				};
+				append(h_menu, flags, id as usize, &text)?;
+				if radio && group_start.is_none() {
+					group_start = Some(items.len());
+				}
+				items.push(ItemInfo {
+					command: item.command,
+					id,
+					parent: h_menu,
+					radio_group: None,
+				});
+			}
+			Entry::Separator => append(h_menu, item_flag::Separator, 0, "")?,
+			Entry::Submenu(text, entries) => {
+				let popup = unsafe { CreatePopupMenu() };
+				assert_ne(popup, 0, "failed to create popup menu").with_last_win32_err()?;
+				// a popup appended to a menu is destroyed along with it
+				let res = append_entries(popup, entries, items, shortcuts)
+					.and_then(|_| append(h_menu, item_flag::Popup, popup as usize, &text));
+				if let Err(e) = res {
+					unsafe { DestroyMenu(popup) };
+					return Err(e);
+				}
+			}
+		}
+	}
+	if let Some(start) = group_start {
+		end_radio_group(&mut items[start..])?;
+	}
+	Ok(())
+}
+
+fn end_radio_group<C>(group: &mut [ItemInfo<C>]) -> Result<()> {
+	let (first, last) = match (group.first(), group.last()) {
+		(Some(first), Some(last)) => (first.id, last.id),
+		_ => return Ok(()),
+	};
+	for item in group.iter_mut() {
+		item.radio_group = Some((first, last));
+	}
+	// drawn with a bullet instead of a check mark, the last checked item of the group wins
+	let parent = group[0].parent;
+	let state = |id: u16| unsafe { GetMenuState(parent, id as u32, item_flag::ByCommand) };
+	let checked = group
+		.iter()
+		.rev()
+		.find(|item| state(item.id) & item_flag::Checked != 0);
+	match checked {
+		Some(item) => check_radio_item(parent, (first, last), item.id),
+		None => Ok(()),
+	}
+}
+
+fn append(h_menu: HMENU, flags: item_flag::Type, id: usize, text: &str) -> Result<()> {
+	assert_eq(
+		unsafe { AppendMenuW(h_menu, flags, id, text) }.as_bool(),
+		true,
+		"failed to append menu",
+	)
+	.with_last_win32_err()
+}
+
+fn check_radio_item(parent: HMENU, (first, last): (u16, u16), id: u16) -> Result<()> {
+	assert_eq(
+		unsafe {
+			CheckMenuRadioItem(
+				parent,
+				first as u32,
+				last as u32,
+				id as u32,
+				item_flag::ByCommand,
+			)
+		}
+		.as_bool(),
+		true,
+		"failed to check menu item",
+	)
+	.with_last_win32_err()
+}
+
+/// Menu bar attached to a window by `MenuBar::attach`, the items being found by their command. Windows destroys it
+/// along with the window, clones and dropped handles leave it in place. Attaching another bar to the window destroys
+/// it and makes its handles stale.
+#[derive(Debug)]
+pub struct Menu<C> {
+	h_window: HWND,
+	h_menu: HMENU,
+	// of the window when attached, see `next_generation`
+	generation: u64,
+	items: Rc<Vec<ItemInfo<C>>>,
+}
+
+impl<C> Clone for Menu<C> {
+	fn clone(&self) -> Self {
+		Self {
+			h_window: self.h_window,
+			h_menu: self.h_menu,
+			generation: self.generation,
+			items: self.items.clone(),
+		}
+	}
+}
+
+impl<C> Menu<C> {
+	/// Destroyed once the menu is stale.
+	pub fn h_menu(&self) -> HMENU {
+		self.h_menu
+	}
+
+	/// False once another bar is attached to the window, the menu being destroyed.
+	pub fn is_attached(&self) -> bool {
+		GENERATIONS
+			.with(|generations| generations.borrow().get(&self.h_window) == Some(&self.generation))
+	}
+
+	/// Command of the item with `id`, the loword of the wparam of a `WM_COMMAND`. None for stale menus, the id being
+	/// one of the bar replacing it.
+	pub fn command(&self, id: u16) -> Option<&C> {
+		if !self.is_attached() {
+			return None;
+		}
+		let index = id.checked_sub(MENU_ID_START)?;
+		self.items.get(index as usize).map(|item| &item.command)
+	}
+}
+
+impl<C: PartialEq> Menu<C> {
+	/// Id of the first item sending `command`.
+	pub fn id_of(&self, command: &C) -> Option<u16> {
+		self.item(command).ok().map(|item| item.id)
+	}
+
+	fn item(&self, command: &C) -> Result<&ItemInfo<C>> {
+		if !self.is_attached() {
+			return Err("menu replaced by another menu bar".into());
+		}
+		self.items
+			.iter()
+			.find(|item| item.command == *command)
+			.ok_or_else(|| "no menu item for this command".into())
+	}
+
+	fn state(&self, command: &C) -> Option<u32> {
+		let item = self.item(command).ok()?;
+		match unsafe { GetMenuState(item.parent, item.id as u32, item_flag::ByCommand) } {
+			NO_ITEM => None,
+			state => Some(state),
+		}
+	}
+
+	/// Checking a radio item unchecks the other ones of its group.
+	pub fn set_checked(&self, command: &C, checked: bool) -> Result<()> {
+		let item = self.item(command)?;
+		if let (Some(group), true) = (item.radio_group, checked) {
+			return check_radio_item(item.parent, group, item.id);
+		}
+		let flags = match checked {
+			true => item_flag::Checked,
+			false => item_flag::Unchecked,
+		};
+		assert_ne(
+			unsafe { CheckMenuItem(item.parent, item.id as u32, item_flag::ByCommand | flags) },
+			NO_ITEM,
+			"failed to check menu item",
+		)
+	}
+
+	pub fn is_checked(&self, command: &C) -> bool {
+		self.state(command)
+			.is_some_and(|state| state & item_flag::Checked != 0)
+	}
+
+	/// A disabled item is grayed out and doesn't send its command.
+	pub fn set_enabled(&self, command: &C, enabled: bool) -> Result<()> {
+		let item = self.item(command)?;
+		let flags = match enabled {
+			true => item_flag::Enabled,
+			false => item_flag::Grayed,
+		};
+		let res =
+			unsafe { EnableMenuItem(item.parent, item.id as u32, item_flag::ByCommand | flags) };
+		// the previous state, or -1 if there's no such item
+		assert_ne(res.0, -1, "failed to enable menu item")?;
+		// items of the bar itself aren't redrawn otherwise
+		if item.parent == self.h_menu {
+			unsafe { DrawMenuBar(self.h_window) };
+		}
+		Ok(())
+	}
+
+	pub fn is_enabled(&self, command: &C) -> bool {
+		self.state(command)
+			.is_some_and(|state| state & (item_flag::Grayed | item_flag::Disabled) == 0)
+	}
+}
+
+#[allow(dead_code)]
+#[allow(non_upper_case_globals)]
 pub mod item_flag {
 	use windows::Win32::UI::WindowsAndMessaging::*;
 	pub type Type = MENU_ITEM_FLAGS;
@@ -33,3 +506,65 @@ pub mod item_flag {
 	pub const MouseSelect: Type = MF_MOUSESELECT;
 	pub const End: Type = MF_END;
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(id: u16) -> ItemInfo<u16> {
+		ItemInfo {
+			command: id,
+			id,
+			parent: 0,
+			radio_group: None,
+		}
+	}
+
+	#[test]
+	fn ids() {
+		let mut items = vec![];
+		assert_eq!(next_id(&items).unwrap(), MENU_ID_START);
+		items.push(item(MENU_ID_START));
+		assert_eq!(next_id(&items).unwrap(), MENU_ID_START + 1);
+
+		// up to u16::MAX
+		let items = (0..=u16::MAX - MENU_ID_START).map(item).collect::<Vec<_>>();
+		assert_eq!(next_id(&items[1..]).unwrap(), u16::MAX);
+		assert_eq!(
+			next_id(&items).unwrap_err().to_string(),
+			"too many menu items"
+		);
+	}
+
+	#[test]
+	fn commands() {
+		// a handle of its own, the generations are shared by the tests of the thread
+		let h_window = 0x7e57;
+		let menu = Menu {
+			h_window,
+			h_menu: 0,
+			generation: next_generation(h_window),
+			items: Rc::new(vec![item(MENU_ID_START), item(MENU_ID_START + 1)]),
+		};
+		assert!(menu.is_attached());
+		assert_eq!(menu.command(MENU_ID_START), Some(&MENU_ID_START));
+		assert_eq!(menu.command(MENU_ID_START + 1), Some(&(MENU_ID_START + 1)));
+		assert_eq!(menu.command(MENU_ID_START + 2), None);
+		assert_eq!(menu.command(0), None);
+		assert_eq!(menu.command(MENU_ID_START - 1), None);
+		assert_eq!(menu.id_of(&(MENU_ID_START + 1)), Some(MENU_ID_START + 1));
+
+		// replaced by another bar
+		let clone = menu.clone();
+		next_generation(h_window);
+		assert!(!clone.is_attached());
+		assert_eq!(clone.command(MENU_ID_START), None);
+		assert_eq!(menu.id_of(&MENU_ID_START), None);
+		assert_eq!(
+			menu.set_checked(&MENU_ID_START, true)
+				.unwrap_err()
+				.to_string(),
+			"menu replaced by another menu bar"
+		);
+	}
+}
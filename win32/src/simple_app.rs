@@ -1,63 +1,250 @@
+use std::fmt;
+
 use crate::{
 	assert::Result,
-	display,
-	layout::{Control, Screen},
-	window::{MessageAction, Options, WindowBase, WindowHandler},
+	display, hiword,
+	layout::{Control, Screen, Size},
+	loword,
+	menu::MenuBar,
+	window::{KeyEvent, MessageAction, Options, WindowBase, WindowHandler},
 };
 use derive::WindowBase;
-use windows::Win32::Foundation::{HINSTANCE, HWND};
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, WPARAM};
+
+// attaches the menu bar on WM_CREATE, returning the route of its commands
+type AttachMenu<S> = Box<dyn FnOnce(HWND) -> Result<MenuRoute<S>>>;
+// calls the menu hook with the command of the item, false if the id isn't one of the menu's
+type MenuRoute<S> = Box<dyn Fn(&mut S, u16) -> bool>;
+
+// gets the state and the argument of the hook
+type Hook<S, A> = Option<Box<dyn Fn(&mut S, A)>>;
+
+// closures set by the hook methods of `SimpleApp`
+struct Hooks<S> {
+	size: Hook<S, Size>,
+	key_down: Hook<S, KeyEvent>,
+	timer: Hook<S, usize>,
+	close: Hook<S, ()>,
+	attach_menu: Option<AttachMenu<S>>,
+	menu: Option<MenuRoute<S>>,
+}
+
+impl<S> Default for Hooks<S> {
+	fn default() -> Self {
+		Self {
+			size: None,
+			key_down: None,
+			timer: None,
+			close: None,
+			attach_menu: None,
+			menu: None,
+		}
+	}
+}
 
-#[derive(Debug, WindowBase)]
-pub struct SimpleApp {
+/// Window showing a `Control` tree built from a user state `S`. Control callbacks and message hooks, e.g. `on_key_down`,
+/// get `&mut S`, after which the layout is built again and the controls that changed are updated.
+#[derive(WindowBase)]
+pub struct SimpleApp<S> {
 	h_instance: HINSTANCE,
 	h_window: HWND,
-	create_layout: fn() -> Control,
+	// only None before `new` and after `run` returns it
+	state: Option<S>,
+	create_layout: Box<dyn Fn(&S) -> Control>,
 	title: String,
+	screen: Option<Screen>,
+	hooks: Hooks<S>,
+}
+
+impl<S> fmt::Debug for SimpleApp<S> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SimpleApp")
+			.field("h_instance", &self.h_instance)
+			.field("h_window", &self.h_window)
+			.field("title", &self.title)
+			.field("screen", &self.screen)
+			.finish_non_exhaustive()
+	}
 }
 
-impl Default for SimpleApp {
+impl<S> Default for SimpleApp<S> {
 	fn default() -> Self {
 		Self {
-			create_layout: || Control::None,
+			state: None,
+			create_layout: Box::new(|_| Control::None),
 			title: Default::default(),
 			h_instance: Default::default(),
 			h_window: Default::default(),
+			screen: None,
+			hooks: Default::default(),
 		}
 	}
 }
 
-impl SimpleApp {
-	pub fn new(title: &str, create_layout: fn() -> Control) -> Self {
+impl<S: 'static> SimpleApp<S> {
+	pub fn new(title: &str, state: S, create_layout: impl Fn(&S) -> Control + 'static) -> Self {
 		Self {
 			title: title.to_owned(),
-			create_layout,
+			state: Some(state),
+			create_layout: Box::new(create_layout),
 			..Default::default()
 		}
 	}
 
-	pub fn run(&self) -> Result<()> {
-		let main_window = Self::new_window(
+	/// Called on `WM_SIZE` with the size of the client area, once the controls are laid out again.
+	pub fn on_size(mut self, f: impl Fn(&mut S, Size) + 'static) -> Self {
+		self.hooks.size = Some(Box::new(f));
+		self
+	}
+
+	pub fn on_key_down(mut self, f: impl Fn(&mut S, KeyEvent) + 'static) -> Self {
+		self.hooks.key_down = Some(Box::new(f));
+		self
+	}
+
+	/// Called with the id of timers set with `SetTimer`.
+	pub fn on_timer(mut self, f: impl Fn(&mut S, usize) + 'static) -> Self {
+		self.hooks.timer = Some(Box::new(f));
+		self
+	}
+
+	/// Called on `WM_CLOSE`, before the window is destroyed.
+	pub fn on_close(mut self, f: impl Fn(&mut S) + 'static) -> Self {
+		self.hooks.close = Some(Box::new(move |state, ()| f(state)));
+		self
+	}
+
+	/// Menu bar of the window, `on_menu` being called with the command of the item clicked.
+	pub fn menu_bar<C: 'static>(
+		mut self,
+		bar: MenuBar<C>,
+		on_menu: impl Fn(&mut S, &C) + 'static,
+	) -> Self {
+		self.hooks.attach_menu = Some(Box::new(move |h_window| {
+			let menu = bar.attach(h_window)?;
+			let route: MenuRoute<S> = Box::new(move |state, id| match menu.command(id) {
+				Some(cmd) => {
+					on_menu(state, cmd);
+					true
+				}
+				None => false,
+			});
+			Ok(route)
+		}));
+		self
+	}
+
+	pub fn state(&self) -> &S {
+		self.state.as_ref().expect("no app state")
+	}
+
+	pub fn state_mut(&mut self) -> &mut S {
+		self.state.as_mut().expect("no app state")
+	}
+
+	/// Build the layout again from the current state and update the controls that changed.
+	pub fn refresh(&mut self) -> Result<()> {
+		let root = (self.create_layout)(self.state());
+		if let Some(screen) = &mut self.screen {
+			screen.update(root)?;
+		}
+		Ok(())
+	}
+
+	// run a hook with the state and update the layout, `hook` returns false if it didn't run, e.g. the hook isn't set
+	fn run_hook(&mut self, hook: impl FnOnce(&Hooks<S>, &mut S) -> bool) -> Result<bool> {
+		let state = self.state.as_mut().expect("no app state");
+		let ran = hook(&self.hooks, state);
+		if ran {
+			self.refresh()?;
+		}
+		Ok(ran)
+	}
+
+	/// Show the window and run the event loop. Returns the state once the window is closed.
+	pub fn run(self) -> Result<S> {
+		let title = self.title.clone();
+		let mut main_window = Self::new_window_with(
 			"MainWindow",
-			self.title.as_str(),
+			title.as_str(),
 			Options {
 				..Default::default()
 			},
+			// the window handler is a new value owning this app's state and layout
+			|h_instance| Self { h_instance, ..self },
 		)?;
 		display!("main_window: {:?}", main_window);
 
-		let res = Self::event_loop();
+		let res = Self::event_loop()?;
 		display!("event_loop result: {} ({:#X})", res, res);
 
-		Ok(())
+		Ok(main_window.state.take().expect("no app state"))
 	}
 }
 
-impl WindowHandler for SimpleApp {
-	fn on_create(&self) -> Result<MessageAction> {
-		let root = (self.create_layout)();
+impl<S: 'static> WindowHandler for SimpleApp<S> {
+	fn on_create_mut(&mut self) -> Result<MessageAction> {
+		if let Some(attach_menu) = self.hooks.attach_menu.take() {
+			self.hooks.menu = Some(attach_menu(self.h_window)?);
+		}
+		let root = (self.create_layout)(self.state());
 		let mut screen = Screen::new(self.h_instance, self.h_window);
 		screen.render(root)?;
+		self.screen = Some(screen);
+
+		Ok(MessageAction::Continue)
+	}
 
+	fn on_size_mut(&mut self) -> Result<MessageAction> {
+		let size = match &self.screen {
+			Some(screen) => {
+				screen.relayout()?;
+				screen.client_size()?
+			}
+			None => return Ok(MessageAction::Continue),
+		};
+		self.run_hook(|hooks, state| hooks.size.as_ref().map(|f| f(state, size)).is_some())?;
 		Ok(MessageAction::Continue)
 	}
+
+	fn on_key_down(&mut self, event: KeyEvent) -> Result<MessageAction> {
+		self.run_hook(|hooks, state| hooks.key_down.as_ref().map(|f| f(state, event)).is_some())?;
+		Ok(MessageAction::Continue)
+	}
+
+	fn on_timer(&mut self, id: usize) -> Result<MessageAction> {
+		self.run_hook(|hooks, state| hooks.timer.as_ref().map(|f| f(state, id)).is_some())?;
+		Ok(MessageAction::Continue)
+	}
+
+	fn on_close(&mut self) -> Result<MessageAction> {
+		self.run_hook(|hooks, state| hooks.close.as_ref().map(|f| f(state, ())).is_some())?;
+		Ok(MessageAction::Continue)
+	}
+
+	// route menu commands to the menu hook and notifications of the layout controls to their callbacks
+	fn on_command(&mut self, lparam: LPARAM, wparam: WPARAM) -> Result<MessageAction> {
+		// menus send a hiword of 0 and accelerators 1, controls send their window and a notification code
+		if lparam == 0 && hiword(wparam) <= 1 {
+			let id = loword(wparam) as u16;
+			let routed = self.run_hook(|hooks, state| {
+				hooks.menu.as_ref().is_some_and(|route| route(state, id))
+			})?;
+			if routed {
+				return Ok(MessageAction::FullyHandled);
+			}
+		}
+		let command = match &self.screen {
+			Some(screen) => screen.command(wparam, lparam)?,
+			None => None,
+		};
+		match command {
+			Some(command) => {
+				command.invoke(self.state_mut())?;
+				self.refresh()?;
+				Ok(MessageAction::FullyHandled)
+			}
+			None => Ok(MessageAction::Continue),
+		}
+	}
 }
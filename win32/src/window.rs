@@ -1,13 +1,15 @@
 use crate::{
 	assert::{assert_eq, assert_ne, assert_not_null, Result, WithLastWin32Error, WrappedError},
-	cursor::{self, load_cursor},
-	display,
-	icon::{self, load_icon, Icon},
-	impl_ops_for_all,
+	cursor, display,
+	icon::{self, Icon},
 	theme::{app_theme_settings, Theme},
 	wide_string::ToWide,
 	window_long::{get_window_long_ptr, set_window_long_ptr},
 };
+use std::{
+	panic::{self, AssertUnwindSafe},
+	time::Duration,
+};
 use windows::Win32::{
 	Foundation::{BOOL, HINSTANCE, HWND, LPARAM, LRESULT, PWSTR, WPARAM},
 	Graphics::Gdi::{GetStockObject, UpdateWindow, DEFAULT_GUI_FONT},
@@ -15,11 +17,28 @@ use windows::Win32::{
 	UI::WindowsAndMessaging::*,
 };
 
+mod app_handle;
+mod builder;
+mod decode;
+mod error_sink;
+mod event;
+mod menu_handler;
+mod message_loop;
+mod registry;
+mod timer;
+pub use app_handle::*;
+pub use builder::*;
+pub use decode::*;
+pub use error_sink::*;
+pub use event::*;
+pub use menu_handler::*;
+pub use message_loop::*;
+pub use registry::*;
+pub use timer::*;
+
 pub type WinProc =
 	unsafe extern "system" fn(window: HWND, message: message::Type, WPARAM, LPARAM) -> LRESULT;
 
-impl_ops_for_all!(class_style::Type, style::Type, ex_style::Type);
-
 pub enum MessageAction {
 	None,
 	Continue,
@@ -56,6 +75,21 @@ impl Default for Options {
 	}
 }
 
+impl Options {
+	pub fn into_builder(self, class_name: &str, title: &str) -> WindowBuilder {
+		WindowBuilder::new(class_name)
+			.text(title)
+			.style(self.window_style)
+			.ex_style(self.window_ext_style)
+			.at(self.x, self.y)
+			.size(self.width, self.height)
+			.class_style(self.class_style)
+			.icon(self.icon)
+			.cursor(self.cursor)
+			.bg_brush(self.bg_brush)
+	}
+}
+
 pub enum WindowCreateData {
 	AppState,
 	None,
@@ -67,48 +101,18 @@ pub trait WindowBase: Default {
 	fn set_h_window(&mut self, h_window: HWND);
 	fn h_window(&self) -> HWND;
 
-	#[allow(clippy::too_many_arguments)]
+	/// Create a window described by `builder`. With `WindowCreateData::AppState`, its `WM_CREATE` gets a pointer to
+	/// `self`, as expected by `WindowHandler::win_proc`.
 	fn create_window(
 		&mut self,
-		class_name: &str,
-		text: Option<&str>,
-		ex_style: Option<ex_style::Type>,
-		style: Option<style::Type>,
-		x: i32,
-		y: i32,
-		width: i32,
-		height: i32,
-		h_instance: Option<HINSTANCE>,
-		parent: Option<HWND>,
+		builder: WindowBuilder,
 		create_data: WindowCreateData,
 	) -> Result<HWND> {
-		let hwnd = unsafe {
-			CreateWindowExW(
-				if let Some(s) = ex_style { s.0 } else { 0 },
-				class_name.to_wide().as_pwstr(),
-				if let Some(t) = text { t } else { "" }.to_wide().as_pwstr(),
-				if let Some(s) = style { s.0 } else { 0 },
-				x,
-				y,
-				width,
-				height,
-				parent,
-				None,
-				h_instance,
-				// pass ptr-to-self to win-proc, via WM_CREATE lparam
-				match create_data {
-					WindowCreateData::AppState => self as *mut _ as _,
-					WindowCreateData::None => std::ptr::null_mut(),
-				},
-			)
+		let param = match create_data {
+			WindowCreateData::AppState => self as *mut Self as *const _,
+			WindowCreateData::None => std::ptr::null(),
 		};
-		assert_ne(
-			hwnd,
-			0,
-			format!("failed to create window for class {:?}", class_name).as_str(),
-		)
-		.with_last_win32_err()?;
-		Ok(hwnd)
+		builder.create_with_param(param)
 	}
 }
 
@@ -117,12 +121,39 @@ where
 	Self: Sized,
 {
 	fn new_window<Opts>(class_name: &str, title: &str, options: Opts) -> Result<Box<Self>>
+	where
+		Self: Sized,
+		Opts: Into<Option<Options>>,
+	{
+		Self::new_window_with(class_name, title, options, Self::init_state)
+	}
+
+	/// Same as `new_window`, with the window state built by `init_state` instead of `WindowBase::init_state`, e.g. to
+	/// keep the data of an existing value.
+	fn new_window_with<Opts>(
+		class_name: &str,
+		title: &str,
+		options: Opts,
+		init_state: impl FnOnce(HINSTANCE) -> Self,
+	) -> Result<Box<Self>>
 	where
 		Self: Sized,
 		Opts: Into<Option<Options>>,
 	{
 		let opts = options.into().unwrap_or_default();
+		Self::create_with(opts.into_builder(class_name, title), init_state)
+	}
+
+	/// Register the class of `builder` with `Self::win_proc` and create the window.
+	fn create(builder: WindowBuilder) -> Result<Box<Self>> {
+		Self::create_with(builder, Self::init_state)
+	}
 
+	/// Same as `create`, with the window state built by `init_state`.
+	fn create_with(
+		builder: WindowBuilder,
+		init_state: impl FnOnce(HINSTANCE) -> Self,
+	) -> Result<Box<Self>> {
 		let mut h_instance: HINSTANCE = Default::default();
 		assert_eq(
 			unsafe { GetModuleHandleExW(0, None, &mut h_instance as *mut _) }.as_bool(),
@@ -131,64 +162,16 @@ where
 		)
 		.with_last_win32_err()?;
 
-		let size: u32 = std::mem::size_of::<WNDCLASSEXW>()
-			.try_into()
-			.expect("WNDCLASSEXW size not u32");
-
-		let icon = load_icon(opts.icon)?;
-		let wnd_class = WNDCLASSEXW {
-			cbSize: size,
-			style: opts.class_style.0,
-			lpfnWndProc: Some(Self::win_proc),
-			cbClsExtra: 0,
-			cbWndExtra: 0,
-			hInstance: h_instance,
-			hIcon: icon,
-			hCursor: load_cursor(opts.cursor)?,
-			hbrBackground: opts
-				.bg_brush
-				.try_into()
-				.expect("cannot convert color to HBRUSH"),
-			lpszMenuName: Default::default(), // defaults to null
-			lpszClassName: class_name.to_wide().as_pwstr(),
-			hIconSm: icon,
-		};
+		// checked before the class gets registered
+		builder.validate()?;
+		let (class, builder) = builder.take_class();
+		// the class is registered by the first window of that class, the next ones share it
+		let class = class.win_proc(Self::win_proc).register(h_instance)?;
 
-		let class = unsafe { RegisterClassExW(&wnd_class) };
-		assert_ne(class, 0, "failed to register class").with_last_win32_err()?;
-
-		let mut state = Box::new(Self::init_state(h_instance));
-
-		let h_window = state.create_window(
-			class_name,
-			Some(title),
-			Some(opts.window_ext_style),
-			Some(opts.window_style),
-			opts.x,
-			opts.y,
-			opts.width,
-			opts.height,
-			Some(h_instance),
-			None,
-			WindowCreateData::AppState,
-		)?;
-		// CreateWindowExW(
-		// 	opts.window_ext_style.0,
-		// 	class_name.to_wide().as_pwstr(),
-		// 	title.to_wide().as_pwstr(),
-		// 	opts.window_style.0,
-		// 	opts.x,
-		// 	opts.y,
-		// 	opts.width,
-		// 	opts.height,
-		// 	None,
-		// 	None,
-		// 	h_instance,
-		// 	&mut state as *mut _ as _,
-		// )
-		// assert_ne(h_window, 0, "failed to create window").with_last_win32_err()?;
-
-		unsafe { ShowWindow(h_window, show_cmd::Show) };
+		let mut state = Box::new(init_state(h_instance));
+		let h_window =
+			state.create_window(builder.h_instance(h_instance), WindowCreateData::AppState)?;
+		class.hold_for_window(h_window);
 		unsafe { UpdateWindow(h_window) };
 
 		display!("new_window => state addr {:p}", &state);
@@ -197,7 +180,6 @@ where
 	}
 
 	// TODO: find better name
-	// TODO: should only be called once per app (or top window?)
 	fn set_window_state_from_lparam(h_window: HWND, lparam: LPARAM) -> Result<*mut Self> {
 		let create_struct = lparam as *mut CREATESTRUCTW;
 		assert_not_null(create_struct, "WM_CREATE lparam cannot be null")?;
@@ -226,21 +208,52 @@ where
 		Ok(state)
 	}
 
-	fn event_loop() -> WPARAM {
-		let mut msg: MSG = Default::default();
-		let msg_ptr: *mut MSG = &mut msg as *mut _;
-		unsafe {
-			while GetMessageW(msg_ptr, 0, 0, 0).as_bool() {
-				TranslateMessage(msg_ptr);
-				DispatchMessageW(msg_ptr);
+	/// Call `callback` every `interval` until the timer is killed, with `kill_timer` or a `TimerGuard`.
+	fn set_timer(
+		&self,
+		interval: Duration,
+		mut callback: impl FnMut(&mut Self) + 'static,
+	) -> Result<TimerId> {
+		let callback =
+			Box::new(move |handler: *mut ()| callback(unsafe { &mut *(handler as *mut Self) }));
+		timer::start(self.h_window(), interval, callback, false)
+	}
+
+	/// Call `callback` once after `delay`, unless the timer is killed before.
+	fn set_timeout(
+		&self,
+		delay: Duration,
+		callback: impl FnOnce(&mut Self) + 'static,
+	) -> Result<TimerId> {
+		let mut callback = Some(callback);
+		let callback = Box::new(move |handler: *mut ()| {
+			if let Some(callback) = callback.take() {
+				callback(unsafe { &mut *(handler as *mut Self) })
 			}
-			(*msg_ptr).wParam
-		}
+		});
+		timer::start(self.h_window(), delay, callback, true)
+	}
+
+	/// Returns false if the timer already stopped, e.g. a timeout that elapsed.
+	fn kill_timer(&self, id: TimerId) -> bool {
+		timer::kill(self.h_window(), id)
+	}
+
+	/// Handle running closures with this window handler on the UI thread, from any thread.
+	fn app_handle(&self) -> AppHandle<Self> {
+		AppHandle::for_window(self.h_window())
+	}
+
+	/// Run until `WM_QUIT` and return its exit code, or the handler error that ended the loop, see `QuitOnError`.
+	/// `MessageLoop` has other ways to run the loop.
+	fn event_loop() -> Result<ExitCode> {
+		MessageLoop::new().run()
 	}
 
 	/// # Safety
 	///
-	/// This function is full of thread unsafetiness and other dangerous stuff.
+	/// This function is full of thread unsafetiness and other dangerous stuff. It never unwinds though: errors and
+	/// panics of the handler are sent to the thread's `ErrorSink`.
 	unsafe extern "system" fn win_proc(
 		h_window: HWND,
 		message: message::Type,
@@ -248,41 +261,85 @@ where
 		lparam: LPARAM,
 	) -> LRESULT {
 		use MessageAction::*;
-		static mut USER_DATA: isize = 0;
 
 		let default_win_proc = || DefWindowProcW(h_window, message, wparam, lparam);
 
-		match message {
-			message::Create => {
-				let state = Self::set_window_state_from_lparam(h_window, lparam).unwrap();
-				(*state).set_h_window(h_window);
-				USER_DATA = state as _;
+		let state: *mut Self = match message {
+			message::Create => match Self::set_window_state_from_lparam(h_window, lparam) {
+				Ok(state) => {
+					(*state).set_h_window(h_window);
+					// child windows close with their parent, they don't keep the app running
+					let style = style::Type(GetWindowLongPtrW(h_window, GWL_STYLE) as u32);
+					if !style.contains(style::Child) {
+						registry::register_window(h_window);
+					}
+					state
+				}
+				Err(e) => {
+					report_error(h_window, message, e);
+					// CreateWindowExW fails
+					return -1;
+				}
+			},
+			// last message received, the window handler mustn't be reached through this window anymore
+			message::NcDestroy => {
+				SetWindowLongPtrW(h_window, GWLP_USERDATA, 0);
+				app_handle::close_queue(h_window);
+				timer::close(h_window);
+				menu_handler::close(h_window);
+				builder::forget_track_size(h_window);
+				return default_win_proc();
 			}
-			// called before Create, no state has yet been associated with window handler
-			message::GetMinmaxinfo | message::NcCreate | message::NcCalcSize => {
+			// min and max size of the builder, the first WM_GETMINMAXINFO comes before WM_NCCREATE
+			message::GetMinmaxinfo if builder::apply_track_size(h_window, lparam) => return 0,
+			// windows that aren't resizable get no WM_GETMINMAXINFO while being created
+			message::NcCreate => {
+				builder::claim_track_size(h_window);
 				return default_win_proc();
 			}
 			// some very noisy messages that can be generally ignored
 			message::NcHitTest | message::NcMouseMove | message::Setcursor => {
 				return default_win_proc();
 			}
-			message::Destroy => {
-				PostQuitMessage(0);
-				// 0
-				// std::ptr::null_mut()
-			}
-
-			_ => {}
+			// each window has its own state, written to GWLP_USERDATA on WM_CREATE
+			_ => GetWindowLongPtrW(h_window, GWLP_USERDATA) as _,
 		};
 
-		assert_ne(USER_DATA, 0, "no win_proc static USER_DATA").unwrap();
-		let state: *mut Self = USER_DATA as _;
+		// messages sent before WM_CREATE, e.g. WM_GETMINMAXINFO, WM_NCCREATE, WM_NCCALCSIZE
+		if state.is_null() {
+			return default_win_proc();
+		}
+
+		if message == message::Destroy {
+			registry::unregister_window(h_window);
+		}
 
 		// display!("win_proc => message {:?}", message);
-		// display!("win_proc => USER_DATA {:?}", USER_DATA);
 		// display!("win_proc => state.h_instance {:?}", (*state).h_instance());
 
-		let action = (*state).on_message(message, wparam, lparam).unwrap();
+		let res = panic::catch_unwind(AssertUnwindSafe(|| match message {
+			APP_HANDLE_MESSAGE => {
+				app_handle::run_queued(h_window, state as *mut ());
+				Ok(FullyHandled)
+			}
+			message::Timer if timer::run(h_window, wparam, state as *mut ()) => Ok(FullyHandled),
+			message::Command => {
+				let routed = menu_handler::run(h_window, wparam, lparam, state as *mut ());
+				routed.unwrap_or_else(|| (*state).on_message(message, wparam, lparam))
+			}
+			_ => (*state).on_message(message, wparam, lparam),
+		}));
+		let action = match res.unwrap_or_else(|payload| Err(panic_error(payload))) {
+			Ok(action) => action,
+			Err(e) => {
+				report_error(h_window, message, e);
+				return match message {
+					// the window isn't created, CreateWindowExW fails
+					message::Create => -1,
+					_ => default_win_proc(),
+				};
+			}
+		};
 
 		// set font to all controls
 		if message == message::Create {
@@ -301,7 +358,7 @@ where
 			SendMessageW(
 				child,
 				message::Setfont,
-				font.try_into().expect("cannot cast LPARAM font to usize"),
+				font as _,
 				1, // => true
 			);
 		}
@@ -332,7 +389,10 @@ where
 			}
 			message::Size => {
 				display!("WM_SIZE");
-				self.on_size()
+				match self.on_size_mut()? {
+					None => self.on_size(),
+					other => Ok(other),
+				}
 			}
 			message::Command => {
 				display!("WM_COMMAND");
@@ -342,14 +402,57 @@ where
 				display!("WM_MOVE");
 				self.on_move()
 			}
-			message::Settingchange => unsafe {
-				let pwstr = lparam as *mut PWSTR;
-				if *pwstr == "ImmersiveColorSet".to_wide().as_pwstr() {
+			message::Settingchange => {
+				// lparam is only valid while the message is handled
+				let area = unsafe { setting_area(lparam) };
+				if area.as_deref() == Some("ImmersiveColorSet") {
 					self.on_theme_change(app_theme_settings()?)
 				} else {
 					Ok(Continue)
 				}
+			}
+			message::Close => {
+				display!("WM_CLOSE");
+				self.on_close()
+			}
+			message::Destroy => {
+				display!("WM_DESTROY");
+				self.on_destroy()
+			}
+			_ => self.on_input_message(Message::decode(message, wparam, lparam)),
+		}
+	}
+
+	// keyboard, mouse, focus and timer hooks
+	fn on_input_message(&mut self, message: Message) -> Result<MessageAction> {
+		use MessageAction::*;
+
+		if let Some(event) = KeyEvent::from_message(&message) {
+			return match message {
+				Message::KeyDown { .. } | Message::SysKeyDown { .. } => self.on_key_down(event),
+				_ => self.on_key_up(event),
+			};
+		}
+		if let Some(event) = MouseEvent::from_message(&message) {
+			return match message {
+				Message::MouseMove { .. } => self.on_mouse_move(event),
+				Message::MouseDown { .. } => self.on_mouse_down(event),
+				Message::MouseUp { .. } => self.on_mouse_up(event),
+				_ => self.on_mouse_double_click(event),
+			};
+		}
+		if let Some(event) = WheelEvent::from_message(&message) {
+			return self.on_mouse_wheel(event);
+		}
+		match message {
+			Message::Char { code, .. } => match char_from_code_unit(code) {
+				Some(c) => self.on_char(c),
+				// first half of a surrogate pair
+				Option::None => Ok(Continue),
 			},
+			Message::SetFocus { previous } => self.on_focus(previous),
+			Message::KillFocus { next } => self.on_blur(next),
+			Message::Timer { id, .. } => self.on_timer(id),
 			_ => Ok(Continue),
 		}
 	}
@@ -366,7 +469,7 @@ where
 		Ok(MessageAction::None)
 	}
 
-	fn on_command(&self, _lparam: LPARAM, _wparamm: WPARAM) -> Result<MessageAction> {
+	fn on_command(&mut self, _lparam: LPARAM, _wparamm: WPARAM) -> Result<MessageAction> {
 		Ok(MessageAction::None)
 	}
 
@@ -374,6 +477,10 @@ where
 		Ok(MessageAction::None)
 	}
 
+	fn on_size_mut(&mut self) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
 	fn on_size(&self) -> Result<MessageAction> {
 		Ok(MessageAction::None)
 	}
@@ -381,27 +488,118 @@ where
 	fn on_theme_change(&self, _app_theme: Theme) -> Result<MessageAction> {
 		Ok(MessageAction::None)
 	}
+
+	/// The window is asked to close. Return `FullyHandled` to keep it open, otherwise it's destroyed.
+	fn on_close(&mut self) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_destroy(&mut self) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_key_down(&mut self, _event: KeyEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_key_up(&mut self, _event: KeyEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	/// Character typed, also sent for auto-repeats of a held key and for characters typed with ALT down.
+	fn on_char(&mut self, _c: char) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_mouse_move(&mut self, _event: MouseEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_mouse_down(&mut self, _event: MouseEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_mouse_up(&mut self, _event: MouseEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_mouse_double_click(&mut self, _event: MouseEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_mouse_wheel(&mut self, _event: WheelEvent) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	/// The window got the keyboard focus from `_previous`, 0 if none.
+	fn on_focus(&mut self, _previous: HWND) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	/// The window lost the keyboard focus to `_next`, 0 if none.
+	fn on_blur(&mut self, _next: HWND) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+
+	fn on_timer(&mut self, _id: usize) -> Result<MessageAction> {
+		Ok(MessageAction::None)
+	}
+}
+
+pub fn get_window_text(h_window: HWND) -> Result<String> {
+	// length doesn't include the terminating null character
+	let len = unsafe { GetWindowTextLengthW(h_window) };
+	if len == 0 {
+		return Ok(String::new());
+	}
+	let capacity: usize = (len + 1).try_into().expect("invalid window text length");
+	let mut buf = vec![0u16; capacity];
+	let copied = unsafe { GetWindowTextW(h_window, PWSTR(buf.as_mut_ptr()), len + 1) };
+	assert_ne(copied, 0, "failed to get window text").with_last_win32_err()?;
+	buf.truncate(copied.try_into().expect("invalid window text length"));
+	Ok(String::from_utf16_lossy(&buf))
+}
+
+pub fn set_window_text(h_window: HWND, text: &str) -> Result<()> {
+	assert_eq(
+		unsafe { SetWindowTextW(h_window, text.to_wide().as_pwstr()) }.as_bool(),
+		true,
+		"failed to set window text",
+	)
+	.with_last_win32_err()
+}
+
+/// Give a control the default GUI font, which `win_proc` only sets to the children created on `WM_CREATE`.
+pub fn set_default_font(h_window: HWND) {
+	unsafe {
+		let font = GetStockObject(DEFAULT_GUI_FONT);
+		SendMessageW(h_window, message::Setfont, font as _, 1);
+	}
 }
 
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
 pub mod class_style {
+	use crate::flags::flags;
 	use windows::Win32::UI::WindowsAndMessaging::*;
-	pub struct Type(pub WNDCLASS_STYLES);
-
-	pub const VRedraw: Type = Type(CS_VREDRAW);
-	pub const HRedraw: Type = Type(CS_HREDRAW);
-	pub const Dblclks: Type = Type(CS_DBLCLKS);
-	pub const OwndC: Type = Type(CS_OWNDC);
-	pub const ClassDc: Type = Type(CS_CLASSDC);
-	pub const ParentDc: Type = Type(CS_PARENTDC);
-	pub const NoClose: Type = Type(CS_NOCLOSE);
-	pub const SaveBits: Type = Type(CS_SAVEBITS);
-	pub const ByteAlignClient: Type = Type(CS_BYTEALIGNCLIENT);
-	pub const ByteAlignWindow: Type = Type(CS_BYTEALIGNWINDOW);
-	pub const GlobalClass: Type = Type(CS_GLOBALCLASS);
-	pub const Ime: Type = Type(CS_IME);
-	pub const DropShadow: Type = Type(CS_DROPSHADOW);
+
+	flags! {
+		pub struct Type(pub WNDCLASS_STYLES);
+
+		VRedraw = CS_VREDRAW;
+		HRedraw = CS_HREDRAW;
+		Dblclks = CS_DBLCLKS;
+		OwndC = CS_OWNDC;
+		ClassDc = CS_CLASSDC;
+		ParentDc = CS_PARENTDC;
+		NoClose = CS_NOCLOSE;
+		SaveBits = CS_SAVEBITS;
+		ByteAlignClient = CS_BYTEALIGNCLIENT;
+		ByteAlignWindow = CS_BYTEALIGNWINDOW;
+		GlobalClass = CS_GLOBALCLASS;
+		Ime = CS_IME;
+		DropShadow = CS_DROPSHADOW;
+	}
 }
 
 #[allow(dead_code)]
@@ -698,71 +896,136 @@ pub mod show_cmd {
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
 pub mod ex_style {
+	use crate::flags::flags;
 	use windows::Win32::UI::WindowsAndMessaging::*;
-	pub struct Type(pub WINDOW_EX_STYLE);
-
-	pub const DlgModalFrame: Type = Type(WS_EX_DLGMODALFRAME);
-	pub const NoParentNotify: Type = Type(WS_EX_NOPARENTNOTIFY);
-	pub const TopMost: Type = Type(WS_EX_TOPMOST);
-	pub const AcceptFiles: Type = Type(WS_EX_ACCEPTFILES);
-	pub const Transparent: Type = Type(WS_EX_TRANSPARENT);
-	pub const MdiChild: Type = Type(WS_EX_MDICHILD);
-	pub const ToolWindow: Type = Type(WS_EX_TOOLWINDOW);
-	pub const WindowEdge: Type = Type(WS_EX_WINDOWEDGE);
-	pub const ClientEdge: Type = Type(WS_EX_CLIENTEDGE);
-	pub const ContextHelp: Type = Type(WS_EX_CONTEXTHELP);
-	pub const Right: Type = Type(WS_EX_RIGHT);
-	pub const Left: Type = Type(WS_EX_LEFT);
-	pub const RtlReading: Type = Type(WS_EX_RTLREADING);
-	pub const LtrReading: Type = Type(WS_EX_LTRREADING);
-	pub const LeftScrollbar: Type = Type(WS_EX_LEFTSCROLLBAR);
-	pub const RightScrollbar: Type = Type(WS_EX_RIGHTSCROLLBAR);
-	pub const ControlParent: Type = Type(WS_EX_CONTROLPARENT);
-	pub const StaticEdge: Type = Type(WS_EX_STATICEDGE);
-	pub const AppWindow: Type = Type(WS_EX_APPWINDOW);
-	pub const OverlappedWindow: Type = Type(WS_EX_OVERLAPPEDWINDOW);
-	pub const PaletteWindow: Type = Type(WS_EX_PALETTEWINDOW);
-	pub const Layered: Type = Type(WS_EX_LAYERED);
-	pub const NoInheritLayout: Type = Type(WS_EX_NOINHERITLAYOUT);
-	pub const NoRedirectionBitmap: Type = Type(WS_EX_NOREDIRECTIONBITMAP);
-	pub const LayoutRtl: Type = Type(WS_EX_LAYOUTRTL);
-	pub const Composited: Type = Type(WS_EX_COMPOSITED);
-	pub const NoActivate: Type = Type(WS_EX_NOACTIVATE);
+
+	flags! {
+		pub struct Type(pub WINDOW_EX_STYLE);
+
+		DlgModalFrame = WS_EX_DLGMODALFRAME;
+		NoParentNotify = WS_EX_NOPARENTNOTIFY;
+		TopMost = WS_EX_TOPMOST;
+		AcceptFiles = WS_EX_ACCEPTFILES;
+		Transparent = WS_EX_TRANSPARENT;
+		MdiChild = WS_EX_MDICHILD;
+		ToolWindow = WS_EX_TOOLWINDOW;
+		WindowEdge = WS_EX_WINDOWEDGE;
+		ClientEdge = WS_EX_CLIENTEDGE;
+		ContextHelp = WS_EX_CONTEXTHELP;
+		Right = WS_EX_RIGHT;
+		Left = WS_EX_LEFT;
+		RtlReading = WS_EX_RTLREADING;
+		LtrReading = WS_EX_LTRREADING;
+		LeftScrollbar = WS_EX_LEFTSCROLLBAR;
+		RightScrollbar = WS_EX_RIGHTSCROLLBAR;
+		ControlParent = WS_EX_CONTROLPARENT;
+		StaticEdge = WS_EX_STATICEDGE;
+		AppWindow = WS_EX_APPWINDOW;
+		OverlappedWindow = WS_EX_OVERLAPPEDWINDOW;
+		PaletteWindow = WS_EX_PALETTEWINDOW;
+		Layered = WS_EX_LAYERED;
+		NoInheritLayout = WS_EX_NOINHERITLAYOUT;
+		NoRedirectionBitmap = WS_EX_NOREDIRECTIONBITMAP;
+		LayoutRtl = WS_EX_LAYOUTRTL;
+		Composited = WS_EX_COMPOSITED;
+		NoActivate = WS_EX_NOACTIVATE;
+	}
 }
 
 #[allow(dead_code)]
 #[allow(non_upper_case_globals)]
 pub mod style {
+	use crate::flags::flags;
 	use windows::Win32::UI::WindowsAndMessaging::*;
-	#[derive(Copy, Clone)]
-	pub struct Type(pub WINDOW_STYLE);
-
-	pub const Overlapped: Type = Type(WS_OVERLAPPED);
-	pub const Popup: Type = Type(WS_POPUP);
-	pub const Child: Type = Type(WS_CHILD);
-	pub const Minimize: Type = Type(WS_MINIMIZE);
-	pub const Visible: Type = Type(WS_VISIBLE);
-	pub const Disabled: Type = Type(WS_DISABLED);
-	pub const ClipSiblings: Type = Type(WS_CLIPSIBLINGS);
-	pub const ClipChildren: Type = Type(WS_CLIPCHILDREN);
-	pub const Maximize: Type = Type(WS_MAXIMIZE);
-	pub const Caption: Type = Type(WS_CAPTION);
-	pub const Border: Type = Type(WS_BORDER);
-	pub const DlgFrame: Type = Type(WS_DLGFRAME);
-	pub const VScroll: Type = Type(WS_VSCROLL);
-	pub const HScroll: Type = Type(WS_HSCROLL);
-	pub const SysMenu: Type = Type(WS_SYSMENU);
-	pub const ThickFrame: Type = Type(WS_THICKFRAME);
-	pub const Group: Type = Type(WS_GROUP);
-	pub const Tabstop: Type = Type(WS_TABSTOP);
-	pub const MinimizeBox: Type = Type(WS_MINIMIZEBOX);
-	pub const MaximizeBox: Type = Type(WS_MAXIMIZEBOX);
-	pub const Tiled: Type = Type(WS_TILED);
-	pub const Iconic: Type = Type(WS_ICONIC);
-	pub const SizeBox: Type = Type(WS_SIZEBOX);
-	pub const TiledWindow: Type = Type(WS_TILEDWINDOW);
-	pub const OverlappedWindow: Type = Type(WS_OVERLAPPEDWINDOW);
-	pub const PopupWindow: Type = Type(WS_POPUPWINDOW);
-	pub const ChildWindow: Type = Type(WS_CHILDWINDOW);
-	pub const ActiveCaption: Type = Type(WS_ACTIVECAPTION);
+
+	flags! {
+		pub struct Type(pub WINDOW_STYLE);
+
+		Overlapped = WS_OVERLAPPED;
+		Popup = WS_POPUP;
+		Child = WS_CHILD;
+		Minimize = WS_MINIMIZE;
+		Visible = WS_VISIBLE;
+		Disabled = WS_DISABLED;
+		ClipSiblings = WS_CLIPSIBLINGS;
+		ClipChildren = WS_CLIPCHILDREN;
+		Maximize = WS_MAXIMIZE;
+		Caption = WS_CAPTION;
+		Border = WS_BORDER;
+		DlgFrame = WS_DLGFRAME;
+		VScroll = WS_VSCROLL;
+		HScroll = WS_HSCROLL;
+		SysMenu = WS_SYSMENU;
+		ThickFrame = WS_THICKFRAME;
+		Group = WS_GROUP;
+		Tabstop = WS_TABSTOP;
+		MinimizeBox = WS_MINIMIZEBOX;
+		MaximizeBox = WS_MAXIMIZEBOX;
+		Tiled = WS_TILED;
+		Iconic = WS_ICONIC;
+		SizeBox = WS_SIZEBOX;
+		OverlappedWindow = WS_OVERLAPPEDWINDOW;
+		TiledWindow = WS_TILEDWINDOW;
+		PopupWindow = WS_POPUPWINDOW;
+		ChildWindow = WS_CHILDWINDOW;
+		ActiveCaption = WS_ACTIVECAPTION;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Default)]
+	struct Typing {
+		h_window: HWND,
+		typed: String,
+	}
+
+	impl WindowBase for Typing {
+		fn init_state(_h_instance: HINSTANCE) -> Self {
+			Self::default()
+		}
+
+		fn h_instance(&self) -> HINSTANCE {
+			Default::default()
+		}
+
+		fn set_h_window(&mut self, h_window: HWND) {
+			self.h_window = h_window;
+		}
+
+		fn h_window(&self) -> HWND {
+			self.h_window
+		}
+	}
+
+	impl WindowHandler for Typing {
+		fn on_char(&mut self, c: char) -> Result<MessageAction> {
+			self.typed.push(c);
+			Ok(MessageAction::FullyHandled)
+		}
+	}
+
+	fn send_char(window: &mut Typing, code: u16, lparam: LPARAM) -> MessageAction {
+		let message = Message::decode(message::Char, code.into(), lparam);
+		window.on_input_message(message).unwrap()
+	}
+
+	#[test]
+	fn on_char_repeat_and_alt() {
+		let mut window = Typing::default();
+		// 'a' pressed, then held down: the next messages have the previous key state bit set
+		for lparam in [0x001e_0001, 0x401e_0001, 0x401e_0003] {
+			let action = send_char(&mut window, 'a' as u16, lparam);
+			assert!(matches!(action, MessageAction::FullyHandled));
+		}
+		// ALT+0233 on the numpad, the character comes with ALT still down
+		send_char(&mut window, 'é' as u16, 0x2038_0001);
+		// surrogate pair of an auto-repeated emoji
+		for code in "💖".encode_utf16() {
+			send_char(&mut window, code, 0x4000_0001);
+		}
+		assert_eq!(window.typed, "aaaé💖");
+	}
 }
@@ -0,0 +1,158 @@
+// Closures sent from other threads to a window handler, queued per window and run by its win_proc on the UI thread.
+
+use std::{
+	collections::{BTreeMap, VecDeque},
+	fmt,
+	marker::PhantomData,
+	sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use windows::Win32::{
+	Foundation::HWND,
+	System::Threading::GetCurrentThreadId,
+	UI::WindowsAndMessaging::{GetWindowThreadProcessId, PostMessageW, SendMessageW, WM_APP},
+};
+
+use super::message;
+use crate::assert::{assert_eq, Result, WithLastWin32Error};
+
+/// Message telling a window to run the closures queued by its `AppHandle`s. Private to this crate, apps can use the
+/// rest of the `WM_APP` range.
+pub const APP_HANDLE_MESSAGE: message::Type = WM_APP + 0x3FFF;
+
+// gets a pointer to the window handler
+type Task = Box<dyn FnOnce(*mut ()) + Send>;
+
+// None once the window is destroyed
+type TaskQueue = Arc<Mutex<Option<VecDeque<Task>>>>;
+
+static QUEUES: Mutex<BTreeMap<HWND, TaskQueue>> = Mutex::new(BTreeMap::new());
+
+/// `Send + Clone` handle running closures on the UI thread with the `WindowHandler` of a window, e.g. to hand the
+/// result of background work back to the window. Obtained with `WindowHandler::app_handle`.
+///
+/// ```ignore
+/// let app = self.app_handle();
+/// std::thread::spawn(move || {
+///     let text = std::fs::read_to_string(path);
+///     app.post(move |editor: &mut Editor| editor.show_file(text))
+/// });
+/// ```
+pub struct AppHandle<H> {
+	h_window: HWND,
+	queue: TaskQueue,
+	// the handle is Send whatever H is, H is only touched on the UI thread
+	handler: PhantomData<fn(&mut H)>,
+}
+
+impl<H> AppHandle<H> {
+	/// `H` must be the handler type of the `win_proc` of `h_window`.
+	pub(crate) fn for_window(h_window: HWND) -> Self {
+		let queue = lock(&QUEUES)
+			.entry(h_window)
+			.or_insert_with(|| Arc::new(Mutex::new(Some(VecDeque::new()))))
+			.clone();
+		Self {
+			h_window,
+			queue,
+			handler: PhantomData,
+		}
+	}
+
+	pub fn h_window(&self) -> HWND {
+		self.h_window
+	}
+
+	/// Queue `f` and return right away. Fails once the window is destroyed.
+	pub fn post(&self, f: impl FnOnce(&mut H) + Send + 'static) -> Result<()> {
+		self.push(f)?;
+		assert_eq(
+			unsafe { PostMessageW(self.h_window, APP_HANDLE_MESSAGE, 0, 0) }.as_bool(),
+			true,
+			"failed to post to the window",
+		)
+		.with_last_win32_err()
+	}
+
+	/// Run `f` and return its result, blocking until the UI thread ran it. Fails on the UI thread of the window, where
+	/// the caller is usually a handler method already borrowing the handler: use `post` there.
+	pub fn send<R: Send + 'static>(
+		&self,
+		f: impl FnOnce(&mut H) -> R + Send + 'static,
+	) -> Result<R> {
+		let ui_thread = unsafe { GetWindowThreadProcessId(self.h_window, std::ptr::null_mut()) };
+		if ui_thread == unsafe { GetCurrentThreadId() } {
+			return Err("send called on the UI thread of the window, use post".into());
+		}
+		let (sender, receiver) = mpsc::channel();
+		self.push(move |handler: &mut H| {
+			// the receiver is only gone if `send` already returned
+			let _ = sender.send(f(handler));
+		})?;
+		// returns once the window ran its queue, which includes `f`
+		unsafe { SendMessageW(self.h_window, APP_HANDLE_MESSAGE, 0, 0) };
+		receiver
+			.recv()
+			.map_err(|_| "window destroyed before running the closure".into())
+	}
+
+	fn push(&self, f: impl FnOnce(&mut H) + Send + 'static) -> Result<()> {
+		let task: Task = Box::new(move |handler| f(unsafe { &mut *(handler as *mut H) }));
+		match lock(&self.queue).as_mut() {
+			Some(tasks) => {
+				tasks.push_back(task);
+				Ok(())
+			}
+			None => Err("window destroyed".into()),
+		}
+	}
+}
+
+impl<H> Clone for AppHandle<H> {
+	fn clone(&self) -> Self {
+		Self {
+			h_window: self.h_window,
+			queue: self.queue.clone(),
+			handler: PhantomData,
+		}
+	}
+}
+
+impl<H> fmt::Debug for AppHandle<H> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("AppHandle")
+			.field("h_window", &self.h_window)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Run the closures queued for `h_window`. `handler` points to the window handler of the type its handles were
+/// created for.
+pub(crate) unsafe fn run_queued(h_window: HWND, handler: *mut ()) {
+	let queue = match lock(&QUEUES).get(&h_window) {
+		Some(queue) => queue.clone(),
+		None => return,
+	};
+	loop {
+		// not locked while a closure runs, it may post more
+		let task = match lock(&queue).as_mut().and_then(|tasks| tasks.pop_front()) {
+			Some(task) => task,
+			None => return,
+		};
+		task(handler);
+	}
+}
+
+/// Drop the closures still queued for a destroyed window, any later `post` or `send` fails.
+pub(crate) fn close_queue(h_window: HWND) {
+	let queue = lock(&QUEUES).remove(&h_window);
+	if let Some(queue) = queue {
+		let tasks = lock(&queue).take();
+		// dropped without the lock, a pending `send` gets an error
+		drop(tasks);
+	}
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
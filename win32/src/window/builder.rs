@@ -0,0 +1,597 @@
+// Everything needed to create a window: its class, styles, frame, parent or owner, and the tracking size applied on
+// WM_GETMINMAXINFO by `WindowHandler::win_proc`.
+
+use std::{
+	cell::{Cell, RefCell},
+	collections::BTreeMap,
+	ffi::c_void,
+	fmt,
+};
+
+use windows::Win32::{
+	Foundation::{HINSTANCE, HWND, LPARAM, RECT},
+	UI::WindowsAndMessaging::{
+		AdjustWindowRectEx, CreateWindowExW, GetSystemMetrics, GetWindowRect, ShowWindow,
+		SystemParametersInfoW, CW_USEDEFAULT, HMENU, MINMAXINFO, SM_CXSCREEN, SM_CYSCREEN,
+		SPI_GETWORKAREA,
+	},
+};
+
+use super::{class_style, ex_style, show_cmd, style};
+use crate::{
+	assert::{assert_ne, Result, WithLastWin32Error},
+	class::WindowClass,
+	cursor,
+	icon::Icon,
+	wide_string::ToWide,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+	/// `CW_USEDEFAULT`, the system picks the position of top-level windows, children are put at 0, 0.
+	Default,
+	At {
+		x: i32,
+		y: i32,
+	},
+	/// Centered on the work area of the primary monitor.
+	CenterScreen,
+	CenterOwner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Size {
+	Outer { width: i32, height: i32 },
+	// the outer size is computed with AdjustWindowRectEx
+	Client { width: i32, height: i32 },
+}
+
+/// Style combinations `CreateWindowExW` would reject or silently misinterpret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowBuilderError {
+	ChildWithoutParent,
+	ParentWithoutChild,
+	ChildAndPopup,
+	ChildWithOwner,
+	ChildWithMenu,
+	IdWithoutChild,
+	CenterOwnerWithoutOwner,
+	MinSizeAboveMaxSize,
+	TrackSizeWithoutHandler,
+}
+
+impl fmt::Display for WindowBuilderError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let msg = match self {
+			Self::ChildWithoutParent => "a `Child` window needs a parent",
+			Self::ParentWithoutChild => {
+				"a window with a parent needs the `Child` style, or use an owner instead"
+			}
+			Self::ChildAndPopup => "`Child` and `Popup` styles are exclusive",
+			Self::ChildWithOwner => "a `Child` window has a parent, not an owner",
+			Self::ChildWithMenu => "a `Child` window cannot have a menu, use an id instead",
+			Self::IdWithoutChild => "only `Child` windows have an id",
+			Self::CenterOwnerWithoutOwner => "cannot center on the owner of a window without owner",
+			Self::MinSizeAboveMaxSize => "min size larger than the max size",
+			Self::TrackSizeWithoutHandler => {
+				"min and max sizes are applied by `WindowHandler` windows only, see `WindowHandler::create`"
+			}
+		};
+		f.write_str(msg)
+	}
+}
+
+impl std::error::Error for WindowBuilderError {}
+
+/// Builder replacing the arguments of `CreateWindowExW`. The class settings are only used by `WindowHandler::create`,
+/// which registers the class.
+///
+/// ```ignore
+/// let app = App::create(
+///     WindowBuilder::new("MainWindow")
+///         .text("Notes")
+///         .client_size(640, 480)
+///         .min_size(320, 240)
+///         .center(),
+/// )?;
+/// ```
+pub struct WindowBuilder {
+	class: WindowClass,
+	text: String,
+	style: style::Type,
+	ex_style: ex_style::Type,
+	position: Position,
+	size: Size,
+	parent: Option<HWND>,
+	owner: Option<HWND>,
+	menu: Option<HMENU>,
+	id: Option<u16>,
+	h_instance: Option<HINSTANCE>,
+	min_size: Option<(i32, i32)>,
+	max_size: Option<(i32, i32)>,
+	show: Option<show_cmd::Type>,
+}
+
+impl WindowBuilder {
+	/// Top-level overlapped window, shown once created.
+	pub fn new(class_name: &str) -> Self {
+		Self {
+			class: WindowClass::new(class_name),
+			text: String::new(),
+			style: style::OverlappedWindow,
+			ex_style: ex_style::OverlappedWindow,
+			position: Position::Default,
+			size: Size::Outer {
+				width: 500,
+				height: 400,
+			},
+			parent: None,
+			owner: None,
+			menu: None,
+			id: None,
+			h_instance: None,
+			min_size: None,
+			max_size: None,
+			show: Some(show_cmd::Show),
+		}
+	}
+
+	/// Visible child window of `parent`, e.g. a control of a system class such as `"BUTTON"`.
+	pub fn child(class_name: &str, parent: HWND) -> Self {
+		Self {
+			style: style::Child | style::Visible,
+			ex_style: ex_style::Left,
+			size: Size::Outer {
+				width: 0,
+				height: 0,
+			},
+			parent: Some(parent),
+			show: None,
+			..Self::new(class_name)
+		}
+	}
+
+	pub fn text(mut self, text: &str) -> Self {
+		self.text = text.to_owned();
+		self
+	}
+
+	pub fn style(mut self, style: style::Type) -> Self {
+		self.style = style;
+		self
+	}
+
+	pub fn ex_style(mut self, ex_style: ex_style::Type) -> Self {
+		self.ex_style = ex_style;
+		self
+	}
+
+	/// Stays above non-topmost windows, even when deactivated.
+	pub fn topmost(mut self, topmost: bool) -> Self {
+		self.ex_style.set(ex_style::TopMost, topmost);
+		self
+	}
+
+	/// Floating toolbar: small caption, no taskbar button.
+	pub fn tool_window(self) -> Self {
+		self.style(style::Popup | style::Caption | style::SysMenu)
+			.ex_style(ex_style::ToolWindow | ex_style::WindowEdge)
+	}
+
+	pub fn position(mut self, position: Position) -> Self {
+		self.position = position;
+		self
+	}
+
+	pub fn at(self, x: i32, y: i32) -> Self {
+		self.position(Position::At { x, y })
+	}
+
+	pub fn center(self) -> Self {
+		self.position(Position::CenterScreen)
+	}
+
+	pub fn center_on_owner(self) -> Self {
+		self.position(Position::CenterOwner)
+	}
+
+	/// Size of the window frame, borders and title bar included.
+	pub fn size(mut self, width: i32, height: i32) -> Self {
+		self.size = Size::Outer { width, height };
+		self
+	}
+
+	/// Size of the client area, the frame is sized around it.
+	pub fn client_size(mut self, width: i32, height: i32) -> Self {
+		self.size = Size::Client { width, height };
+		self
+	}
+
+	/// Smallest size the user can resize the window to, frame included. Only for `WindowHandler` windows.
+	pub fn min_size(mut self, width: i32, height: i32) -> Self {
+		self.min_size = Some((width, height));
+		self
+	}
+
+	/// Largest size the user can resize the window to, frame included. Only for `WindowHandler` windows.
+	pub fn max_size(mut self, width: i32, height: i32) -> Self {
+		self.max_size = Some((width, height));
+		self
+	}
+
+	/// Makes the window a child of `parent`, it needs the `Child` style.
+	pub fn parent(mut self, parent: HWND) -> Self {
+		self.parent = Some(parent);
+		self
+	}
+
+	/// A top-level window stays above its owner and is hidden when the owner is minimized.
+	pub fn owner(mut self, owner: HWND) -> Self {
+		self.owner = Some(owner);
+		self
+	}
+
+	/// Menu bar of a top-level window.
+	pub fn menu(mut self, menu: HMENU) -> Self {
+		self.menu = Some(menu);
+		self
+	}
+
+	/// Control id of a child window, sent with its notifications in `WM_COMMAND`.
+	pub fn id(mut self, id: u16) -> Self {
+		self.id = Some(id);
+		self
+	}
+
+	/// Module of the window class, the current module by default for `WindowHandler` windows.
+	pub fn h_instance(mut self, h_instance: HINSTANCE) -> Self {
+		self.h_instance = Some(h_instance);
+		self
+	}
+
+	/// How the window is shown once created, e.g. `show_cmd::ShowMaximized`. Child windows are visible with the
+	/// `Visible` style instead.
+	pub fn show(mut self, show: show_cmd::Type) -> Self {
+		self.show = Some(show);
+		self
+	}
+
+	/// Keep the window hidden once created.
+	pub fn hidden(mut self) -> Self {
+		self.show = None;
+		self
+	}
+
+	pub fn class_style(mut self, style: class_style::Type) -> Self {
+		self.class = self.class.style(style);
+		self
+	}
+
+	pub fn icon(mut self, icon: Icon) -> Self {
+		self.class = self.class.icon(icon);
+		self
+	}
+
+	pub fn cursor(mut self, cursor: cursor::Type) -> Self {
+		self.class = self.class.cursor(cursor);
+		self
+	}
+
+	/// System color index plus one, e.g. `COLOR_WINDOW + 1`.
+	pub fn bg_brush(mut self, bg_brush: u32) -> Self {
+		self.class = self.class.bg_brush(bg_brush);
+		self
+	}
+
+	pub fn class_name(&self) -> &str {
+		self.class.name()
+	}
+
+	/// Check the builder for style combinations that cannot work, done by `create` before `CreateWindowExW`.
+	pub fn validate(&self) -> std::result::Result<(), WindowBuilderError> {
+		use WindowBuilderError::*;
+
+		let child = self.style.contains(style::Child);
+		let popup = self.style.contains(style::Popup);
+		if child && popup {
+			return Err(ChildAndPopup);
+		}
+		if child {
+			if self.parent.is_none() {
+				return Err(ChildWithoutParent);
+			}
+			if self.owner.is_some() {
+				return Err(ChildWithOwner);
+			}
+			if self.menu.is_some() {
+				return Err(ChildWithMenu);
+			}
+		} else {
+			if self.parent.is_some() {
+				return Err(ParentWithoutChild);
+			}
+			if self.id.is_some() {
+				return Err(IdWithoutChild);
+			}
+		}
+		if self.position == Position::CenterOwner && self.owner.is_none() {
+			return Err(CenterOwnerWithoutOwner);
+		}
+		if let (Some((min_w, min_h)), Some((max_w, max_h))) = (self.min_size, self.max_size) {
+			if min_w > max_w || min_h > max_h {
+				return Err(MinSizeAboveMaxSize);
+			}
+		}
+		Ok(())
+	}
+
+	/// Create the window, e.g. a control of a system class. Windows of a `WindowHandler` are created with
+	/// `WindowHandler::create` instead, min and max sizes are rejected since the window procedure wouldn't apply them.
+	pub fn create(self) -> Result<HWND> {
+		if self.min_size.is_some() || self.max_size.is_some() {
+			return Err(WindowBuilderError::TrackSizeWithoutHandler.into());
+		}
+		self.create_with_param(std::ptr::null())
+	}
+
+	/// Split the class settings from the window ones, for the class to be registered first.
+	pub(crate) fn take_class(mut self) -> (WindowClass, Self) {
+		let placeholder = WindowClass::new(self.class.name());
+		let class = std::mem::replace(&mut self.class, placeholder);
+		(class, self)
+	}
+
+	/// `param` is given to the window procedure as `CREATESTRUCTW::lpCreateParams` on `WM_CREATE`.
+	pub(crate) fn create_with_param(self, param: *const c_void) -> Result<HWND> {
+		self.validate()?;
+
+		let (x, y, width, height) = self.frame();
+		let parent = self.parent.or(self.owner);
+		// the menu parameter of a child window is its id
+		let menu = match self.id {
+			Some(id) => id as HMENU,
+			None => self.menu.unwrap_or_default(),
+		};
+		if self.min_size.is_some() || self.max_size.is_some() {
+			let size = TrackSize {
+				min: self.min_size,
+				max: self.max_size,
+			};
+			PENDING_TRACK_SIZE.with(|pending| pending.set(Some(size)));
+		}
+		let h_window = unsafe {
+			CreateWindowExW(
+				self.ex_style.0,
+				self.class.name().to_wide().as_pwstr(),
+				self.text.to_wide().as_pwstr(),
+				self.style.0,
+				x,
+				y,
+				width,
+				height,
+				parent,
+				menu,
+				self.h_instance,
+				param,
+			)
+		};
+		// still there if the class doesn't use `WindowHandler::win_proc`, it mustn't go to the next window
+		PENDING_TRACK_SIZE.with(Cell::take);
+		assert_ne(
+			h_window,
+			0,
+			format!("failed to create window for class {:?}", self.class.name()).as_str(),
+		)
+		.with_last_win32_err()?;
+
+		if let Some(show) = self.show {
+			unsafe { ShowWindow(h_window, show) };
+		}
+		Ok(h_window)
+	}
+
+	// x, y, width and height given to CreateWindowExW
+	fn frame(&self) -> (i32, i32, i32, i32) {
+		let (mut width, mut height) = match self.size {
+			Size::Outer { width, height } => (width, height),
+			Size::Client { width, height } => {
+				let mut rect = RECT {
+					left: 0,
+					top: 0,
+					right: width,
+					bottom: height,
+				};
+				let ok = unsafe {
+					AdjustWindowRectEx(
+						&mut rect,
+						self.style.0,
+						self.menu.is_some(),
+						self.ex_style.0,
+					)
+				};
+				match ok.as_bool() {
+					true => (rect.right - rect.left, rect.bottom - rect.top),
+					false => (width, height),
+				}
+			}
+		};
+		if let Some((min_w, min_h)) = self.min_size {
+			width = width.max(min_w);
+			height = height.max(min_h);
+		}
+		if let Some((max_w, max_h)) = self.max_size {
+			width = width.min(max_w);
+			height = height.min(max_h);
+		}
+
+		let center_in = |area: RECT| {
+			(
+				area.left + (area.right - area.left - width) / 2,
+				area.top + (area.bottom - area.top - height) / 2,
+			)
+		};
+		let (x, y) = match self.position {
+			Position::Default => (CW_USEDEFAULT, CW_USEDEFAULT),
+			Position::At { x, y } => (x, y),
+			Position::CenterScreen => center_in(work_area()),
+			Position::CenterOwner => {
+				let mut owner_rect: RECT = Default::default();
+				let owner = self.owner.unwrap_or_default();
+				match unsafe { GetWindowRect(owner, &mut owner_rect) }.as_bool() {
+					true => center_in(owner_rect),
+					false => center_in(work_area()),
+				}
+			}
+		};
+		(x, y, width, height)
+	}
+}
+
+// screen minus the taskbar
+fn work_area() -> RECT {
+	let mut area: RECT = Default::default();
+	let ok = unsafe { SystemParametersInfoW(SPI_GETWORKAREA, 0, &mut area as *mut _ as _, 0) };
+	if !ok.as_bool() {
+		area.right = unsafe { GetSystemMetrics(SM_CXSCREEN) };
+		area.bottom = unsafe { GetSystemMetrics(SM_CYSCREEN) };
+	}
+	area
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TrackSize {
+	min: Option<(i32, i32)>,
+	max: Option<(i32, i32)>,
+}
+
+thread_local! {
+	static TRACK_SIZES: RefCell<BTreeMap<HWND, TrackSize>> = const { RefCell::new(BTreeMap::new()) };
+	// tracking size of the window CreateWindowExW is creating, its handle isn't known yet
+	static PENDING_TRACK_SIZE: Cell<Option<TrackSize>> = const { Cell::new(None) };
+}
+
+/// Give the tracking size of the window being created to `h_window`. Called on its first `WM_GETMINMAXINFO`, sent
+/// before `WM_NCCREATE` by resizable windows, or else on `WM_NCCREATE`, before `CreateWindowExW` returns.
+pub(crate) fn claim_track_size(h_window: HWND) {
+	if let Some(size) = PENDING_TRACK_SIZE.with(Cell::take) {
+		TRACK_SIZES.with(|sizes| sizes.borrow_mut().insert(h_window, size));
+	}
+}
+
+/// Write the tracking size of `h_window` to the `MINMAXINFO` of a `WM_GETMINMAXINFO`, returns false if the window has
+/// none.
+pub(crate) unsafe fn apply_track_size(h_window: HWND, lparam: LPARAM) -> bool {
+	claim_track_size(h_window);
+	TRACK_SIZES.with(|sizes| {
+		let sizes = sizes.borrow();
+		let size = match sizes.get(&h_window) {
+			Some(size) => size,
+			None => return false,
+		};
+		let info = &mut *(lparam as *mut MINMAXINFO);
+		if let Some((width, height)) = size.min {
+			info.ptMinTrackSize.x = width;
+			info.ptMinTrackSize.y = height;
+		}
+		if let Some((width, height)) = size.max {
+			info.ptMaxTrackSize.x = width;
+			info.ptMaxTrackSize.y = height;
+		}
+		true
+	})
+}
+
+pub(crate) fn forget_track_size(h_window: HWND) {
+	TRACK_SIZES.with(|sizes| sizes.borrow_mut().remove(&h_window));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn track_size(h_window: HWND) -> Option<TrackSize> {
+		TRACK_SIZES.with(|sizes| sizes.borrow().get(&h_window).copied())
+	}
+
+	#[test]
+	fn validate() {
+		use WindowBuilderError::*;
+
+		let cases = [
+			(
+				WindowBuilder::new("Test").style(style::Child),
+				ChildWithoutParent,
+			),
+			(WindowBuilder::new("Test").parent(1), ParentWithoutChild),
+			(
+				WindowBuilder::child("Test", 1).style(style::Child | style::Popup),
+				ChildAndPopup,
+			),
+			(WindowBuilder::child("Test", 1).owner(2), ChildWithOwner),
+			(WindowBuilder::child("Test", 1).menu(3), ChildWithMenu),
+			(WindowBuilder::new("Test").id(7), IdWithoutChild),
+			(
+				WindowBuilder::new("Test").center_on_owner(),
+				CenterOwnerWithoutOwner,
+			),
+			(
+				WindowBuilder::new("Test")
+					.min_size(300, 100)
+					.max_size(200, 200),
+				MinSizeAboveMaxSize,
+			),
+			(
+				WindowBuilder::new("Test")
+					.min_size(100, 300)
+					.max_size(200, 200),
+				MinSizeAboveMaxSize,
+			),
+		];
+		for (builder, e) in cases {
+			assert_eq!(builder.validate(), Err(e));
+		}
+
+		for builder in [
+			WindowBuilder::new("Test"),
+			WindowBuilder::new("Test")
+				.style(style::Popup | style::Caption)
+				.owner(2)
+				.center_on_owner()
+				.min_size(100, 100)
+				.max_size(100, 100),
+			WindowBuilder::new("Test").menu(3),
+			WindowBuilder::child("Test", 1).id(7),
+		] {
+			assert_eq!(builder.validate(), Ok(()));
+		}
+	}
+
+	#[test]
+	fn create_rejects_track_size() {
+		for builder in [
+			WindowBuilder::new("Test").min_size(100, 100),
+			WindowBuilder::new("Test").max_size(800, 600),
+		] {
+			let e = builder.create().unwrap_err();
+			let e = e.downcast_ref::<WindowBuilderError>();
+			assert_eq!(e, Some(&WindowBuilderError::TrackSizeWithoutHandler));
+		}
+	}
+
+	#[test]
+	fn claim_pending_track_size() {
+		let size = TrackSize {
+			min: Some((100, 50)),
+			max: None,
+		};
+		PENDING_TRACK_SIZE.with(|pending| pending.set(Some(size)));
+		claim_track_size(1);
+		// only the window being created gets it
+		claim_track_size(2);
+		assert_eq!(track_size(1), Some(size));
+		assert_eq!(track_size(2), None);
+
+		forget_track_size(1);
+		assert_eq!(track_size(1), None);
+	}
+}
@@ -0,0 +1,969 @@
+// Typed window messages. Decoding only splits and sign-extends the WPARAM/LPARAM bits, it never dereferences them, so
+// it can run anywhere.
+
+use windows::Win32::{
+	Foundation::{HWND, LPARAM, WPARAM},
+	UI::WindowsAndMessaging::{
+		MK_CONTROL, MK_LBUTTON, MK_MBUTTON, MK_RBUTTON, MK_SHIFT, MK_XBUTTON1, MK_XBUTTON2,
+		SIZE_MAXHIDE, SIZE_MAXIMIZED, SIZE_MAXSHOW, SIZE_MINIMIZED, SIZE_RESTORED, XBUTTON1,
+		XBUTTON2,
+	},
+};
+
+use super::message;
+use crate::{hiword, loword};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeKind {
+	Restored,
+	Minimized,
+	Maximized,
+	// another window has been restored or maximized
+	MaxShow,
+	MaxHide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+	Left,
+	Right,
+	Middle,
+	X1,
+	X2,
+}
+
+/// Mouse buttons and modifier keys held down when a mouse message was sent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MouseButtons {
+	pub left: bool,
+	pub right: bool,
+	pub middle: bool,
+	pub x1: bool,
+	pub x2: bool,
+	pub shift: bool,
+	pub control: bool,
+}
+
+/// Window message with its parameters decoded. Messages without a variant, or with a field having a value a variant
+/// can't hold such as an unknown size kind, are kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+	Close,
+	Destroy,
+	Quit {
+		exit_code: usize,
+	},
+	Paint,
+	/// New size of the client area.
+	Size {
+		width: u16,
+		height: u16,
+		kind: SizeKind,
+	},
+	/// New position of the client area, in screen coordinates.
+	Move {
+		x: i16,
+		y: i16,
+	},
+	/// `code` is 0 for a menu, 1 for an accelerator, or a notification code of the control `hwnd`.
+	Command {
+		id: u16,
+		code: u16,
+		hwnd: HWND,
+	},
+	KeyDown {
+		vk: u16,
+		repeat: u16,
+		scan: u8,
+		extended: bool,
+		// the key was already down, the message is an auto-repeat
+		previous_down: bool,
+	},
+	KeyUp {
+		vk: u16,
+		scan: u8,
+		extended: bool,
+	},
+	/// Key pressed with ALT down, or F10.
+	SysKeyDown {
+		vk: u16,
+		repeat: u16,
+		scan: u8,
+		extended: bool,
+		alt: bool,
+		previous_down: bool,
+	},
+	SysKeyUp {
+		vk: u16,
+		scan: u8,
+		extended: bool,
+		alt: bool,
+	},
+	/// UTF-16 code unit typed, characters outside of the BMP come as two messages. The flags are those of the key that
+	/// produced it, `previous_down` for auto-repeats and `alt` when typed with ALT down.
+	Char {
+		code: u16,
+		repeat: u16,
+		scan: u8,
+		extended: bool,
+		alt: bool,
+		previous_down: bool,
+		released: bool,
+	},
+	/// Coordinates are relative to the client area.
+	MouseMove {
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	MouseDown {
+		button: MouseButton,
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	MouseUp {
+		button: MouseButton,
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	MouseDoubleClick {
+		button: MouseButton,
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	/// `delta` is a multiple of `WHEEL_DELTA`, positive away from the user. Coordinates are in screen coordinates.
+	MouseWheel {
+		delta: i16,
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	/// Same as `MouseWheel`, positive to the right.
+	MouseHWheel {
+		delta: i16,
+		x: i16,
+		y: i16,
+		buttons: MouseButtons,
+	},
+	SetFocus {
+		previous: HWND,
+	},
+	KillFocus {
+		next: HWND,
+	},
+	/// `callback` is the TIMERPROC given to SetTimer, 0 if none.
+	Timer {
+		id: usize,
+		callback: LPARAM,
+	},
+	/// `area` points to the name of the changed setting, e.g. "ImmersiveColorSet", see `setting_area`.
+	SettingChange {
+		flag: usize,
+		area: LPARAM,
+	},
+	Other {
+		message: message::Type,
+		wparam: WPARAM,
+		lparam: LPARAM,
+	},
+}
+
+impl Message {
+	/// Typed view of a message. Only the documented fields are read, reserved bits and the ones a message doesn't use,
+	/// like the ALT flag of a `WM_KEYDOWN`, are ignored: `encode` gives them back cleared.
+	pub fn decode(message: message::Type, wparam: WPARAM, lparam: LPARAM) -> Self {
+		let other = Message::Other {
+			message,
+			wparam,
+			lparam,
+		};
+		// keyboard and mouse messages only use the low 32 bits of lparam
+		let low = lparam as u32;
+		let (x, y) = (low as u16 as i16, (low >> 16) as u16 as i16);
+
+		match message {
+			message::Close => Message::Close,
+			message::Destroy => Message::Destroy,
+			message::Quit => Message::Quit { exit_code: wparam },
+			message::Paint => Message::Paint,
+			message::Size => {
+				let kind = match wparam as u32 {
+					SIZE_RESTORED => SizeKind::Restored,
+					SIZE_MINIMIZED => SizeKind::Minimized,
+					SIZE_MAXIMIZED => SizeKind::Maximized,
+					SIZE_MAXSHOW => SizeKind::MaxShow,
+					SIZE_MAXHIDE => SizeKind::MaxHide,
+					_ => return other,
+				};
+				Message::Size {
+					width: low as u16,
+					height: (low >> 16) as u16,
+					kind,
+				}
+			}
+			message::Move => Message::Move { x, y },
+			message::Command => Message::Command {
+				id: loword(wparam) as u16,
+				code: hiword(wparam) as u16,
+				hwnd: lparam,
+			},
+			message::KeyDown | message::Syskeydown => {
+				let key = KeyBits::decode(low);
+				let vk = wparam as u16;
+				let (repeat, scan, extended, previous_down) =
+					(key.repeat, key.scan, key.extended, key.previous_down);
+				match message {
+					message::KeyDown => Message::KeyDown {
+						vk,
+						repeat,
+						scan,
+						extended,
+						previous_down,
+					},
+					_ => Message::SysKeyDown {
+						vk,
+						repeat,
+						scan,
+						extended,
+						alt: key.alt,
+						previous_down,
+					},
+				}
+			}
+			message::KeyUp | message::Syskeyup => {
+				let key = KeyBits::decode(low);
+				let (vk, scan, extended) = (wparam as u16, key.scan, key.extended);
+				match message {
+					message::KeyUp => Message::KeyUp { vk, scan, extended },
+					_ => Message::SysKeyUp {
+						vk,
+						scan,
+						extended,
+						alt: key.alt,
+					},
+				}
+			}
+			message::Char => {
+				let key = KeyBits::decode(low);
+				Message::Char {
+					code: wparam as u16,
+					repeat: key.repeat,
+					scan: key.scan,
+					extended: key.extended,
+					alt: key.alt,
+					previous_down: key.previous_down,
+					released: key.released,
+				}
+			}
+			message::MouseMove => Message::MouseMove {
+				x,
+				y,
+				buttons: MouseButtons::decode(wparam),
+			},
+			message::LButtonDown
+			| message::Rbuttondown
+			| message::MButtonDown
+			| message::Xbuttondown
+			| message::LButtonUp
+			| message::Rbuttonup
+			| message::MButtonUp
+			| message::XBUTTONUP
+			| message::LButtonDblclk
+			| message::Rbuttondblclk
+			| message::MButtonDblclk
+			| message::Xbuttondblclk => {
+				let (kind, button) = match mouse_button(message, wparam) {
+					Some(found) => found,
+					None => return other,
+				};
+				// for X buttons, the high word of wparam tells which one and is not part of the key state
+				let buttons = MouseButtons::decode(loword(wparam));
+				match kind {
+					ButtonEvent::Down => Message::MouseDown {
+						button,
+						x,
+						y,
+						buttons,
+					},
+					ButtonEvent::Up => Message::MouseUp {
+						button,
+						x,
+						y,
+						buttons,
+					},
+					ButtonEvent::DoubleClick => Message::MouseDoubleClick {
+						button,
+						x,
+						y,
+						buttons,
+					},
+				}
+			}
+			message::MouseWheel | message::MouseHWheel => {
+				let delta = hiword(wparam) as u16 as i16;
+				let buttons = MouseButtons::decode(loword(wparam));
+				if message == message::MouseWheel {
+					Message::MouseWheel {
+						delta,
+						x,
+						y,
+						buttons,
+					}
+				} else {
+					Message::MouseHWheel {
+						delta,
+						x,
+						y,
+						buttons,
+					}
+				}
+			}
+			message::Setfocus => Message::SetFocus {
+				previous: wparam as HWND,
+			},
+			message::KillFocus => Message::KillFocus {
+				next: wparam as HWND,
+			},
+			message::Timer => Message::Timer {
+				id: wparam,
+				callback: lparam,
+			},
+			message::Settingchange => Message::SettingChange {
+				flag: wparam,
+				area: lparam,
+			},
+			_ => other,
+		}
+	}
+
+	/// Message and parameters `decode` reads back as `self`.
+	pub fn encode(&self) -> (message::Type, WPARAM, LPARAM) {
+		match *self {
+			Message::Close => (message::Close, 0, 0),
+			Message::Destroy => (message::Destroy, 0, 0),
+			Message::Quit { exit_code } => (message::Quit, exit_code, 0),
+			Message::Paint => (message::Paint, 0, 0),
+			Message::Size {
+				width,
+				height,
+				kind,
+			} => {
+				let kind = match kind {
+					SizeKind::Restored => SIZE_RESTORED,
+					SizeKind::Minimized => SIZE_MINIMIZED,
+					SizeKind::Maximized => SIZE_MAXIMIZED,
+					SizeKind::MaxShow => SIZE_MAXSHOW,
+					SizeKind::MaxHide => SIZE_MAXHIDE,
+				};
+				(message::Size, kind as WPARAM, make_lparam(width, height))
+			}
+			Message::Move { x, y } => (message::Move, 0, point_lparam(x, y)),
+			Message::Command { id, code, hwnd } => (message::Command, make_wparam(id, code), hwnd),
+			Message::KeyDown {
+				vk,
+				repeat,
+				scan,
+				extended,
+				previous_down,
+			} => encode_key(
+				message::KeyDown,
+				vk,
+				repeat,
+				scan,
+				extended,
+				false,
+				previous_down,
+			),
+			Message::SysKeyDown {
+				vk,
+				repeat,
+				scan,
+				extended,
+				alt,
+				previous_down,
+			} => encode_key(
+				message::Syskeydown,
+				vk,
+				repeat,
+				scan,
+				extended,
+				alt,
+				previous_down,
+			),
+			Message::KeyUp { vk, scan, extended } => {
+				encode_key(message::KeyUp, vk, 1, scan, extended, false, true)
+			}
+			Message::SysKeyUp {
+				vk,
+				scan,
+				extended,
+				alt,
+			} => encode_key(message::Syskeyup, vk, 1, scan, extended, alt, true),
+			Message::Char {
+				code,
+				repeat,
+				scan,
+				extended,
+				alt,
+				previous_down,
+				released,
+			} => {
+				let key = KeyBits {
+					repeat,
+					scan,
+					extended,
+					alt,
+					previous_down,
+					released,
+				};
+				(message::Char, code.into(), key.encode())
+			}
+			Message::MouseMove { x, y, buttons } => {
+				(message::MouseMove, buttons.encode(), point_lparam(x, y))
+			}
+			Message::MouseDown {
+				button,
+				x,
+				y,
+				buttons,
+			} => encode_button(ButtonEvent::Down, button, x, y, buttons),
+			Message::MouseUp {
+				button,
+				x,
+				y,
+				buttons,
+			} => encode_button(ButtonEvent::Up, button, x, y, buttons),
+			Message::MouseDoubleClick {
+				button,
+				x,
+				y,
+				buttons,
+			} => encode_button(ButtonEvent::DoubleClick, button, x, y, buttons),
+			Message::MouseWheel {
+				delta,
+				x,
+				y,
+				buttons,
+			} => (
+				message::MouseWheel,
+				make_wparam(buttons.encode() as u16, delta as u16),
+				point_lparam(x, y),
+			),
+			Message::MouseHWheel {
+				delta,
+				x,
+				y,
+				buttons,
+			} => (
+				message::MouseHWheel,
+				make_wparam(buttons.encode() as u16, delta as u16),
+				point_lparam(x, y),
+			),
+			Message::SetFocus { previous } => (message::Setfocus, previous as WPARAM, 0),
+			Message::KillFocus { next } => (message::KillFocus, next as WPARAM, 0),
+			Message::Timer { id, callback } => (message::Timer, id, callback),
+			Message::SettingChange { flag, area } => (message::Settingchange, flag, area),
+			Message::Other {
+				message,
+				wparam,
+				lparam,
+			} => (message, wparam, lparam),
+		}
+	}
+}
+
+/// Name of the setting area of a `WM_SETTINGCHANGE`, `None` if lparam is null.
+///
+/// # Safety
+///
+/// `area` must be the lparam of a `WM_SETTINGCHANGE` message currently being handled.
+pub unsafe fn setting_area(area: LPARAM) -> Option<String> {
+	let ptr = area as *const u16;
+	if ptr.is_null() {
+		return None;
+	}
+	let mut len = 0;
+	while *ptr.add(len) != 0 {
+		len += 1;
+	}
+	Some(String::from_utf16_lossy(std::slice::from_raw_parts(
+		ptr, len,
+	)))
+}
+
+impl MouseButtons {
+	fn decode(wparam: WPARAM) -> Self {
+		let flags = wparam as u32;
+		Self {
+			left: flags & MK_LBUTTON != 0,
+			right: flags & MK_RBUTTON != 0,
+			middle: flags & MK_MBUTTON != 0,
+			x1: flags & MK_XBUTTON1 != 0,
+			x2: flags & MK_XBUTTON2 != 0,
+			shift: flags & MK_SHIFT != 0,
+			control: flags & MK_CONTROL != 0,
+		}
+	}
+
+	fn encode(&self) -> WPARAM {
+		let flags = [
+			(self.left, MK_LBUTTON),
+			(self.right, MK_RBUTTON),
+			(self.middle, MK_MBUTTON),
+			(self.x1, MK_XBUTTON1),
+			(self.x2, MK_XBUTTON2),
+			(self.shift, MK_SHIFT),
+			(self.control, MK_CONTROL),
+		];
+		flags
+			.iter()
+			.filter(|(set, _)| *set)
+			.fold(0, |acc, (_, flag)| acc | *flag as WPARAM)
+	}
+}
+
+// keystroke flags of the lparam of keyboard messages
+#[derive(Default)]
+struct KeyBits {
+	repeat: u16,
+	scan: u8,
+	extended: bool,
+	// context code, ALT was down
+	alt: bool,
+	previous_down: bool,
+	// transition state
+	released: bool,
+}
+
+impl KeyBits {
+	fn decode(lparam: u32) -> Self {
+		Self {
+			repeat: lparam as u16,
+			scan: (lparam >> 16) as u8,
+			extended: lparam & (1 << 24) != 0,
+			alt: lparam & (1 << 29) != 0,
+			previous_down: lparam & (1 << 30) != 0,
+			released: lparam & (1 << 31) != 0,
+		}
+	}
+
+	fn encode(&self) -> LPARAM {
+		let mut bits = u32::from(self.repeat) | u32::from(self.scan) << 16;
+		for (set, bit) in [
+			(self.extended, 24),
+			(self.alt, 29),
+			(self.previous_down, 30),
+			(self.released, 31),
+		] {
+			if set {
+				bits |= 1 << bit;
+			}
+		}
+		bits as LPARAM
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn encode_key(
+	message: message::Type,
+	vk: u16,
+	repeat: u16,
+	scan: u8,
+	extended: bool,
+	alt: bool,
+	previous_down: bool,
+) -> (message::Type, WPARAM, LPARAM) {
+	let key = KeyBits {
+		repeat,
+		scan,
+		extended,
+		alt,
+		previous_down,
+		// up messages are the only ones with the key released
+		released: message == message::KeyUp || message == message::Syskeyup,
+	};
+	(message, vk.into(), key.encode())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ButtonEvent {
+	Down,
+	Up,
+	DoubleClick,
+}
+
+const BUTTON_MESSAGES: [(message::Type, ButtonEvent, MouseButton); 9] = [
+	(message::LButtonDown, ButtonEvent::Down, MouseButton::Left),
+	(message::LButtonUp, ButtonEvent::Up, MouseButton::Left),
+	(
+		message::LButtonDblclk,
+		ButtonEvent::DoubleClick,
+		MouseButton::Left,
+	),
+	(message::Rbuttondown, ButtonEvent::Down, MouseButton::Right),
+	(message::Rbuttonup, ButtonEvent::Up, MouseButton::Right),
+	(
+		message::Rbuttondblclk,
+		ButtonEvent::DoubleClick,
+		MouseButton::Right,
+	),
+	(message::MButtonDown, ButtonEvent::Down, MouseButton::Middle),
+	(message::MButtonUp, ButtonEvent::Up, MouseButton::Middle),
+	(
+		message::MButtonDblclk,
+		ButtonEvent::DoubleClick,
+		MouseButton::Middle,
+	),
+];
+
+const X_BUTTON_MESSAGES: [(message::Type, ButtonEvent); 3] = [
+	(message::Xbuttondown, ButtonEvent::Down),
+	(message::XBUTTONUP, ButtonEvent::Up),
+	(message::Xbuttondblclk, ButtonEvent::DoubleClick),
+];
+
+fn mouse_button(message: message::Type, wparam: WPARAM) -> Option<(ButtonEvent, MouseButton)> {
+	if let Some((_, event, button)) = BUTTON_MESSAGES.into_iter().find(|(m, ..)| *m == message) {
+		// the high word is unused for these buttons
+		return Some((event, button));
+	}
+	let (_, event) = X_BUTTON_MESSAGES.into_iter().find(|(m, _)| *m == message)?;
+	let button = match hiword(wparam) as u32 {
+		XBUTTON1 => MouseButton::X1,
+		XBUTTON2 => MouseButton::X2,
+		_ => return None,
+	};
+	Some((event, button))
+}
+
+fn encode_button(
+	event: ButtonEvent,
+	button: MouseButton,
+	x: i16,
+	y: i16,
+	buttons: MouseButtons,
+) -> (message::Type, WPARAM, LPARAM) {
+	let lparam = point_lparam(x, y);
+	let flags = buttons.encode() as u16;
+	let x_button = match button {
+		MouseButton::X1 => XBUTTON1,
+		MouseButton::X2 => XBUTTON2,
+		_ => {
+			let (message, ..) = BUTTON_MESSAGES
+				.into_iter()
+				.find(|(_, e, b)| *b == button && *e == event)
+				.expect("all buttons and events have a message");
+			return (message, flags.into(), lparam);
+		}
+	};
+	let (message, _) = X_BUTTON_MESSAGES
+		.into_iter()
+		.find(|(_, e)| *e == event)
+		.expect("all events have a message");
+	(message, make_wparam(flags, x_button as u16), lparam)
+}
+
+fn make_wparam(low: u16, high: u16) -> WPARAM {
+	(u32::from(high) << 16 | u32::from(low)) as WPARAM
+}
+
+fn make_lparam(low: u16, high: u16) -> LPARAM {
+	// MAKELPARAM zero-extends the DWORD it builds
+	(u32::from(high) << 16 | u32::from(low)) as LPARAM
+}
+
+fn point_lparam(x: i16, y: i16) -> LPARAM {
+	make_lparam(x as u16, y as u16)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn buttons() -> MouseButtons {
+		MouseButtons {
+			left: true,
+			x2: true,
+			control: true,
+			..Default::default()
+		}
+	}
+
+	// one of each variant, with fields away from their defaults
+	fn every_message() -> Vec<Message> {
+		let mut messages = vec![
+			Message::Close,
+			Message::Destroy,
+			Message::Quit { exit_code: 3 },
+			Message::Paint,
+			Message::Move { x: -20, y: 300 },
+			Message::Command {
+				id: 0x8001,
+				code: 1,
+				hwnd: 0x1234,
+			},
+			Message::KeyDown {
+				vk: 0x41,
+				repeat: 3,
+				scan: 0x1e,
+				extended: true,
+				previous_down: true,
+			},
+			Message::KeyUp {
+				vk: 0x41,
+				scan: 0x1e,
+				extended: false,
+			},
+			Message::SysKeyDown {
+				vk: 0x73,
+				repeat: 1,
+				scan: 0x3e,
+				extended: false,
+				alt: true,
+				previous_down: false,
+			},
+			Message::SysKeyUp {
+				vk: 0x12,
+				scan: 0x38,
+				extended: true,
+				alt: true,
+			},
+			Message::MouseMove {
+				x: -1,
+				y: 42,
+				buttons: buttons(),
+			},
+			Message::MouseWheel {
+				delta: -120,
+				x: 500,
+				y: -30,
+				buttons: buttons(),
+			},
+			Message::MouseHWheel {
+				delta: 240,
+				x: 0,
+				y: 1,
+				buttons: MouseButtons::default(),
+			},
+			Message::SetFocus { previous: 0x10 },
+			Message::KillFocus { next: 0 },
+			Message::Timer {
+				id: 0x1001,
+				callback: 0,
+			},
+			Message::SettingChange {
+				flag: 0,
+				area: 0x5000,
+			},
+			Message::Other {
+				message: message::Null,
+				wparam: 7,
+				lparam: -7,
+			},
+		];
+		for kind in [
+			SizeKind::Restored,
+			SizeKind::Minimized,
+			SizeKind::Maximized,
+			SizeKind::MaxShow,
+			SizeKind::MaxHide,
+		] {
+			messages.push(Message::Size {
+				width: 640,
+				height: 480,
+				kind,
+			});
+		}
+		for (alt, previous_down, released) in [
+			(false, false, false),
+			(false, true, false),
+			(true, false, false),
+			(true, true, true),
+		] {
+			messages.push(Message::Char {
+				code: 'é' as u16,
+				repeat: 2,
+				scan: 0x12,
+				extended: false,
+				alt,
+				previous_down,
+				released,
+			});
+		}
+		for button in [
+			MouseButton::Left,
+			MouseButton::Right,
+			MouseButton::Middle,
+			MouseButton::X1,
+			MouseButton::X2,
+		] {
+			let (x, y, buttons) = (10, -10, buttons());
+			messages.push(Message::MouseDown {
+				button,
+				x,
+				y,
+				buttons,
+			});
+			messages.push(Message::MouseUp {
+				button,
+				x,
+				y,
+				buttons,
+			});
+			messages.push(Message::MouseDoubleClick {
+				button,
+				x,
+				y,
+				buttons,
+			});
+		}
+		messages
+	}
+
+	#[test]
+	fn round_trip() {
+		for message in every_message() {
+			let (m, wparam, lparam) = message.encode();
+			assert_eq!(Message::decode(m, wparam, lparam), message);
+		}
+	}
+
+	// parameters with bits set that no field keeps
+	fn undocumented_bits() -> Vec<(message::Type, WPARAM, LPARAM)> {
+		vec![
+			// released flag on a key down
+			(message::KeyDown, 0x41, 0x801e_0001_u32 as LPARAM),
+			// reserved keystroke bits
+			(message::KeyDown, 0x41, 0x021e_0001),
+			(message::Syskeyup, 0x12, 0x1c38_0001),
+			(message::Char, 'a' as WPARAM, 0x021e_0001),
+			// context code of a key up
+			(message::KeyUp, 0x41, 0xe01e_0001_u32 as LPARAM),
+			// high word of a left button message
+			(message::LButtonDown, 0x1_0000, 0),
+			// unknown key state flags
+			(message::MouseMove, 0x8000, 0),
+		]
+	}
+
+	#[test]
+	fn undocumented_bits_are_masked() {
+		let decoded = undocumented_bits()
+			.into_iter()
+			.map(|(message, wparam, lparam)| Message::decode(message, wparam, lparam))
+			.collect::<Vec<_>>();
+		assert!(matches!(
+			decoded[0],
+			Message::KeyDown {
+				vk: 0x41,
+				repeat: 1,
+				scan: 0x1e,
+				..
+			}
+		));
+		assert!(matches!(decoded[1], Message::KeyDown { scan: 0x1e, .. }));
+		assert!(matches!(
+			decoded[2],
+			Message::SysKeyUp {
+				extended: false,
+				alt: false,
+				..
+			}
+		));
+		assert!(matches!(decoded[3], Message::Char { code: 0x61, .. }));
+		assert!(matches!(decoded[4], Message::KeyUp { scan: 0x1e, .. }));
+		assert!(matches!(
+			decoded[5],
+			Message::MouseDown {
+				button: MouseButton::Left,
+				..
+			}
+		));
+		assert_eq!(
+			decoded[6],
+			Message::MouseMove {
+				x: 0,
+				y: 0,
+				buttons: MouseButtons::default(),
+			}
+		);
+
+		// encoded without them, and decoded back the same
+		for (message, decoded) in undocumented_bits().into_iter().zip(decoded) {
+			let encoded = decoded.encode();
+			assert_ne!(encoded, message);
+			assert_eq!(Message::decode(encoded.0, encoded.1, encoded.2), decoded);
+		}
+	}
+
+	#[test]
+	fn char_flags() {
+		// auto-repeated 'a', A key held down
+		let repeat = Message::decode(message::Char, 'a' as WPARAM, 0x401e_0001);
+		assert_eq!(
+			repeat,
+			Message::Char {
+				code: 'a' as u16,
+				repeat: 1,
+				scan: 0x1e,
+				extended: false,
+				alt: false,
+				previous_down: true,
+				released: false,
+			}
+		);
+		assert_eq!(repeat.encode(), (message::Char, 'a' as WPARAM, 0x401e_0001));
+
+		// typed with ALT+numpad, ALT still down
+		let alt = Message::decode(message::Char, 'é' as WPARAM, 0x2038_0001);
+		assert!(matches!(
+			alt,
+			Message::Char {
+				alt: true,
+				previous_down: false,
+				..
+			}
+		));
+		assert_eq!(alt.encode(), (message::Char, 'é' as WPARAM, 0x2038_0001));
+	}
+
+	#[test]
+	fn key_flags() {
+		let repeat = Message::decode(message::KeyDown, 0x41, 0x401e_0005);
+		assert!(matches!(
+			repeat,
+			Message::KeyDown {
+				repeat: 5,
+				previous_down: true,
+				..
+			}
+		));
+		let up = Message::decode(message::KeyUp, 0x41, 0xc01e_0001_u32 as LPARAM);
+		assert!(matches!(up, Message::KeyUp { scan: 0x1e, .. }));
+		let alt = Message::decode(message::Syskeydown, 0x73, 0x203e_0001);
+		assert!(matches!(alt, Message::SysKeyDown { alt: true, .. }));
+	}
+
+	#[test]
+	fn unknown_values_are_other() {
+		let cases = [
+			// unknown size kind
+			(message::Size, 9, 0),
+			// not an X button
+			(message::Xbuttondown, 0x3_0000, 0),
+		];
+		for (message, wparam, lparam) in cases {
+			assert_eq!(
+				Message::decode(message, wparam, lparam),
+				Message::Other {
+					message,
+					wparam,
+					lparam
+				}
+			);
+		}
+	}
+}
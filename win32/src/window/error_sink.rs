@@ -0,0 +1,126 @@
+// Errors and panics of window handlers can't cross `win_proc`, they are reported to the thread's `ErrorSink` instead.
+
+use std::{any::Any, cell::RefCell, fmt, rc::Rc};
+
+use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::PostQuitMessage};
+
+use crate::{assert::Error, err_display, message_box};
+
+use super::message;
+
+/// Error returned, or panic raised, by a `WindowHandler` while handling a message.
+#[derive(Debug)]
+pub struct HandlerError {
+	pub h_window: HWND,
+	pub message: message::Type,
+	pub error: Error,
+}
+
+impl fmt::Display for HandlerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"window {:#X} failed to handle message {:#X}: {}",
+			self.h_window, self.message, self.error
+		)
+	}
+}
+
+impl std::error::Error for HandlerError {}
+
+pub trait ErrorSink {
+	fn report(&self, error: HandlerError);
+}
+
+impl<F: Fn(HandlerError)> ErrorSink for F {
+	fn report(&self, error: HandlerError) {
+		self(error)
+	}
+}
+
+/// Print errors to stderr and keep the event loop running.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogErrors;
+
+impl ErrorSink for LogErrors {
+	fn report(&self, error: HandlerError) {
+		err_display!("{}", error);
+	}
+}
+
+/// Show errors in a message box and keep the event loop running.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessageBoxErrors;
+
+impl ErrorSink for MessageBoxErrors {
+	fn report(&self, error: HandlerError) {
+		let style = message_box::style::Ok | message_box::style::IconHand;
+		if let Err(e) = message_box::new(&error.to_string(), "Error", style) {
+			err_display!("{} (message box failed: {})", error, e);
+		}
+	}
+}
+
+/// Quit the event loop with `exit_code` on the first error, `event_loop` then returns the error. The default sink.
+#[derive(Debug, Clone, Copy)]
+pub struct QuitOnError {
+	pub exit_code: i32,
+}
+
+impl Default for QuitOnError {
+	fn default() -> Self {
+		Self { exit_code: 1 }
+	}
+}
+
+impl ErrorSink for QuitOnError {
+	fn report(&self, error: HandlerError) {
+		PENDING_ERROR.with(|pending| {
+			let mut pending = pending.borrow_mut();
+			match *pending {
+				// the loop is already quitting because of a previous error
+				Some(_) => err_display!("{}", error),
+				None => {
+					*pending = Some(error);
+					unsafe { PostQuitMessage(self.exit_code) };
+				}
+			}
+		});
+	}
+}
+
+thread_local! {
+	static ERROR_SINK: RefCell<Rc<dyn ErrorSink>> = RefCell::new(Rc::new(QuitOnError::default()));
+	// error that made QuitOnError quit the loop, returned by event_loop
+	static PENDING_ERROR: RefCell<Option<HandlerError>> = const { RefCell::new(None) };
+}
+
+/// Replace the sink receiving the errors of the window handlers running on this thread.
+pub fn set_error_sink(sink: impl ErrorSink + 'static) {
+	ERROR_SINK.with(|s| *s.borrow_mut() = Rc::new(sink));
+}
+
+pub(crate) fn report_error(h_window: HWND, message: message::Type, error: Error) {
+	// cloned out, the sink may run a nested message loop, e.g. a message box
+	let sink = ERROR_SINK.with(|s| s.borrow().clone());
+	sink.report(HandlerError {
+		h_window,
+		message,
+		error,
+	});
+}
+
+pub(crate) fn take_pending_error() -> Option<HandlerError> {
+	PENDING_ERROR.with(|pending| pending.borrow_mut().take())
+}
+
+pub(crate) fn panic_error(payload: Box<dyn Any + Send>) -> Error {
+	let msg = match payload.downcast::<String>() {
+		Ok(msg) => *msg,
+		Err(payload) => match payload.downcast::<&str>() {
+			Ok(msg) => (*msg).to_owned(),
+			Err(_) => "unknown panic payload".to_owned(),
+		},
+	};
+	format!("handler panicked: {}", msg).into()
+}
@@ -0,0 +1,182 @@
+// Parameters of the typed `WindowHandler` hooks, built from decoded messages.
+
+use std::cell::Cell;
+
+use super::{Message, MouseButton, MouseButtons};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+	/// Virtual-key code, e.g. `VK_RETURN`.
+	pub vk: u16,
+	pub repeat: u16,
+	pub scan: u8,
+	pub extended: bool,
+	// the key was already down, the event is an auto-repeat
+	pub previous_down: bool,
+	// WM_SYSKEYDOWN/WM_SYSKEYUP, the key was pressed with ALT down or is F10
+	pub system: bool,
+}
+
+impl KeyEvent {
+	pub fn from_message(message: &Message) -> Option<Self> {
+		let event = match *message {
+			Message::KeyDown {
+				vk,
+				repeat,
+				scan,
+				extended,
+				previous_down,
+			} => Self {
+				vk,
+				repeat,
+				scan,
+				extended,
+				previous_down,
+				system: false,
+			},
+			Message::SysKeyDown {
+				vk,
+				repeat,
+				scan,
+				extended,
+				previous_down,
+				..
+			} => Self {
+				vk,
+				repeat,
+				scan,
+				extended,
+				previous_down,
+				system: true,
+			},
+			Message::KeyUp { vk, scan, extended } => Self {
+				vk,
+				repeat: 1,
+				scan,
+				extended,
+				previous_down: true,
+				system: false,
+			},
+			Message::SysKeyUp {
+				vk, scan, extended, ..
+			} => Self {
+				vk,
+				repeat: 1,
+				scan,
+				extended,
+				previous_down: true,
+				system: true,
+			},
+			_ => return None,
+		};
+		Some(event)
+	}
+}
+
+/// Mouse position relative to the client area, `button` is the one pressed or released, `None` for moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+	pub button: Option<MouseButton>,
+	pub x: i16,
+	pub y: i16,
+	pub buttons: MouseButtons,
+}
+
+impl MouseEvent {
+	pub fn from_message(message: &Message) -> Option<Self> {
+		let event = match *message {
+			Message::MouseMove { x, y, buttons } => Self {
+				button: None,
+				x,
+				y,
+				buttons,
+			},
+			Message::MouseDown {
+				button,
+				x,
+				y,
+				buttons,
+			}
+			| Message::MouseUp {
+				button,
+				x,
+				y,
+				buttons,
+			}
+			| Message::MouseDoubleClick {
+				button,
+				x,
+				y,
+				buttons,
+			} => Self {
+				button: Some(button),
+				x,
+				y,
+				buttons,
+			},
+			_ => return None,
+		};
+		Some(event)
+	}
+}
+
+/// Wheel rotation in multiples of `WHEEL_DELTA`. The position is in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WheelEvent {
+	pub delta: i16,
+	pub horizontal: bool,
+	pub x: i16,
+	pub y: i16,
+	pub buttons: MouseButtons,
+}
+
+impl WheelEvent {
+	pub fn from_message(message: &Message) -> Option<Self> {
+		let (delta, horizontal, x, y, buttons) = match *message {
+			Message::MouseWheel {
+				delta,
+				x,
+				y,
+				buttons,
+			} => (delta, false, x, y, buttons),
+			Message::MouseHWheel {
+				delta,
+				x,
+				y,
+				buttons,
+			} => (delta, true, x, y, buttons),
+			_ => return None,
+		};
+		Some(Self {
+			delta,
+			horizontal,
+			x,
+			y,
+			buttons,
+		})
+	}
+}
+
+thread_local! {
+	// high surrogate of a character sent as two WM_CHAR messages
+	static HIGH_SURROGATE: Cell<Option<u16>> = const { Cell::new(None) };
+}
+
+/// Character typed for a `WM_CHAR` code unit. Characters outside of the BMP come as a surrogate pair: `None` is
+/// returned for the first half, and the whole character with the second one.
+pub fn char_from_code_unit(code: u16) -> Option<char> {
+	HIGH_SURROGATE.with(|high| match code {
+		0xD800..=0xDBFF => {
+			high.set(Some(code));
+			None
+		}
+		0xDC00..=0xDFFF => {
+			let first = high.take()?;
+			char::decode_utf16([first, code]).next()?.ok()
+		}
+		_ => {
+			high.set(None);
+			char::from_u32(code.into())
+		}
+	})
+}
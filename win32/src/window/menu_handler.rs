@@ -0,0 +1,79 @@
+// Menu commands of window handlers. `set_menu_bar` keeps a route per window from the ids of its menu items to
+// `MenuHandler::on_menu`, which win_proc follows on WM_COMMAND.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+
+use super::{MessageAction, WindowHandler};
+use crate::{
+	assert::Result,
+	hiword, loword,
+	menu::{Menu, MenuBar},
+};
+
+/// Window handler with a menu bar sending commands of type `Command`.
+///
+/// ```ignore
+/// impl MenuHandler for App {
+///     type Command = Cmd;
+///
+///     fn on_menu(&mut self, cmd: Cmd) -> Result<MessageAction> {
+///         match cmd {
+///             Cmd::Save => self.save()?,
+///             Cmd::About => self.show_about()?,
+///         }
+///         Ok(MessageAction::FullyHandled)
+///     }
+/// }
+/// ```
+pub trait MenuHandler: WindowHandler {
+	type Command: Clone + PartialEq + 'static;
+
+	/// Called with the command of the menu item clicked, instead of `on_command`.
+	fn on_menu(&mut self, cmd: Self::Command) -> Result<MessageAction>;
+
+	/// Make `bar` the menu bar of the window, its commands going to `on_menu`. The returned handle checks and enables
+	/// items at runtime.
+	fn set_menu_bar(&self, bar: MenuBar<Self::Command>) -> Result<Menu<Self::Command>> {
+		let h_window = self.h_window();
+		let menu = bar.attach(h_window)?;
+		let commands = menu.clone();
+		let route: MenuRoute = Rc::new(move |handler: *mut (), id: u16| {
+			let cmd = commands.command(id)?.clone();
+			Some(unsafe { &mut *(handler as *mut Self) }.on_menu(cmd))
+		});
+		MENUS.with(|menus| menus.borrow_mut().insert(h_window, route));
+		Ok(menu)
+	}
+}
+
+// gets a pointer to the window handler and the id of the item, None if the id isn't one of the menu's
+type MenuRoute = Rc<dyn Fn(*mut (), u16) -> Option<Result<MessageAction>>>;
+
+thread_local! {
+	static MENUS: RefCell<BTreeMap<HWND, MenuRoute>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Call `on_menu` for a `WM_COMMAND` sent by a menu item or its shortcut, `None` if it isn't one, e.g. a control
+/// notification.
+pub(crate) unsafe fn run(
+	h_window: HWND,
+	wparam: WPARAM,
+	lparam: LPARAM,
+	handler: *mut (),
+) -> Option<Result<MessageAction>> {
+	// menus send a hiword of 0 and accelerators 1, controls send their window and a notification code
+	if lparam != 0 || hiword(wparam) > 1 {
+		return None;
+	}
+	let route = MENUS.with(|menus| menus.borrow().get(&h_window).cloned())?;
+	// not borrowed while the handler runs, it may set another menu bar
+	route(handler, loword(wparam) as u16)
+}
+
+/// Drop the route of a destroyed window, Windows already destroyed its menu.
+pub(crate) fn close(h_window: HWND) {
+	let route = MENUS.with(|menus| menus.borrow_mut().remove(&h_window));
+	drop(route);
+}
@@ -0,0 +1,163 @@
+// Ways to run the thread's message queue: blocking until WM_QUIT, as a game loop, until a condition, or only what's
+// already queued.
+
+use std::fmt;
+
+use windows::Win32::UI::WindowsAndMessaging::{
+	DispatchMessageW, GetMessageW, PeekMessageW, PostQuitMessage, TranslateMessage, MSG, PM_REMOVE,
+	WM_QUIT,
+};
+
+use super::take_pending_error;
+use crate::{accelerator, assert::Result, class, dialog};
+
+/// Exit code given to `PostQuitMessage`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExitCode(pub i32);
+
+impl ExitCode {
+	pub const SUCCESS: ExitCode = ExitCode(0);
+
+	pub fn is_success(self) -> bool {
+		self == Self::SUCCESS
+	}
+
+	/// End the thread's message loop with this code.
+	pub fn post(self) {
+		unsafe { PostQuitMessage(self.0) };
+	}
+}
+
+impl fmt::Display for ExitCode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(&self.0, f)
+	}
+}
+
+impl fmt::UpperHex for ExitCode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::UpperHex::fmt(&self.0, f)
+	}
+}
+
+/// Called for each message before `TranslateMessage`, returns true when it handled the message, which then isn't
+/// translated nor dispatched.
+pub type MessageFilter = Box<dyn FnMut(&MSG) -> bool>;
+
+/// ```ignore
+/// let exit_code = MessageLoop::new()
+///     .filter(move |msg| unsafe { IsDialogMessageW(h_find_dialog, msg) }.as_bool())
+///     .run()?;
+/// ```
+pub struct MessageLoop {
+	filters: Vec<MessageFilter>,
+}
+
+impl Default for MessageLoop {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MessageLoop {
+	/// Loop handling the shortcuts of accelerator tables, see `AcceleratorTable::attach`, and the keyboard navigation
+	/// of modeless dialogs, see `dialog::Dialog::show_modeless`.
+	pub fn new() -> Self {
+		Self::without_filters()
+			.filter(accelerator::is_accelerator_message)
+			.filter(dialog::is_modeless_dialog_message)
+	}
+
+	pub fn without_filters() -> Self {
+		Self { filters: vec![] }
+	}
+
+	/// Filters run in the order they are added, the first one handling a message stops the others.
+	pub fn filter(mut self, filter: impl FnMut(&MSG) -> bool + 'static) -> Self {
+		self.filters.push(Box::new(filter));
+		self
+	}
+
+	/// Wait for messages and dispatch them until `WM_QUIT`. Fails with the handler error that ended the loop, see
+	/// `QuitOnError`.
+	pub fn run(&mut self) -> Result<ExitCode> {
+		let mut msg: MSG = Default::default();
+		loop {
+			match unsafe { GetMessageW(&mut msg, 0, 0, 0) }.0 {
+				0 => return quit(&msg),
+				-1 => return Err("GetMessageW failed".into()),
+				_ => self.dispatch(&msg),
+			}
+		}
+	}
+
+	/// Game loop: dispatch the queued messages, then call `on_idle` and start over without waiting. `on_idle` runs
+	/// continuously, e.g. to render a frame, and ends the loop when it fails.
+	pub fn run_with_idle(&mut self, mut on_idle: impl FnMut() -> Result<()>) -> Result<ExitCode> {
+		let mut msg: MSG = Default::default();
+		loop {
+			while unsafe { PeekMessageW(&mut msg, 0, 0, 0, PM_REMOVE) }.as_bool() {
+				if msg.message == WM_QUIT {
+					return quit(&msg);
+				}
+				self.dispatch(&msg);
+			}
+			on_idle()?;
+		}
+	}
+
+	/// Wait for messages and dispatch them until `done` returns true, checked before each wait. Meant to be nested in
+	/// another loop, e.g. for a modal window: `WM_QUIT` is posted again for the outer loop, and its code returned.
+	pub fn run_until(&mut self, mut done: impl FnMut() -> bool) -> Result<Option<ExitCode>> {
+		let mut msg: MSG = Default::default();
+		while !done() {
+			match unsafe { GetMessageW(&mut msg, 0, 0, 0) }.0 {
+				0 => return Ok(Some(repost_quit(&msg))),
+				-1 => return Err("GetMessageW failed".into()),
+				_ => self.dispatch(&msg),
+			}
+		}
+		Ok(None)
+	}
+
+	/// Dispatch the messages already queued and return without waiting, e.g. in tests. As with `run_until`, a
+	/// `WM_QUIT` is posted again and its code returned.
+	pub fn pump_pending(&mut self) -> Option<ExitCode> {
+		let mut msg: MSG = Default::default();
+		while unsafe { PeekMessageW(&mut msg, 0, 0, 0, PM_REMOVE) }.as_bool() {
+			if msg.message == WM_QUIT {
+				return Some(repost_quit(&msg));
+			}
+			self.dispatch(&msg);
+		}
+		None
+	}
+
+	fn dispatch(&mut self, msg: &MSG) {
+		if self.filters.iter_mut().any(|filter| filter(msg)) {
+			return;
+		}
+		unsafe {
+			TranslateMessage(msg);
+			DispatchMessageW(msg);
+		}
+	}
+}
+
+fn exit_code(msg: &MSG) -> ExitCode {
+	ExitCode(msg.wParam as i32)
+}
+
+fn quit(msg: &MSG) -> Result<ExitCode> {
+	class::release_destroyed_windows();
+	match take_pending_error() {
+		Some(e) => Err(e.into()),
+		None => Ok(exit_code(msg)),
+	}
+}
+
+fn repost_quit(msg: &MSG) -> ExitCode {
+	let exit_code = exit_code(msg);
+	exit_code.post();
+	exit_code
+}
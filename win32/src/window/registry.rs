@@ -0,0 +1,87 @@
+// Top-level windows alive on the UI thread, used to decide when the app quits.
+
+use std::cell::RefCell;
+
+use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::PostQuitMessage};
+
+/// When a `WindowHandler` app posts `WM_QUIT`, ending its `event_loop`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuitPolicy {
+	/// Quit once no top-level window is left.
+	#[default]
+	LastWindowClosed,
+	/// Quit when this window is destroyed, even if others are still open.
+	WindowClosed(HWND),
+	/// Never quit on its own, the app calls `PostQuitMessage`.
+	Manual,
+}
+
+#[derive(Debug, Default)]
+pub struct WindowRegistry {
+	windows: Vec<HWND>,
+	policy: QuitPolicy,
+}
+
+impl WindowRegistry {
+	pub fn add(&mut self, h_window: HWND) {
+		if !self.windows.contains(&h_window) {
+			self.windows.push(h_window);
+		}
+	}
+
+	/// Forget a destroyed window. Returns whether the app should quit according to the policy.
+	pub fn remove(&mut self, h_window: HWND) -> bool {
+		let len = self.windows.len();
+		self.windows.retain(|&w| w != h_window);
+		if self.windows.len() == len {
+			return false;
+		}
+		match self.policy {
+			QuitPolicy::LastWindowClosed => self.windows.is_empty(),
+			QuitPolicy::WindowClosed(main) => main == h_window,
+			QuitPolicy::Manual => false,
+		}
+	}
+
+	pub fn windows(&self) -> &[HWND] {
+		&self.windows
+	}
+
+	pub fn policy(&self) -> QuitPolicy {
+		self.policy
+	}
+
+	pub fn set_policy(&mut self, policy: QuitPolicy) {
+		self.policy = policy;
+	}
+}
+
+thread_local! {
+	// windows belong to the thread that created them, so does the registry
+	static REGISTRY: RefCell<WindowRegistry> = RefCell::new(WindowRegistry::default());
+}
+
+pub fn set_quit_policy(policy: QuitPolicy) {
+	REGISTRY.with(|r| r.borrow_mut().set_policy(policy));
+}
+
+pub fn quit_policy() -> QuitPolicy {
+	REGISTRY.with(|r| r.borrow().policy())
+}
+
+/// Top-level windows of `WindowHandler`s still open on this thread.
+pub fn open_windows() -> Vec<HWND> {
+	REGISTRY.with(|r| r.borrow().windows().to_vec())
+}
+
+pub(crate) fn register_window(h_window: HWND) {
+	REGISTRY.with(|r| r.borrow_mut().add(h_window));
+}
+
+// posts WM_QUIT when required by the quit policy
+pub(crate) fn unregister_window(h_window: HWND) {
+	let quit = REGISTRY.with(|r| r.borrow_mut().remove(h_window));
+	if quit {
+		unsafe { PostQuitMessage(0) };
+	}
+}
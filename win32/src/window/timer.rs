@@ -0,0 +1,314 @@
+// Timers of window handlers. `TimerTable` does the id allocation and bookkeeping without Win32, the functions below
+// pair it with SetTimer/KillTimer and WM_TIMER.
+
+use std::{cell::RefCell, collections::BTreeMap, marker::PhantomData, time::Duration};
+
+use windows::Win32::{
+	Foundation::HWND,
+	UI::WindowsAndMessaging::{KillTimer, SetTimer},
+};
+
+use crate::assert::{assert_ne, Result, WithLastWin32Error};
+
+/// Ids given to `SetTimer` by `WindowHandler::set_timer`, the ones below are free for `on_timer`.
+pub const TIMER_ID_START: usize = 0x1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(pub usize);
+
+#[derive(Debug)]
+struct TimerEntry<C> {
+	// None while the callback runs
+	callback: Option<C>,
+	once: bool,
+}
+
+/// Timers of one window, `C` being their callback.
+#[derive(Debug)]
+pub struct TimerTable<C> {
+	next_id: usize,
+	timers: BTreeMap<TimerId, TimerEntry<C>>,
+}
+
+impl<C> Default for TimerTable<C> {
+	fn default() -> Self {
+		Self {
+			next_id: TIMER_ID_START,
+			timers: BTreeMap::new(),
+		}
+	}
+}
+
+impl<C> TimerTable<C> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a timer with an id not used by the other timers of the table.
+	pub fn insert(&mut self, callback: C, once: bool) -> TimerId {
+		let id = loop {
+			let id = TimerId(self.next_id);
+			self.next_id = match self.next_id.checked_add(1) {
+				Some(next) => next,
+				None => TIMER_ID_START,
+			};
+			if !self.timers.contains_key(&id) {
+				break id;
+			}
+		};
+		let callback = Some(callback);
+		self.timers.insert(id, TimerEntry { callback, once });
+		id
+	}
+
+	/// Returns the callback of the removed timer, `None` if there was no such timer, e.g. a one-shot timer that
+	/// already fired, or if its callback is running.
+	pub fn remove(&mut self, id: TimerId) -> Option<C> {
+		self.timers.remove(&id)?.callback
+	}
+
+	pub fn contains(&self, id: TimerId) -> bool {
+		self.timers.contains_key(&id)
+	}
+
+	pub fn len(&self) -> usize {
+		self.timers.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.timers.is_empty()
+	}
+
+	/// Take the callback of a timer that elapsed, with whether it was a one-shot timer, which is removed. A repeating
+	/// timer gets its callback back with `restore`. `None` if the timer is unknown or its callback is already running.
+	pub fn fire(&mut self, id: TimerId) -> Option<(C, bool)> {
+		let entry = self.timers.get_mut(&id)?;
+		let callback = entry.callback.take()?;
+		let once = entry.once;
+		if once {
+			self.timers.remove(&id);
+		}
+		Some((callback, once))
+	}
+
+	/// Give back the callback of a repeating timer after it ran. The callback is returned if the timer was removed in
+	/// the meantime.
+	pub fn restore(&mut self, id: TimerId, callback: C) -> Option<C> {
+		match self.timers.get_mut(&id) {
+			Some(entry) if entry.callback.is_none() => {
+				entry.callback = Some(callback);
+				None
+			}
+			_ => Some(callback),
+		}
+	}
+}
+
+// gets a pointer to the window handler
+pub(crate) type TimerCallback = Box<dyn FnMut(*mut ())>;
+
+thread_local! {
+	static TIMERS: RefCell<BTreeMap<HWND, TimerTable<TimerCallback>>> = RefCell::new(BTreeMap::new());
+}
+
+pub(crate) fn start(
+	h_window: HWND,
+	interval: Duration,
+	callback: TimerCallback,
+	once: bool,
+) -> Result<TimerId> {
+	let id = TIMERS.with(|timers| {
+		let mut timers = timers.borrow_mut();
+		timers.entry(h_window).or_default().insert(callback, once)
+	});
+	let millis = interval.as_millis().try_into().unwrap_or(u32::MAX);
+	let res = assert_ne(
+		unsafe { SetTimer(h_window, id.0, millis, None) },
+		0,
+		"failed to set timer",
+	)
+	.with_last_win32_err();
+	if res.is_err() {
+		forget(h_window, id);
+	}
+	res.map(|_| id)
+}
+
+/// Returns false if the timer wasn't running.
+pub(crate) fn kill(h_window: HWND, id: TimerId) -> bool {
+	let removed = forget(h_window, id);
+	if removed {
+		unsafe { KillTimer(h_window, id.0) };
+	}
+	removed
+}
+
+fn forget(h_window: HWND, id: TimerId) -> bool {
+	let (removed, callback) = TIMERS.with(|timers| {
+		let mut timers = timers.borrow_mut();
+		match timers.get_mut(&h_window) {
+			Some(table) => (table.contains(id), table.remove(id)),
+			None => (false, None),
+		}
+	});
+	// dropped once the table isn't borrowed, the closure may own a `TimerGuard`
+	drop(callback);
+	removed
+}
+
+/// Run the callback of a `WM_TIMER`, returns false if the timer isn't one of `h_window`'s, e.g. a raw `SetTimer`.
+pub(crate) unsafe fn run(h_window: HWND, id: usize, handler: *mut ()) -> bool {
+	let id = TimerId(id);
+	let fired = TIMERS.with(|timers| {
+		let mut timers = timers.borrow_mut();
+		timers.get_mut(&h_window).and_then(|table| table.fire(id))
+	});
+	let (mut callback, once) = match fired {
+		Some(fired) => fired,
+		None => return false,
+	};
+	if once {
+		KillTimer(h_window, id.0);
+	}
+	// not borrowed while the callback runs, it may set or kill timers
+	callback(handler);
+	if !once {
+		let killed = TIMERS.with(|timers| match timers.borrow_mut().get_mut(&h_window) {
+			Some(table) => table.restore(id, callback),
+			None => Some(callback),
+		});
+		// killed by the callback, dropped once the table isn't borrowed
+		drop(killed);
+	}
+	true
+}
+
+/// Drop the timers of a destroyed window, Windows already killed them.
+pub(crate) fn close(h_window: HWND) {
+	let table = TIMERS.with(|timers| timers.borrow_mut().remove(&h_window));
+	drop(table);
+}
+
+/// Kills its timer when dropped.
+#[derive(Debug)]
+pub struct TimerGuard {
+	h_window: HWND,
+	id: TimerId,
+	// timers belong to the UI thread
+	thread: PhantomData<*const ()>,
+}
+
+impl TimerGuard {
+	pub fn new(h_window: HWND, id: TimerId) -> Self {
+		Self {
+			h_window,
+			id,
+			thread: PhantomData,
+		}
+	}
+
+	pub fn id(&self) -> TimerId {
+		self.id
+	}
+
+	/// Keep the timer running, returning its id.
+	pub fn forget(self) -> TimerId {
+		let id = self.id;
+		std::mem::forget(self);
+		id
+	}
+}
+
+impl Drop for TimerGuard {
+	fn drop(&mut self) {
+		kill(self.h_window, self.id);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ids() {
+		let mut table = TimerTable::new();
+		assert!(table.is_empty());
+		let a = table.insert("a", false);
+		let b = table.insert("b", true);
+		assert_eq!(
+			(a, b),
+			(TimerId(TIMER_ID_START), TimerId(TIMER_ID_START + 1))
+		);
+		assert_eq!(table.len(), 2);
+		assert!(table.contains(a) && table.contains(b));
+		assert!(!table.contains(TimerId(1)));
+	}
+
+	#[test]
+	fn cancel() {
+		let mut table = TimerTable::new();
+		let a = table.insert("a", false);
+		let b = table.insert("b", true);
+		assert_eq!(table.remove(a), Some("a"));
+		assert!(!table.contains(a));
+		assert_eq!(table.remove(a), None);
+		assert_eq!(table.fire(a), None);
+		// one-shot timer cancelled before it fired
+		assert_eq!(table.remove(b), Some("b"));
+		assert!(table.is_empty());
+	}
+
+	#[test]
+	fn once() {
+		let mut table = TimerTable::new();
+		let id = table.insert("once", true);
+		assert_eq!(table.fire(id), Some(("once", true)));
+		// removed when it fired, nothing to cancel or run again
+		assert!(!table.contains(id));
+		assert_eq!(table.fire(id), None);
+		assert_eq!(table.remove(id), None);
+		assert_eq!(table.restore(id, "once"), Some("once"));
+	}
+
+	#[test]
+	fn repeating() {
+		let mut table = TimerTable::new();
+		let id = table.insert("tick", false);
+		for _ in 0..3 {
+			let (callback, once) = table.fire(id).unwrap();
+			assert!(!once);
+			assert!(table.contains(id));
+			// a timer doesn't fire again while its callback runs
+			assert_eq!(table.fire(id), None);
+			assert_eq!(table.restore(id, callback), None);
+		}
+
+		// cancelled by its own callback, which is given back to be dropped
+		let (callback, _) = table.fire(id).unwrap();
+		assert_eq!(table.remove(id), None);
+		assert!(!table.contains(id));
+		assert_eq!(table.restore(id, callback), Some("tick"));
+
+		// restoring a timer that isn't running gives the callback back too
+		let id = table.insert("idle", false);
+		assert_eq!(table.restore(id, "other"), Some("other"));
+		assert_eq!(table.fire(id), Some(("idle", false)));
+	}
+
+	#[test]
+	fn reuse() {
+		let mut table = TimerTable::new();
+		let a = table.insert("a", false);
+		table.remove(a);
+		// ids aren't reused right away, a late WM_TIMER can't reach a new timer
+		let b = table.insert("b", false);
+		assert_ne!(a, b);
+
+		// until ids wrap around, skipping those still in use
+		table.next_id = usize::MAX;
+		assert_eq!(table.insert("last", false), TimerId(usize::MAX));
+		assert_eq!(table.insert("c", false), a);
+		assert_eq!(table.insert("d", false), TimerId(b.0 + 1));
+		assert_eq!(table.len(), 4);
+	}
+}